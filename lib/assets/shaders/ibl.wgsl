@@ -0,0 +1,189 @@
+const PI: f32 = 3.1415926535897932384626433832795;
+const SAMPLE_COUNT: u32 = 512u;
+
+struct Prefilter {
+    roughness: f32,
+}
+
+@group(0)
+@binding(0)
+var env: texture_cube<f32>;
+
+@group(0)
+@binding(1)
+var env_sampler: sampler;
+
+@group(0)
+@binding(2)
+var dst: texture_storage_2d_array<rgba16float, write>;
+
+// BRDF lookup doesn't sample the environment, so it has own layout
+@group(0)
+@binding(3)
+var lut: texture_storage_2d<rgba16float, write>;
+
+@group(1)
+@binding(0)
+var<uniform> prefilter: Prefilter;
+
+// Direction of the texel for the face order `+X`, `-X`, `+Y`, `-Y`, `+Z`, `-Z`
+fn cube_direction(face: u32, texel: vec2<u32>, size: vec2<u32>) -> vec3<f32> {
+    let uv = (vec2<f32>(texel) + 0.5) / vec2<f32>(size) * 2.0 - 1.0;
+
+    switch face {
+        case 0u: {
+            return normalize(vec3(1.0, -uv.y, -uv.x));
+        }
+        case 1u: {
+            return normalize(vec3(-1.0, -uv.y, uv.x));
+        }
+        case 2u: {
+            return normalize(vec3(uv.x, 1.0, uv.y));
+        }
+        case 3u: {
+            return normalize(vec3(uv.x, -1.0, -uv.y));
+        }
+        case 4u: {
+            return normalize(vec3(uv.x, -uv.y, 1.0));
+        }
+        default: {
+            return normalize(vec3(-uv.x, -uv.y, -1.0));
+        }
+    }
+}
+
+fn radical_inverse(index: u32) -> f32 {
+    var bits = (index << 16u) | (index >> 16u);
+    bits = ((bits & 0x55555555u) << 1u) | ((bits & 0xAAAAAAAAu) >> 1u);
+    bits = ((bits & 0x33333333u) << 2u) | ((bits & 0xCCCCCCCCu) >> 2u);
+    bits = ((bits & 0x0F0F0F0Fu) << 4u) | ((bits & 0xF0F0F0F0u) >> 4u);
+    bits = ((bits & 0x00FF00FFu) << 8u) | ((bits & 0xFF00FF00u) >> 8u);
+
+    return f32(bits) * 2.3283064365386963e-10;
+}
+
+fn hammersley(index: u32, count: u32) -> vec2<f32> {
+    return vec2(f32(index) / f32(count), radical_inverse(index));
+}
+
+fn importance_sample_ggx(xi: vec2<f32>, n: vec3<f32>, roughness: f32) -> vec3<f32> {
+    let a = roughness * roughness;
+
+    let phi = 2.0 * PI * xi.x;
+    let cos_theta = sqrt((1.0 - xi.y) / (1.0 + (a * a - 1.0) * xi.y));
+    let sin_theta = sqrt(1.0 - cos_theta * cos_theta);
+    let h = vec3(cos(phi) * sin_theta, sin(phi) * sin_theta, cos_theta);
+
+    let up = select(vec3(1.0, 0.0, 0.0), vec3(0.0, 0.0, 1.0), abs(n.z) < 0.999);
+    let tangent = normalize(cross(up, n));
+    let bitangent = cross(n, tangent);
+
+    return normalize(tangent * h.x + bitangent * h.y + n * h.z);
+}
+
+fn geometry_schlick_ggx(n_dot_v: f32, roughness: f32) -> f32 {
+    let k = roughness * roughness / 2.0;
+
+    return n_dot_v / (n_dot_v * (1.0 - k) + k);
+}
+
+@compute
+@workgroup_size(8, 8, 1)
+fn compute_irradiance(
+    @builtin(global_invocation_id)
+    gid: vec3<u32>,
+) {
+    let size = textureDimensions(dst);
+    if gid.x >= size.x || gid.y >= size.y {
+        return;
+    }
+
+    let n = cube_direction(gid.z, gid.xy, size);
+    let up = select(vec3(0.0, 1.0, 0.0), vec3(0.0, 0.0, 1.0), abs(n.y) > 0.999);
+    let right = normalize(cross(up, n));
+    let tangent_up = cross(n, right);
+
+    // Cosine weighted convolution over the hemisphere
+    let delta = 0.05;
+    var irradiance = vec3(0.0);
+    var count = 0.0;
+    for (var phi = 0.0; phi < 2.0 * PI; phi += delta) {
+        for (var theta = 0.0; theta < 0.5 * PI; theta += delta) {
+            let t = vec3(sin(theta) * cos(phi), sin(theta) * sin(phi), cos(theta));
+            let dir = t.x * right + t.y * tangent_up + t.z * n;
+
+            irradiance += textureSampleLevel(env, env_sampler, dir, 0.0).rgb * cos(theta) * sin(theta);
+            count += 1.0;
+        }
+    }
+
+    textureStore(dst, gid.xy, gid.z, vec4(PI * irradiance / count, 1.0));
+}
+
+@compute
+@workgroup_size(8, 8, 1)
+fn compute_prefiltered(
+    @builtin(global_invocation_id)
+    gid: vec3<u32>,
+) {
+    let size = textureDimensions(dst);
+    if gid.x >= size.x || gid.y >= size.y {
+        return;
+    }
+
+    // View and reflection directions are equal to the normal
+    let n = cube_direction(gid.z, gid.xy, size);
+    var color = vec3(0.0);
+    var weight = 0.0;
+    for (var i = 0u; i < SAMPLE_COUNT; i++) {
+        let h = importance_sample_ggx(hammersley(i, SAMPLE_COUNT), n, prefilter.roughness);
+        let l = normalize(2.0 * dot(n, h) * h - n);
+
+        let n_dot_l = dot(n, l);
+        if n_dot_l > 0.0 {
+            color += textureSampleLevel(env, env_sampler, l, 0.0).rgb * n_dot_l;
+            weight += n_dot_l;
+        }
+    }
+
+    textureStore(dst, gid.xy, gid.z, vec4(color / max(weight, 0.0001), 1.0));
+}
+
+@compute
+@workgroup_size(8, 8, 1)
+fn compute_brdf_lut(
+    @builtin(global_invocation_id)
+    gid: vec3<u32>,
+) {
+    let size = textureDimensions(lut);
+    if gid.x >= size.x || gid.y >= size.y {
+        return;
+    }
+
+    // `x` is the angle between the normal and the view, `y` is the roughness
+    let n_dot_v = (f32(gid.x) + 0.5) / f32(size.x);
+    let roughness = (f32(gid.y) + 0.5) / f32(size.y);
+
+    let v = vec3(sqrt(1.0 - n_dot_v * n_dot_v), 0.0, n_dot_v);
+    let n = vec3(0.0, 0.0, 1.0);
+    var scale = 0.0;
+    var bias = 0.0;
+    for (var i = 0u; i < SAMPLE_COUNT; i++) {
+        let h = importance_sample_ggx(hammersley(i, SAMPLE_COUNT), n, roughness);
+        let l = normalize(2.0 * dot(v, h) * h - v);
+
+        let n_dot_l = max(l.z, 0.0);
+        let n_dot_h = max(h.z, 0.0);
+        let v_dot_h = max(dot(v, h), 0.0);
+        if n_dot_l > 0.0 {
+            let g = geometry_schlick_ggx(n_dot_v, roughness) * geometry_schlick_ggx(n_dot_l, roughness);
+            let g_vis = g * v_dot_h / (n_dot_h * n_dot_v);
+            let fc = pow(1.0 - v_dot_h, 5.0);
+
+            scale += (1.0 - fc) * g_vis;
+            bias += fc * g_vis;
+        }
+    }
+
+    textureStore(lut, gid.xy, vec4(scale, bias, 0.0, 1.0) / vec4(f32(SAMPLE_COUNT), f32(SAMPLE_COUNT), 1.0, 1.0));
+}
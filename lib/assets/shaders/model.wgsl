@@ -5,6 +5,10 @@ struct Camera {
   inv_proj: mat4x4<f32>,
   inv_view: mat4x4<f32>,
 }
+struct Node {
+  model: mat4x4<f32>,
+  normal: mat3x3<f32>,
+}
 struct Light {
   position: vec3<f32>,
   color: vec3<f32>,
@@ -14,6 +18,8 @@ struct Light {
 var<uniform> light: Light;
 @group(2) @binding(0)
 var<uniform> camera: Camera;
+@group(3) @binding(0)
+var<uniform> node: Node;
 
 struct VertexInput {
   @location(0) position: vec3<f32>,
@@ -61,13 +67,13 @@ fn vs_main(
     instance.normal_matrix_1,
     instance.normal_matrix_2,
   );
-  let world_position = model_matrix * vec4<f32>(model.position, 1.0);
+  let world_position = model_matrix * node.model * vec4<f32>(model.position, 1.0);
 
   out.clip_position = camera.view_proj * world_position;
   out.tex_coords = model.tex_coords;
-  out.world_normal = normalize(normal_matrix * model.normal);
-  out.world_tangent = normalize(normal_matrix * model.tangent);
-  out.world_bitangent = normalize(normal_matrix * model.bitangent);
+  out.world_normal = normalize(normal_matrix * node.normal * model.normal);
+  out.world_tangent = normalize(normal_matrix * node.normal * model.tangent);
+  out.world_bitangent = normalize(normal_matrix * node.normal * model.bitangent);
   out.world_position = world_position.xyz;
   out.world_view_position = camera.view_pos.xyz;
   
@@ -84,6 +90,21 @@ var t_normal: texture_2d<f32>;
 @group(0) @binding(3)
 var s_normal: sampler;
 
+// Maps of `Ibl`, the prefiltered map has the roughness `level / 4.0`
+@group(4) @binding(0)
+var t_irradiance: texture_cube<f32>;
+@group(4) @binding(1)
+var t_prefiltered: texture_cube<f32>;
+@group(4) @binding(2)
+var t_brdf_lut: texture_2d<f32>;
+@group(4) @binding(3)
+var s_ibl: sampler;
+
+// Materials have no metallic-roughness map, so they're dielectrics with the constant roughness
+const ROUGHNESS: f32 = 0.5;
+const F0: vec3<f32> = vec3<f32>(0.04);
+const PREFILTERED_MAX_LEVEL: f32 = 4.0;
+
 @fragment
 fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
   let object_color: vec4<f32> = textureSample(t_diffuse, s_diffuse, in.tex_coords);
@@ -102,7 +123,7 @@ fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
       in.world_normal,
   );
   let tangent_normal = object_normal.xyz * 2.0 - 1.0;
-  let world_normal = TBN * tangent_normal;
+  let world_normal = normalize(TBN * tangent_normal);
   
   // Create the lighting vectors
   let light_dir = normalize(light.position - in.world_position);
@@ -115,16 +136,18 @@ fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
   let specular_strength = pow(max(dot(world_normal, half_dir), 0.0), 32.0);
   let specular_color = specular_strength * light.color;
   
-  let ambient_strength = 0.1;
-  let ambient_color = light.color * ambient_strength;
-  
-  // NEW!
-  // Calculate reflections
-  //let world_reflect = reflect(-view_dir, world_normal);
-  //let reflection = textureSample(env_map, env_sampler, world_reflect).rgb;
-  //let shininess = 0.1;
+  // Ambient is the irradiance and the split-sum specular of the environment
+  let n_dot_v = max(dot(world_normal, view_dir), 0.0);
+  let world_reflect = reflect(-view_dir, world_normal);
+  let irradiance = textureSample(t_irradiance, s_ibl, world_normal).rgb;
+  let prefiltered = textureSampleLevel(t_prefiltered, s_ibl, world_reflect, ROUGHNESS * PREFILTERED_MAX_LEVEL).rgb;
+  let brdf = textureSample(t_brdf_lut, s_ibl, vec2<f32>(n_dot_v, ROUGHNESS)).rg;
+  let fresnel = F0 + (max(vec3<f32>(1.0 - ROUGHNESS), F0) - F0) * pow(1.0 - n_dot_v, 5.0);
+  let ambient_diffuse = (1.0 - fresnel) * irradiance * object_color.xyz;
+  let ambient_specular = prefiltered * (fresnel * brdf.x + brdf.y);
+  let ambient_color = ambient_diffuse + ambient_specular;
   
-  let result = (ambient_color + diffuse_color + specular_color) * object_color.xyz;
+  let result = ambient_color + (diffuse_color + specular_color) * object_color.xyz;
  
   return vec4<f32>(result, 0.0);
 }
@@ -0,0 +1,190 @@
+struct Camera {
+  view_pos: vec4<f32>,
+  view: mat4x4<f32>,
+  view_proj: mat4x4<f32>,
+  inv_proj: mat4x4<f32>,
+  inv_view: mat4x4<f32>,
+}
+struct Light {
+  position: vec3<f32>,
+  color: vec3<f32>,
+};
+
+@group(1) @binding(0)
+var<uniform> light: Light;
+@group(2) @binding(0)
+var<uniform> camera: Camera;
+struct Node {
+  model: mat4x4<f32>,
+  normal: mat3x3<f32>,
+}
+struct MorphDelta {
+  position: vec4<f32>,
+  normal: vec4<f32>,
+  tangent: vec4<f32>,
+}
+struct MorphWeights {
+  targets: u32,
+  vertex_count: u32,
+  _padding: vec2<u32>,
+  weights: array<f32>,
+}
+
+@group(3) @binding(0)
+var<uniform> node: Node;
+@group(3) @binding(1)
+var<storage, read> joints: array<mat4x4<f32>>;
+@group(3) @binding(2)
+var<storage, read> morph_deltas: array<MorphDelta>;
+@group(3) @binding(3)
+var<storage, read> morph_weights: MorphWeights;
+
+struct VertexInput {
+  @location(0) position: vec3<f32>,
+  @location(1) tex_coords: vec2<f32>,
+  @location(2) normal: vec3<f32>,
+  @location(3) tangent: vec3<f32>,
+  @location(4) bitangent: vec3<f32>,
+  @location(12) joints: vec4<u32>,
+  @location(13) weights: vec4<f32>,
+}
+struct InstanceInput {
+  @location(5) model_matrix_0: vec4<f32>,
+  @location(6) model_matrix_1: vec4<f32>,
+  @location(7) model_matrix_2: vec4<f32>,
+  @location(8) model_matrix_3: vec4<f32>,
+  @location(9) normal_matrix_0: vec3<f32>,
+  @location(10) normal_matrix_1: vec3<f32>,
+  @location(11) normal_matrix_2: vec3<f32>,
+}
+
+struct VertexOutput {
+  @builtin(position) clip_position: vec4<f32>,
+  @location(0) tex_coords: vec2<f32>,
+  @location(1) world_position: vec3<f32>,
+  @location(2) world_view_position: vec3<f32>,
+  @location(3) world_light_position: vec3<f32>,
+  @location(4) world_normal: vec3<f32>,
+  @location(5) world_tangent: vec3<f32>,
+  @location(6) world_bitangent: vec3<f32>,
+}
+
+@vertex
+fn vs_main(
+  @builtin(vertex_index) vertex_index: u32,
+  model: VertexInput,
+  instance: InstanceInput,
+) -> VertexOutput {
+  var out: VertexOutput;
+  
+  let model_matrix = mat4x4<f32>(
+    instance.model_matrix_0,
+    instance.model_matrix_1,
+    instance.model_matrix_2,
+    instance.model_matrix_3,
+  );
+  let normal_matrix = mat3x3<f32>(
+    instance.normal_matrix_0,
+    instance.normal_matrix_1,
+    instance.normal_matrix_2,
+  );
+
+  // Morph targets are blended before the skinning, deltas are laid out by targets
+  var position = model.position;
+  var normal = model.normal;
+  var tangent = model.tangent;
+  for (var t = 0u; t < morph_weights.targets; t++) {
+    let weight = morph_weights.weights[t];
+    let delta = morph_deltas[t * morph_weights.vertex_count + vertex_index];
+
+    position += delta.position.xyz * weight;
+    normal += delta.normal.xyz * weight;
+    tangent += delta.tangent.xyz * weight;
+  }
+
+  // Joint matrices are blended by the weights of the vertex, unskinned vertices keep the identity
+  var skin_matrix = mat4x4<f32>(
+    vec4<f32>(1.0, 0.0, 0.0, 0.0),
+    vec4<f32>(0.0, 1.0, 0.0, 0.0),
+    vec4<f32>(0.0, 0.0, 1.0, 0.0),
+    vec4<f32>(0.0, 0.0, 0.0, 1.0),
+  );
+  if (dot(model.weights, vec4<f32>(1.0)) > 0.0) {
+    skin_matrix =
+      joints[model.joints.x] * model.weights.x +
+      joints[model.joints.y] * model.weights.y +
+      joints[model.joints.z] * model.weights.z +
+      joints[model.joints.w] * model.weights.w;
+  }
+  let skin_normal_matrix = mat3x3<f32>(
+    skin_matrix[0].xyz,
+    skin_matrix[1].xyz,
+    skin_matrix[2].xyz,
+  );
+  let world_position = model_matrix * node.model * skin_matrix * vec4<f32>(position, 1.0);
+
+  out.clip_position = camera.view_proj * world_position;
+  out.tex_coords = model.tex_coords;
+  out.world_normal = normalize(normal_matrix * node.normal * skin_normal_matrix * normal);
+  out.world_tangent = normalize(normal_matrix * node.normal * skin_normal_matrix * tangent);
+  out.world_bitangent = normalize(normal_matrix * node.normal * skin_normal_matrix * model.bitangent);
+  out.world_position = world_position.xyz;
+  out.world_view_position = camera.view_pos.xyz;
+  
+  return out;
+}
+
+// Fragment shader
+@group(0) @binding(0)
+var t_diffuse: texture_2d<f32>;
+@group(0)@binding(1)
+var s_diffuse: sampler;
+@group(0)@binding(2)
+var t_normal: texture_2d<f32>;
+@group(0) @binding(3)
+var s_normal: sampler;
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+  let object_color: vec4<f32> = textureSample(t_diffuse, s_diffuse, in.tex_coords);
+  let object_normal: vec4<f32> = textureSample(t_normal, s_normal, in.tex_coords);
+  
+  // Adjust the tangent and bitangent using the Gramm-Schmidt process
+  // This makes sure that they are perpedicular to each other and the
+  // normal of the surface.
+  let world_tangent = normalize(in.world_tangent - dot(in.world_tangent, in.world_normal) * in.world_normal);
+  let world_bitangent = cross(world_tangent, in.world_normal);
+  
+  // Convert the normal sample to world space
+  let TBN = mat3x3(
+      world_tangent,
+      world_bitangent,
+      in.world_normal,
+  );
+  let tangent_normal = object_normal.xyz * 2.0 - 1.0;
+  let world_normal = TBN * tangent_normal;
+  
+  // Create the lighting vectors
+  let light_dir = normalize(light.position - in.world_position);
+  let view_dir = normalize(in.world_view_position - in.world_position);
+  let half_dir = normalize(view_dir + light_dir);
+  
+  let diffuse_strength = max(dot(world_normal, light_dir), 0.0);
+  let diffuse_color = light.color * diffuse_strength;
+  
+  let specular_strength = pow(max(dot(world_normal, half_dir), 0.0), 32.0);
+  let specular_color = specular_strength * light.color;
+  
+  let ambient_strength = 0.1;
+  let ambient_color = light.color * ambient_strength;
+  
+  // NEW!
+  // Calculate reflections
+  //let world_reflect = reflect(-view_dir, world_normal);
+  //let reflection = textureSample(env_map, env_sampler, world_reflect).rgb;
+  //let shininess = 0.1;
+  
+  let result = (ambient_color + diffuse_color + specular_color) * object_color.xyz;
+ 
+  return vec4<f32>(result, 0.0);
+}
@@ -1,2 +1,4 @@
 pub mod camera;
+pub mod ibl;
 pub mod light;
+pub mod skybox;
@@ -4,11 +4,13 @@ pub(crate) mod projection;
 
 use anyhow::Result;
 use cgmath::{Deg, Matrix, SquareMatrix};
+use custom_engine_models::gltf::CameraNode;
 use instant::Duration;
 use winit::event::WindowEvent;
 
 use custom_engine_core::{
     bind_group::{layout::BindGroupLayout, BindGroup},
+    context::Handle,
     errors::CoreError,
     traits::Builder,
     uniform::{UniformDescription, Uniforms},
@@ -68,10 +70,12 @@ impl Component<CameraRaw> for CameraInner {
         }
     }
 
-    fn update(&mut self, event: &WindowEvent, dt: Duration) {
-        if self.controller.process_events(event) {
-            self.data.update(&mut self.controller, dt);
-        }
+    fn input(&mut self, event: &WindowEvent) -> bool {
+        self.controller.process_events(event)
+    }
+
+    fn update(&mut self, dt: Duration) {
+        self.data.update(&mut self.controller, dt);
     }
 }
 
@@ -79,17 +83,36 @@ impl Component<CameraRaw> for CameraInner {
 pub struct Camera {
     inner: CameraInner,
     uniform: Uniforms,
+
+    // Cameras of the glTF file, they're switched by `next_camera`
+    cameras: Vec<CameraNode>,
+    current: Option<usize>,
 }
 
 impl Camera {
     pub fn init(w: &mut Worker<'_>, bind_group_binding: u32) -> Result<Self, CoreError> {
+        Self::from_gltf(w, vec![], bind_group_binding)
+    }
+
+    // Starts from the first camera of the file, the default view is used without cameras
+    pub fn from_gltf(
+        w: &mut Worker<'_>,
+        cameras: Vec<CameraNode>,
+        bind_group_binding: u32,
+    ) -> Result<Self, CoreError> {
         let size = w.size();
 
         let projection = Projection::new(size.0, size.1, Deg(45.), 0.1, 100.);
-        let controller = CameraController::new(0.2, 0.2);
+        let controller = CameraController::new(4.0, 0.2);
         let data = CameraData::new((0.0, 5.0, 10.0), Deg(-90.0), Deg(-20.0));
 
-        let inner = CameraInner::new(projection, data, controller);
+        let mut inner = CameraInner::new(projection, data, controller);
+        if let Some(c) = cameras.first() {
+            inner.projection = Projection::from_gltf(&c.camera, inner.projection.aspect);
+            inner.data = CameraData::from_transform(c.transform);
+        }
+        let current = (!cameras.is_empty()).then_some(0);
+
         let uniform = w
             .create_uniform()
             .name("Uniform block")
@@ -102,20 +125,55 @@ impl Camera {
             .bind_group_binding(bind_group_binding)
             .build()?;
 
-        Ok(Self { uniform, inner })
+        Ok(Self {
+            uniform,
+            inner,
+            cameras,
+            current,
+        })
     }
 
-    pub fn update(
-        &mut self,
-        w: &mut Worker<'_>,
-        event: &WindowEvent,
-        dt: Duration,
-    ) -> Result<(), CoreError> {
-        self.inner.update(event, dt);
+    // The uniform is written by the next `update`
+    pub fn set_camera(&mut self, index: usize) -> Option<&CameraNode> {
+        let c = self.cameras.get(index)?;
+
+        self.inner.projection = Projection::from_gltf(&c.camera, self.inner.projection.aspect);
+        self.inner.data = CameraData::from_transform(c.transform);
+        self.current = Some(index);
+
+        Some(c)
+    }
+
+    pub fn next_camera(&mut self) -> Option<&CameraNode> {
+        let next = self
+            .current
+            .map_or(0, |c| (c + 1) % self.cameras.len().max(1));
+
+        self.set_camera(next)
+    }
+
+    pub fn current_camera(&self) -> Option<&CameraNode> {
+        self.cameras.get(self.current?)
+    }
+
+    pub fn cameras(&self) -> &[CameraNode] {
+        &self.cameras
+    }
+
+    pub fn input(&mut self, event: &WindowEvent) -> bool {
+        self.inner.input(event)
+    }
+
+    pub fn update(&mut self, w: &mut Worker<'_>, dt: Duration) -> Result<(), CoreError> {
+        self.inner.update(dt);
 
         w.update_uniform_direct(&self.uniform, "Camera", &[self.inner.data()])
     }
 
+    pub fn data(&self) -> CameraRaw {
+        self.inner.data()
+    }
+
     pub fn bind_group(&self) -> &BindGroup {
         self.uniform.get_group()
     }
@@ -124,7 +182,7 @@ impl Camera {
         self.uniform.get_layout()
     }
 
-    pub fn to_worker(self, w: &mut Worker<'_>) {
+    pub fn to_worker(self, w: &mut Worker<'_>) -> Handle<Uniforms> {
         w.add_uniform(self.uniform)
     }
 }
@@ -40,6 +40,20 @@ impl CameraData {
         }
     }
 
+    // Roll of the transform is dropped, the camera looks down its `-Z` axis
+    pub fn from_transform(transform: Matrix4<f32>) -> Self {
+        let forward = -transform.z.truncate().normalize();
+
+        Self {
+            position: Point3::from_homogeneous(transform.w),
+            yaw: Rad(forward.z.atan2(forward.x)),
+            pitch: Rad(forward
+                .y
+                .clamp(-SAFE_FRAC_PI_2.sin(), SAFE_FRAC_PI_2.sin())
+                .asin()),
+        }
+    }
+
     #[inline]
     pub fn matrix(&self) -> Matrix4<f32> {
         let (sin_pitch, cos_pitch) = self.pitch.0.sin_cos();
@@ -99,3 +113,27 @@ impl CameraData {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use cgmath::SquareMatrix;
+
+    use super::*;
+
+    #[test]
+    fn camera_from_transform() {
+        // Node transform of a camera is the inverse of its view matrix
+        let camera = CameraData::new((1., 2., 3.), Rad(0.7), Rad(-0.3));
+        let transform = camera.matrix().invert().unwrap();
+
+        let data = CameraData::from_transform(transform);
+        assert!((data.position - camera.position).magnitude() < 1e-5);
+        assert!((data.yaw.0 - 0.7).abs() < 1e-5);
+        assert!((data.pitch.0 + 0.3).abs() < 1e-5);
+
+        // Straight up is clamped to the safe pitch
+        let up = Matrix4::from_angle_x(Rad(FRAC_PI_2));
+        let data = CameraData::from_transform(up);
+        assert!((data.pitch.0 - SAFE_FRAC_PI_2).abs() < 1e-3);
+    }
+}
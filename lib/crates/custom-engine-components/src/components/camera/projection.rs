@@ -1,11 +1,15 @@
-use cgmath::{perspective, Matrix4, Rad};
+use cgmath::{ortho, perspective, Matrix4, Rad, Vector4};
+use custom_engine_models::gltf::Camera;
 
 #[derive(Debug)]
 pub struct Projection {
     pub aspect: f32,
     pub fovy: Rad<f32>,
     pub znear: f32,
+    // Perspective without the far plane has the infinite `zfar`
     pub zfar: f32,
+    // Half extents of the orthographic view, `fovy` and `aspect` are unused with them
+    pub orthographic: Option<(f32, f32)>,
 }
 
 impl Default for Projection {
@@ -15,6 +19,7 @@ impl Default for Projection {
             fovy: Rad(0.),
             znear: 0.,
             zfar: 0.,
+            orthographic: None,
         }
     }
 }
@@ -26,6 +31,33 @@ impl Projection {
             fovy: fovy.into(),
             znear,
             zfar,
+            orthographic: None,
+        }
+    }
+
+    pub fn orthographic(xmag: f32, ymag: f32, znear: f32, zfar: f32) -> Self {
+        Self {
+            znear,
+            zfar,
+            orthographic: Some((xmag, ymag)),
+            ..Default::default()
+        }
+    }
+
+    // Aspect of the viewport is kept, the one of the file is only a hint
+    pub fn from_gltf(camera: &Camera, aspect: f32) -> Self {
+        match camera {
+            Camera::Perspective(p) => Self {
+                aspect,
+                fovy: p.fovy.into(),
+                znear: p.znear,
+                zfar: p.zfar.unwrap_or(f32::INFINITY),
+                orthographic: None,
+            },
+            Camera::Orthographic(o) => Self {
+                aspect,
+                ..Self::orthographic(o.xmag, o.ymag, o.znear, o.zfar)
+            },
         }
     }
 
@@ -34,6 +66,48 @@ impl Projection {
     }
 
     pub fn matrix(&self) -> Matrix4<f32> {
-        perspective(self.fovy, self.aspect, self.znear, self.zfar)
+        if let Some((xmag, ymag)) = self.orthographic {
+            return ortho(-xmag, xmag, -ymag, ymag, self.znear, self.zfar);
+        }
+        if self.zfar.is_finite() {
+            return perspective(self.fovy, self.aspect, self.znear, self.zfar);
+        }
+
+        let f = 1. / (0.5 * self.fovy.0).tan();
+        Matrix4::from_cols(
+            Vector4::new(f / self.aspect, 0., 0., 0.),
+            Vector4::new(0., f, 0., 0.),
+            Vector4::new(0., 0., -1., -1.),
+            Vector4::new(0., 0., -2. * self.znear, 0.),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use cgmath::{Deg, InnerSpace, Vector3};
+
+    use super::*;
+
+    #[test]
+    fn projection_matrix() {
+        // The infinite far plane is the limit of the finite one
+        let infinite = Projection::new(16, 9, Deg(60.), 0.1, f32::INFINITY).matrix();
+        let far = perspective(Deg(60.), 16. / 9., 0.1, 1e7);
+        let (a, b): (&[f32; 16], &[f32; 16]) = (infinite.as_ref(), far.as_ref());
+        for (a, b) in a.iter().zip(b) {
+            assert!((a - b).abs() < 1e-5);
+        }
+
+        let ndc = |p: Vector4<f32>| p.truncate() / p.w;
+        let near = ndc(infinite * Vector4::new(0., 0., -0.1, 1.));
+        assert!((near.z + 1.).abs() < 1e-5);
+
+        // Corners of the half extents and the planes go to the corners of the view volume
+        let ortho = Projection::orthographic(4., 2., 1., 11.).matrix();
+        let corner = ndc(ortho * Vector4::new(4., 2., -1., 1.));
+        assert!((corner - Vector3::new(1., 1., -1.)).magnitude() < 1e-5);
+        let corner = ndc(ortho * Vector4::new(-4., -2., -11., 1.));
+        assert!((corner - Vector3::new(-1., -1., 1.)).magnitude() < 1e-5);
     }
 }
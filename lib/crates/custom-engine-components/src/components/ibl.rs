@@ -0,0 +1,368 @@
+use std::borrow::Cow;
+
+use custom_engine_core::{
+    bind_group::{layout::BindGroupLayout, BindGroup},
+    errors::CoreError,
+    pipeline::Pipeline,
+    render_pass::ComputeStage,
+    texture::RenderTexture,
+    traits::Builder,
+    uniform::{UniformDescription, Uniforms},
+    worker::Worker,
+};
+
+const IBL_SHADER: &str = include_str!("../../../../assets/shaders/ibl.wgsl");
+
+// Workgroup size of the kernels in `ibl.wgsl`
+const WORKGROUP_SIZE: u32 = 8;
+const IRRADIANCE_SIZE: u32 = 32;
+const PREFILTERED_SIZE: u32 = 128;
+const PREFILTERED_MIP_LEVELS: u32 = 5;
+const BRDF_LUT_SIZE: u32 = 256;
+const IBL_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba16Float;
+
+#[repr(C)]
+#[derive(Debug, Copy, Clone, bytemuck::Pod, bytemuck::Zeroable)]
+struct PrefilterRaw {
+    roughness: f32,
+    _padding: [f32; 3],
+}
+
+// Bind group has the irradiance cube map at binding 0, the prefiltered cube map at 1,
+// the BRDF lookup texture at 2 and the linear sampler at 3
+#[derive(Debug)]
+pub struct Ibl {
+    irradiance: RenderTexture,
+    prefiltered: RenderTexture,
+    brdf_lut: RenderTexture,
+
+    bind_group_layout: BindGroupLayout,
+    bind_group: BindGroup,
+}
+
+impl Ibl {
+    // Environment is a cube map with a sampler, e.g. `Skybox::cube_map`
+    pub fn init(
+        w: &mut Worker<'_>,
+        environment: &RenderTexture,
+        bind_group_binding: u32,
+    ) -> Result<Self, CoreError> {
+        let usage = wgpu::TextureUsages::STORAGE_BINDING | wgpu::TextureUsages::TEXTURE_BINDING;
+
+        let irradiance = w
+            .create_render_texture()
+            .label("Irradiance map")
+            .texture_size((IRRADIANCE_SIZE, IRRADIANCE_SIZE))
+            .depth_or_array_layers(6)
+            .view_dimension(wgpu::TextureViewDimension::Cube)
+            .format(IBL_FORMAT)
+            .usage(usage)
+            .is_sampler(false)
+            .build()?;
+        let prefiltered = w
+            .create_render_texture()
+            .label("Prefiltered map")
+            .texture_desc(wgpu::TextureDescriptor {
+                label: Some("Prefiltered map"),
+                size: wgpu::Extent3d {
+                    width: PREFILTERED_SIZE,
+                    height: PREFILTERED_SIZE,
+                    depth_or_array_layers: 6,
+                },
+                mip_level_count: PREFILTERED_MIP_LEVELS,
+                sample_count: 1,
+                dimension: wgpu::TextureDimension::D2,
+                format: IBL_FORMAT,
+                usage,
+                view_formats: &[],
+            })
+            .view_dimension(wgpu::TextureViewDimension::Cube)
+            .sampler_desc(wgpu::SamplerDescriptor {
+                label: Some("IBL sampler"),
+                address_mode_u: wgpu::AddressMode::ClampToEdge,
+                address_mode_v: wgpu::AddressMode::ClampToEdge,
+                address_mode_w: wgpu::AddressMode::ClampToEdge,
+                mag_filter: wgpu::FilterMode::Linear,
+                min_filter: wgpu::FilterMode::Linear,
+                mipmap_filter: wgpu::FilterMode::Linear,
+                ..Default::default()
+            })
+            .build()?;
+        let brdf_lut = w
+            .create_render_texture()
+            .label("BRDF lookup texture")
+            .texture_size((BRDF_LUT_SIZE, BRDF_LUT_SIZE))
+            .format(IBL_FORMAT)
+            .usage(usage)
+            .is_sampler(false)
+            .build()?;
+
+        Self::precompute(w, environment, &irradiance, &prefiltered, &brdf_lut)?;
+
+        let bind_group_layout = w
+            .create_bind_group_layout()
+            .label("IBL bind group layout")
+            .entries(cube_layout_entry(0, wgpu::ShaderStages::FRAGMENT, true))
+            .entries(cube_layout_entry(1, wgpu::ShaderStages::FRAGMENT, true))
+            .entries(wgpu::BindGroupLayoutEntry {
+                binding: 2,
+                visibility: wgpu::ShaderStages::FRAGMENT,
+                ty: wgpu::BindingType::Texture {
+                    multisampled: false,
+                    view_dimension: wgpu::TextureViewDimension::D2,
+                    sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                },
+                count: None,
+            })
+            .entries(wgpu::BindGroupLayoutEntry {
+                binding: 3,
+                visibility: wgpu::ShaderStages::FRAGMENT,
+                ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                count: None,
+            })
+            .build()?;
+        let bind_group = w
+            .create_bind_group()
+            .label("IBL bind group")
+            .layout(&bind_group_layout)
+            .binding(bind_group_binding)
+            .entries_view(0, irradiance.view())
+            .entries_view(1, prefiltered.view())
+            .entries_view(2, brdf_lut.view())
+            .entries_sampler(3, prefiltered.sampler()?)
+            .build()?;
+
+        Ok(Self {
+            irradiance,
+            prefiltered,
+            brdf_lut,
+            bind_group_layout,
+            bind_group,
+        })
+    }
+
+    pub fn bind_group(&self) -> &BindGroup {
+        &self.bind_group
+    }
+
+    pub fn bind_group_layout(&self) -> &BindGroupLayout {
+        &self.bind_group_layout
+    }
+
+    pub fn irradiance(&self) -> &RenderTexture {
+        &self.irradiance
+    }
+
+    // Roughness of the mip level is `level / (mip_level_count - 1)`
+    pub fn prefiltered(&self) -> &RenderTexture {
+        &self.prefiltered
+    }
+
+    // `x` is `dot(normal, view)`, `y` is the roughness, channels are the scale and bias of F0
+    pub fn brdf_lut(&self) -> &RenderTexture {
+        &self.brdf_lut
+    }
+
+    fn precompute(
+        w: &mut Worker<'_>,
+        environment: &RenderTexture,
+        irradiance: &RenderTexture,
+        prefiltered: &RenderTexture,
+        brdf_lut: &RenderTexture,
+    ) -> Result<(), CoreError> {
+        let filterable = matches!(
+            environment.format().sample_type(None, Some(w.features())),
+            Some(wgpu::TextureSampleType::Float { filterable: true })
+        );
+        let sampler_type = if filterable {
+            wgpu::SamplerBindingType::Filtering
+        } else {
+            wgpu::SamplerBindingType::NonFiltering
+        };
+
+        let env_bgl = w
+            .create_bind_group_layout()
+            .label("IBL environment bind group layout")
+            .entries(cube_layout_entry(
+                0,
+                wgpu::ShaderStages::COMPUTE,
+                filterable,
+            ))
+            .entries(wgpu::BindGroupLayoutEntry {
+                binding: 1,
+                visibility: wgpu::ShaderStages::COMPUTE,
+                ty: wgpu::BindingType::Sampler(sampler_type),
+                count: None,
+            })
+            .entries(storage_layout_entry(2, wgpu::TextureViewDimension::D2Array))
+            .build()?;
+        let lut_bgl = w
+            .create_bind_group_layout()
+            .label("BRDF lookup bind group layout")
+            .entries(storage_layout_entry(3, wgpu::TextureViewDimension::D2))
+            .build()?;
+
+        // Every level of the prefiltered map has own roughness
+        let levels = 0..PREFILTERED_MIP_LEVELS;
+        let prefilter_uniforms = levels
+            .clone()
+            .map(|level| {
+                w.create_uniform()
+                    .name("Prefilter uniform block")
+                    .entries(UniformDescription::new(
+                        "Prefilter",
+                        0,
+                        wgpu::ShaderStages::COMPUTE,
+                        &[PrefilterRaw {
+                            roughness: level as f32 / (PREFILTERED_MIP_LEVELS - 1) as f32,
+                            _padding: [0.; 3],
+                        }],
+                    ))
+                    .bind_group_binding(1)
+                    .build()
+            })
+            .collect::<Result<Vec<Uniforms>, _>>()?;
+
+        let irradiance_view = faces_view(irradiance, 0);
+        let prefiltered_views = levels
+            .clone()
+            .map(|level| faces_view(prefiltered, level))
+            .collect::<Vec<_>>();
+
+        let env_bind_group = |view: &wgpu::TextureView| {
+            w.create_bind_group()
+                .label("IBL environment bind group")
+                .layout(&env_bgl)
+                .binding(0)
+                .entries_view(0, environment.view())
+                .entries_sampler(1, environment.sampler()?)
+                .entries_view(2, view)
+                .build()
+        };
+        let irradiance_bg = env_bind_group(&irradiance_view)?;
+        let prefiltered_bgs = prefiltered_views
+            .iter()
+            .map(env_bind_group)
+            .collect::<Result<Vec<_>, _>>()?;
+        let lut_bg = w
+            .create_bind_group()
+            .label("BRDF lookup bind group")
+            .layout(&lut_bgl)
+            .binding(0)
+            .entries_view(3, brdf_lut.view())
+            .build()?;
+
+        let irradiance_pipeline = compute_pipeline(w, "compute_irradiance", vec![&env_bgl])?;
+        let prefiltered_pipeline = compute_pipeline(
+            w,
+            "compute_prefiltered",
+            vec![&env_bgl, prefilter_uniforms[0].get_layout()],
+        )?;
+        let lut_pipeline = compute_pipeline(w, "compute_brdf_lut", vec![&lut_bgl])?;
+
+        let workgroups = |size: u32| size.div_ceil(WORKGROUP_SIZE);
+        let mut render_pass = w.render_pass().label("IBL pass").compute_stage(
+            0,
+            ComputeStage::new(&irradiance_pipeline)
+                .bind_groups(vec![&irradiance_bg])
+                .x_dimension(workgroups(IRRADIANCE_SIZE))
+                .y_dimension(workgroups(IRRADIANCE_SIZE))
+                .z_dimension(6),
+        );
+        for ((level, bg), uniform) in levels.zip(&prefiltered_bgs).zip(&prefilter_uniforms) {
+            let size = (PREFILTERED_SIZE >> level).max(1);
+
+            render_pass = render_pass.compute_stage(
+                1 + level as usize,
+                ComputeStage::new(&prefiltered_pipeline)
+                    .bind_groups(vec![bg, uniform.get_group()])
+                    .x_dimension(workgroups(size))
+                    .y_dimension(workgroups(size))
+                    .z_dimension(6),
+            );
+        }
+        render_pass = render_pass.compute_stage(
+            1 + PREFILTERED_MIP_LEVELS as usize,
+            ComputeStage::new(&lut_pipeline)
+                .bind_groups(vec![&lut_bg])
+                .x_dimension(workgroups(BRDF_LUT_SIZE))
+                .y_dimension(workgroups(BRDF_LUT_SIZE))
+                .z_dimension(1),
+        );
+
+        w.render(render_pass)
+    }
+}
+
+fn compute_pipeline(
+    w: &Worker<'_>,
+    entry_point: &str,
+    bgls: Vec<&BindGroupLayout>,
+) -> Result<Pipeline, CoreError> {
+    let label = format!("IBL `{entry_point}`");
+
+    let shader = w
+        .create_shader()
+        .label(&label)
+        .is_compute(true)
+        .compute_entry_point(entry_point)
+        .source(wgpu::ShaderSource::Wgsl(Cow::Borrowed(IBL_SHADER)))
+        .build()?;
+    let pipeline_layout = w
+        .create_pipeline_layout()
+        .label(&label)
+        .entries(bgls)
+        .build()?;
+
+    w.create_pipeline()
+        .label(&label)
+        .layout(&pipeline_layout)
+        .shader(&shader)
+        .primitive(&wgpu::PrimitiveState::default())
+        .multisample(&wgpu::MultisampleState::default())
+        .is_compute(true)
+        .build()
+}
+
+fn faces_view(texture: &RenderTexture, level: u32) -> wgpu::TextureView {
+    texture.create_view(&wgpu::TextureViewDescriptor {
+        label: Some("IBL faces view"),
+        dimension: Some(wgpu::TextureViewDimension::D2Array),
+        base_mip_level: level,
+        mip_level_count: Some(1),
+        ..Default::default()
+    })
+}
+
+fn cube_layout_entry(
+    binding: u32,
+    visibility: wgpu::ShaderStages,
+    filterable: bool,
+) -> wgpu::BindGroupLayoutEntry {
+    wgpu::BindGroupLayoutEntry {
+        binding,
+        visibility,
+        ty: wgpu::BindingType::Texture {
+            multisampled: false,
+            view_dimension: wgpu::TextureViewDimension::Cube,
+            sample_type: wgpu::TextureSampleType::Float { filterable },
+        },
+        count: None,
+    }
+}
+
+fn storage_layout_entry(
+    binding: u32,
+    view_dimension: wgpu::TextureViewDimension,
+) -> wgpu::BindGroupLayoutEntry {
+    wgpu::BindGroupLayoutEntry {
+        binding,
+        visibility: wgpu::ShaderStages::COMPUTE,
+        ty: wgpu::BindingType::StorageTexture {
+            access: wgpu::StorageTextureAccess::WriteOnly,
+            format: IBL_FORMAT,
+            view_dimension,
+        },
+        count: None,
+    }
+}
@@ -39,12 +39,19 @@ impl Component<LightRaw> for Light {
         }
     }
 
-    fn update(&mut self, event: &WindowEvent, _: Duration) {
+    // Light is moved by a step per key press, so it doesn't depend on the frame time
+    fn input(&mut self, event: &WindowEvent) -> bool {
         if self.controller.process_events(event) {
             self.data.update(&self.controller);
             self.controller.reset();
+
+            true
+        } else {
+            false
         }
     }
+
+    fn update(&mut self, _: Duration) {}
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -0,0 +1,272 @@
+use std::borrow::Cow;
+
+use custom_engine_core::{
+    errors::CoreError,
+    pipeline::{layout::PipelineLayout, Pipeline},
+    render_pass::{
+        color_attachment::ColorAttachmentBuilder, depth_stencil::DepthStencilAttachmentBuilder,
+        ComputeStage, RenderStage,
+    },
+    shader::Shader,
+    texture::{RenderTexture, TextureKind},
+    traits::Builder,
+    worker::Worker,
+};
+
+use crate::components::camera::Camera;
+
+const SKY_SHADER: &str = include_str!("../../../../assets/shaders/sky.wgsl");
+const EQUIRECTANGULAR_SHADER: &str =
+    include_str!("../../../../assets/shaders/equirectangular.wgsl");
+
+// Workgroup size of `compute_equirect_to_cubemap`
+const WORKGROUP_SIZE: u32 = 16;
+// Groups of `sky.wgsl`
+const CAMERA_GROUP: u32 = 0;
+const CUBE_MAP_GROUP: u32 = 1;
+// Half floats are filterable without `FLOAT32_FILTERABLE`
+const CUBE_MAP_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba16Float;
+
+#[derive(Debug)]
+pub struct Skybox {
+    cube_map: RenderTexture,
+
+    shader: Shader,
+    pipeline_layout: PipelineLayout,
+    pipeline: Pipeline,
+}
+
+impl Skybox {
+    // Equirectangular `.hdr` image is converted to a cube map, which is drawn at the far plane.
+    // The uniform of the camera is shared, so the camera is bound at the group 0
+    pub fn init(
+        w: &mut Worker<'_>,
+        camera: &Camera,
+        hdr_data: &[u8],
+        color_format: wgpu::TextureFormat,
+    ) -> Result<Self, CoreError> {
+        let camera_group = camera.bind_group().binding;
+        if camera_group != CAMERA_GROUP {
+            return Err(CoreError::UnexpectedBindGroup(
+                "Skybox camera".to_string(),
+                CAMERA_GROUP,
+                camera_group,
+            ));
+        }
+        let cube_map = Self::load_cube_map(w, hdr_data)?;
+
+        let shader = w
+            .create_shader()
+            .label("Skybox shader")
+            .vs_entry_point("vs_main")
+            .fs_entry_point("fs_main")
+            .fs_options(vec![wgpu::ColorTargetState {
+                format: color_format,
+                blend: None,
+                write_mask: wgpu::ColorWrites::ALL,
+            }])
+            .source(wgpu::ShaderSource::Wgsl(Cow::Borrowed(SKY_SHADER)))
+            .build()?;
+
+        let pipeline_layout = w
+            .create_pipeline_layout()
+            .label("Skybox pipeline layout")
+            .entry(camera.bind_group_layout())
+            .entry(cube_map.bind_group_layout()?)
+            .build()?;
+
+        let pipeline = w
+            .create_pipeline()
+            .label("Skybox pipeline")
+            .layout(&pipeline_layout)
+            .shader(&shader)
+            .primitive(&wgpu::PrimitiveState {
+                cull_mode: None,
+                ..Default::default()
+            })
+            // Sky is drawn at the depth `1.0` behind the scene, the depth isn't written
+            .depth_stencil(&wgpu::DepthStencilState {
+                format: TextureKind::Depth.into(),
+                depth_write_enabled: false,
+                depth_compare: wgpu::CompareFunction::LessEqual,
+                stencil: wgpu::StencilState::default(),
+                bias: wgpu::DepthBiasState::default(),
+            })
+            .multisample(&w.multisample_state())
+            .build()?;
+
+        Ok(Self {
+            cube_map,
+            shader,
+            pipeline_layout,
+            pipeline,
+        })
+    }
+
+    // Stage loads the color and depth of the scene, it's drawn after the scene stages
+    pub fn render_stage<'a>(
+        &'a self,
+        camera: &'a Camera,
+        color_attachment: ColorAttachmentBuilder<'a>,
+        depth_view: &'a wgpu::TextureView,
+    ) -> Result<RenderStage<'a>, CoreError> {
+        Ok(RenderStage::new(&self.pipeline)
+            .color_attachments_builder(color_attachment.ops(wgpu::Operations {
+                load: wgpu::LoadOp::Load,
+                store: wgpu::StoreOp::Store,
+            }))
+            .depth_stencil_builder(
+                DepthStencilAttachmentBuilder::new()
+                    .label("Skybox depth attach")
+                    .view(depth_view)
+                    .depth_ops(wgpu::Operations {
+                        load: wgpu::LoadOp::Load,
+                        store: wgpu::StoreOp::Store,
+                    }),
+            )
+            .bind_groups(vec![camera.bind_group(), self.cube_map.bind_group()?])
+            .instances(0..1)
+            .entities(0..3))
+    }
+
+    pub fn cube_map(&self) -> &RenderTexture {
+        &self.cube_map
+    }
+
+    pub fn shader(&self) -> &Shader {
+        &self.shader
+    }
+
+    pub fn pipeline_layout(&self) -> &PipelineLayout {
+        &self.pipeline_layout
+    }
+
+    fn load_cube_map(w: &mut Worker<'_>, hdr_data: &[u8]) -> Result<RenderTexture, CoreError> {
+        let equirect = w
+            .create_render_texture()
+            .label("Equirectangular texture")
+            .bytes(hdr_data)
+            .format(wgpu::TextureFormat::Rgba32Float)
+            .usage(wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST)
+            .is_sampler(false)
+            .build()?;
+        w.load_texture(&equirect);
+
+        // Power of two keeps the faces divisible by the workgroup size
+        let face_size = (equirect.width() / 4)
+            .next_power_of_two()
+            .clamp(WORKGROUP_SIZE, w.limits().max_texture_dimension_2d);
+        let cube_map = w
+            .create_render_texture()
+            .label("Skybox cube map")
+            .texture_size((face_size, face_size))
+            .depth_or_array_layers(6)
+            .view_dimension(wgpu::TextureViewDimension::Cube)
+            .format(CUBE_MAP_FORMAT)
+            .usage(wgpu::TextureUsages::STORAGE_BINDING | wgpu::TextureUsages::TEXTURE_BINDING)
+            .sampler_desc(wgpu::SamplerDescriptor {
+                label: Some("Skybox sampler"),
+                address_mode_u: wgpu::AddressMode::ClampToEdge,
+                address_mode_v: wgpu::AddressMode::ClampToEdge,
+                address_mode_w: wgpu::AddressMode::ClampToEdge,
+                mag_filter: wgpu::FilterMode::Linear,
+                min_filter: wgpu::FilterMode::Linear,
+                mipmap_filter: wgpu::FilterMode::Nearest,
+                ..Default::default()
+            })
+            .view_layout_entry(wgpu::BindGroupLayoutEntry {
+                binding: 0,
+                visibility: wgpu::ShaderStages::FRAGMENT,
+                ty: wgpu::BindingType::Texture {
+                    multisampled: false,
+                    view_dimension: wgpu::TextureViewDimension::Cube,
+                    sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                },
+                count: None,
+            })
+            .sampler_layout_entry(wgpu::BindGroupLayoutEntry {
+                binding: 1,
+                visibility: wgpu::ShaderStages::FRAGMENT,
+                ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                count: None,
+            })
+            .bind_group_binding(CUBE_MAP_GROUP)
+            .build()?;
+
+        let compute_bgl = w
+            .create_bind_group_layout()
+            .label("Equirectangular bind group layout")
+            .entries(wgpu::BindGroupLayoutEntry {
+                binding: 0,
+                visibility: wgpu::ShaderStages::COMPUTE,
+                ty: wgpu::BindingType::Texture {
+                    multisampled: false,
+                    view_dimension: wgpu::TextureViewDimension::D2,
+                    sample_type: wgpu::TextureSampleType::Float { filterable: false },
+                },
+                count: None,
+            })
+            .entries(wgpu::BindGroupLayoutEntry {
+                binding: 1,
+                visibility: wgpu::ShaderStages::COMPUTE,
+                ty: wgpu::BindingType::StorageTexture {
+                    access: wgpu::StorageTextureAccess::WriteOnly,
+                    format: CUBE_MAP_FORMAT,
+                    view_dimension: wgpu::TextureViewDimension::D2Array,
+                },
+                count: None,
+            })
+            .build()?;
+        let faces_view = cube_map.create_view(&wgpu::TextureViewDescriptor {
+            label: Some("Skybox faces view"),
+            dimension: Some(wgpu::TextureViewDimension::D2Array),
+            ..Default::default()
+        });
+        let compute_bg = w
+            .create_bind_group()
+            .label("Equirectangular bind group")
+            .layout(&compute_bgl)
+            .binding(0)
+            .entries_view(0, equirect.view())
+            .entries_view(1, &faces_view)
+            .build()?;
+
+        let compute_shader = w
+            .create_shader()
+            .label("Equirectangular shader")
+            .is_compute(true)
+            .compute_entry_point("compute_equirect_to_cubemap")
+            .source(wgpu::ShaderSource::Wgsl(Cow::Borrowed(
+                EQUIRECTANGULAR_SHADER,
+            )))
+            .build()?;
+        let compute_pl = w
+            .create_pipeline_layout()
+            .label("Equirectangular pipeline layout")
+            .entry(&compute_bgl)
+            .build()?;
+        let compute_pipeline = w
+            .create_pipeline()
+            .label("Equirectangular pipeline")
+            .layout(&compute_pl)
+            .shader(&compute_shader)
+            .primitive(&wgpu::PrimitiveState::default())
+            .multisample(&wgpu::MultisampleState::default())
+            .is_compute(true)
+            .build()?;
+
+        let workgroups = face_size / WORKGROUP_SIZE;
+        w.render(
+            w.render_pass().label("Equirectangular pass").compute_stage(
+                0,
+                ComputeStage::new(&compute_pipeline)
+                    .bind_groups(vec![&compute_bg])
+                    .x_dimension(workgroups)
+                    .y_dimension(workgroups)
+                    .z_dimension(6),
+            ),
+        )?;
+
+        Ok(cube_map)
+    }
+}
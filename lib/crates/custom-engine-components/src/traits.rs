@@ -4,7 +4,8 @@ use winit::event::WindowEvent;
 
 pub trait Component<T: bytemuck::Zeroable + bytemuck::Pod> {
     fn data(&self) -> T;
-    fn update(&mut self, event: &WindowEvent, dt: Duration);
+    fn input(&mut self, event: &WindowEvent) -> bool;
+    fn update(&mut self, dt: Duration);
 }
 
 pub trait Object {}
@@ -4,12 +4,13 @@ pub mod layout;
 use derive_more::{Deref, DerefMut};
 use log::debug;
 
-use crate::{buffer::Buffer, errors::CoreError, traits::Builder};
+use crate::{buffer::Buffer, context::ResourceVersion, errors::CoreError, traits::Builder};
 
 #[derive(Debug, Deref, DerefMut)]
 pub struct BindGroup {
     pub id: usize,
     pub binding: u32,
+    pub(crate) version: ResourceVersion,
 
     #[deref]
     #[deref_mut]
@@ -92,6 +93,7 @@ Build `{label}`:
             id,
             inner_bg,
             binding,
+            version: Default::default(),
         })
     }
 }
@@ -112,6 +114,21 @@ impl<'a> BindGroupBuilder<'a> {
         self
     }
 
+    // Binds the part of the buffer, the rest is reached by the dynamic offset
+    pub fn entries_buffer_range(mut self, buffer: &'a Buffer, size: u64) -> Self {
+        self.entries
+            .get_or_insert(vec![])
+            .push(wgpu::BindGroupEntry {
+                binding: buffer.binding,
+                resource: wgpu::BindingResource::Buffer(wgpu::BufferBinding {
+                    buffer,
+                    offset: 0,
+                    size: wgpu::BufferSize::new(size),
+                }),
+            });
+        self
+    }
+
     pub fn entries_buffers(mut self, buffers: Vec<&'a Buffer>) -> Self {
         let entries = self.entries.get_or_insert(vec![]);
 
@@ -2,13 +2,14 @@ use derive_more::{Deref, DerefMut};
 use flume::bounded;
 use log::{debug, error};
 
-use crate::{errors::CoreError, traits::Builder};
+use crate::{context::ResourceVersion, errors::CoreError, traits::Builder};
 
 #[derive(Debug, Deref, DerefMut)]
 pub struct Buffer {
     pub id: usize,
     pub binding: u32,
     pub usage: wgpu::BufferUsages,
+    pub(crate) version: ResourceVersion,
 
     #[deref]
     #[deref_mut]
@@ -166,6 +167,7 @@ Build `{label}`:
             inner_buffer,
             binding,
             usage,
+            version: Default::default(),
         })
     }
 }
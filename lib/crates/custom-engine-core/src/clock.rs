@@ -0,0 +1,199 @@
+use instant::{Duration, Instant};
+
+#[derive(Debug)]
+pub struct FrameClock {
+    last_tick: Option<Instant>,
+
+    delta: Duration,
+    elapsed: Duration,
+    frame: u64,
+
+    paused: bool,
+    time_scale: f64,
+    max_delta: Duration,
+
+    fixed_step: Duration,
+    accumulator: Duration,
+}
+
+impl Default for FrameClock {
+    fn default() -> Self {
+        Self {
+            last_tick: None,
+            delta: Duration::ZERO,
+            elapsed: Duration::ZERO,
+            frame: 0,
+            paused: false,
+            time_scale: 1.,
+            max_delta: Duration::from_millis(250),
+            fixed_step: Duration::from_secs_f64(1. / 60.),
+            accumulator: Duration::ZERO,
+        }
+    }
+}
+
+impl FrameClock {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // Measure the real time since the previous tick
+    pub fn tick(&mut self) -> Duration {
+        let now = Instant::now();
+        let real_delta = self
+            .last_tick
+            .map(|last| now.duration_since(last))
+            .unwrap_or_default();
+        self.last_tick = Some(now);
+
+        self.advance(real_delta)
+    }
+
+    // Advance by a given real time, e.g. a fixed frame time in headless mode
+    pub fn advance(&mut self, real_delta: Duration) -> Duration {
+        self.delta = if self.paused {
+            Duration::ZERO
+        } else {
+            real_delta.min(self.max_delta).mul_f64(self.time_scale)
+        };
+
+        self.elapsed += self.delta;
+        self.accumulator += self.delta;
+        self.frame += 1;
+
+        self.delta
+    }
+
+    pub fn consume_fixed_step(&mut self) -> bool {
+        if !self.fixed_step.is_zero() && self.accumulator >= self.fixed_step {
+            self.accumulator -= self.fixed_step;
+            true
+        } else {
+            false
+        }
+    }
+
+    pub fn pause(&mut self) {
+        self.paused = true;
+    }
+
+    pub fn resume(&mut self) {
+        self.paused = false;
+    }
+
+    pub fn set_paused(&mut self, paused: bool) {
+        self.paused = paused;
+    }
+
+    pub fn set_time_scale(&mut self, time_scale: f64) {
+        self.time_scale = time_scale.max(0.);
+    }
+
+    pub fn set_fixed_step(&mut self, fixed_step: Duration) {
+        self.fixed_step = fixed_step;
+        self.accumulator = Duration::ZERO;
+    }
+
+    pub fn set_max_delta(&mut self, max_delta: Duration) {
+        self.max_delta = max_delta;
+    }
+
+    #[inline]
+    pub fn delta(&self) -> Duration {
+        self.delta
+    }
+
+    #[inline]
+    pub fn elapsed(&self) -> Duration {
+        self.elapsed
+    }
+
+    #[inline]
+    pub fn frame(&self) -> u64 {
+        self.frame
+    }
+
+    #[inline]
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    #[inline]
+    pub fn time_scale(&self) -> f64 {
+        self.time_scale
+    }
+
+    #[inline]
+    pub fn fixed_step(&self) -> Duration {
+        self.fixed_step
+    }
+
+    // Interpolation factor between the last two fixed updates
+    #[inline]
+    pub fn alpha(&self) -> f64 {
+        if self.fixed_step.is_zero() {
+            0.
+        } else {
+            self.accumulator.as_secs_f64() / self.fixed_step.as_secs_f64()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fixed_steps() {
+        let mut clock = FrameClock::new();
+        clock.set_fixed_step(Duration::from_millis(10));
+
+        // First tick has no previous one, so it doesn't advance the time
+        assert_eq!(clock.tick(), Duration::ZERO);
+        assert!(!clock.consume_fixed_step());
+
+        // 25 ms make two steps and leave a half of the step
+        clock.advance(Duration::from_millis(25));
+        assert!(clock.consume_fixed_step());
+        assert!(clock.consume_fixed_step());
+        assert!(!clock.consume_fixed_step());
+        assert!((clock.alpha() - 0.5).abs() < 1e-9);
+
+        // The rest is accumulated with the next frame
+        clock.advance(Duration::from_millis(5));
+        assert!(clock.consume_fixed_step());
+        assert!(!clock.consume_fixed_step());
+        assert_eq!(clock.elapsed(), Duration::from_millis(30));
+        assert_eq!(clock.frame(), 3);
+    }
+
+    #[test]
+    fn delta_clamp() {
+        let mut clock = FrameClock::new();
+
+        // Long stalls, e.g. a dragged window, are clamped to 250 ms
+        assert_eq!(
+            clock.advance(Duration::from_secs(2)),
+            Duration::from_millis(250)
+        );
+
+        clock.set_time_scale(2.);
+        assert_eq!(
+            clock.advance(Duration::from_secs(1)),
+            Duration::from_millis(500)
+        );
+        assert_eq!(
+            clock.advance(Duration::from_millis(10)),
+            Duration::from_millis(20)
+        );
+
+        clock.pause();
+        assert_eq!(clock.advance(Duration::from_millis(10)), Duration::ZERO);
+        assert_eq!(clock.elapsed(), Duration::from_millis(770));
+
+        clock.resume();
+        clock.set_time_scale(-1.);
+        assert_eq!(clock.time_scale(), 0.);
+        assert_eq!(clock.advance(Duration::from_millis(10)), Duration::ZERO);
+    }
+}
@@ -1,7 +1,10 @@
-use std::collections::{HashMap, HashSet};
+mod pool;
+mod version;
 
-use log::warn;
-use rand::{thread_rng, Rng};
+pub use pool::*;
+pub use version::*;
+
+use std::sync::Arc;
 
 use crate::{
     bind_group::{layout::BindGroupLayout, BindGroup},
@@ -9,30 +12,48 @@ use crate::{
     errors::CoreError,
     model::Model,
     pipeline::{layout::PipelineLayout, Pipeline},
+    render_pass::bundle::RenderBundle,
     shader::Shader,
     storage::Storages,
     texture::{DepthTexture, RenderTexture},
     uniform::Uniforms,
 };
 
-use custom_engine_utils::Ref;
-
-#[derive(Debug, Default)]
+#[derive(Debug)]
 pub struct Context {
-    buffers: HashMap<usize, Buffer>,
-    bind_groups: HashMap<usize, BindGroup>,
-    bind_group_layouts: HashMap<usize, BindGroupLayout>,
-    pipelines: HashMap<usize, Pipeline>,
-    pipeline_layouts: HashMap<usize, PipelineLayout>,
-    shaders: HashMap<usize, Shader>,
-    render_textures: HashMap<usize, RenderTexture>,
-    depth_textures: HashMap<usize, DepthTexture>,
-    process_textures: HashMap<usize, RenderTexture>,
-    models: HashMap<usize, Model>,
-    uniforms: HashMap<usize, Uniforms>,
-    storages: HashMap<usize, Storages>,
-
-    ids: HashSet<usize>,
+    buffers: Pool<Buffer>,
+    bind_groups: Pool<BindGroup>,
+    bind_group_layouts: Pool<BindGroupLayout>,
+    pipelines: Pool<Pipeline>,
+    pipeline_layouts: Pool<PipelineLayout>,
+    shaders: Pool<Shader>,
+    render_textures: Pool<RenderTexture>,
+    depth_textures: Pool<DepthTexture>,
+    process_textures: Pool<RenderTexture>,
+    models: Pool<Model>,
+    uniforms: Pool<Uniforms>,
+    storages: Pool<Storages>,
+    render_bundles: Pool<RenderBundle>,
+}
+
+impl Default for Context {
+    fn default() -> Self {
+        Self {
+            buffers: Pool::new("Buffer"),
+            bind_groups: Pool::new("BindGroup"),
+            bind_group_layouts: Pool::new("BindGroupLayout"),
+            pipelines: Pool::new("Pipeline"),
+            pipeline_layouts: Pool::new("PipelineLayout"),
+            shaders: Pool::new("Shader"),
+            render_textures: Pool::new("Render Texture"),
+            depth_textures: Pool::new("Depth Texture"),
+            process_textures: Pool::new("Process Texture"),
+            models: Pool::new("Model"),
+            uniforms: Pool::new("Uniforms"),
+            storages: Pool::new("Storages"),
+            render_bundles: Pool::new("Render Bundle"),
+        }
+    }
 }
 
 impl Context {
@@ -42,791 +63,715 @@ impl Context {
         }
     }
 
-    pub fn generate_unique_id(&self) -> usize {
-        let mut rng = thread_rng();
-        let mut val = rng.gen();
-
-        while self.ids.contains(&val) {
-            val = rng.gen();
-        }
-
-        val
-    }
-
     // Storages
     #[inline]
-    pub fn add_storage(&mut self, u: Storages) {
-        if self.storages.contains_key(&u.id) {
-            warn!("Storages with id: {} exist in `context`", u.id);
-        } else {
-            let _ = self.ids.insert(u.id);
-            let _ = self.storages.insert(u.id, u);
-        }
+    pub fn reserve_storage(&self) -> Handle<Storages> {
+        self.storages.reserve()
     }
 
     #[inline]
-    pub fn replace_storage(&mut self, id: usize, mut u: Storages) -> Result<(), CoreError> {
-        if self.storages.contains_key(&id) {
-            u.id = id;
-            *(self.get_storage_mut(id)?) = u;
-        } else {
-            warn!("Storages with id: {id} doesn't exist in `context`");
-        }
-
-        Ok(())
+    pub fn add_storage(&mut self, v: Storages) -> Handle<Storages> {
+        self.storages.insert(v)
     }
 
     #[inline]
-    pub fn get_storage(&self, id: usize) -> Result<&Storages, CoreError> {
-        self.storages
-            .get(&id)
-            .ok_or(CoreError::ContextFieldIsNotExist(
-                "Storages".to_string(),
-                id,
-            ))
+    pub fn replace_storage(
+        &mut self,
+        handle: Handle<Storages>,
+        v: Storages,
+    ) -> Result<(), CoreError> {
+        replace_tracked(&mut self.storages, handle, v)
     }
 
     #[inline]
-    pub fn get_storage_mut(&mut self, id: usize) -> Result<&mut Storages, CoreError> {
-        self.storages
-            .get_mut(&id)
-            .ok_or(CoreError::ContextFieldIsNotExist(
-                "Storages".to_string(),
-                id,
-            ))
+    pub fn get_storage(&self, handle: Handle<Storages>) -> Result<&Storages, CoreError> {
+        self.storages.get(handle)
     }
 
     #[inline]
-    pub fn get_storage_ref(&self, id: usize) -> Result<Ref<Storages>, CoreError> {
-        let val = self
-            .storages
-            .get(&id)
-            .ok_or(CoreError::ContextFieldIsNotExist(
-                "Storages".to_string(),
-                id,
-            ))?;
+    pub fn get_storage_mut(
+        &mut self,
+        handle: Handle<Storages>,
+    ) -> Result<&mut Storages, CoreError> {
+        get_tracked_mut(&mut self.storages, handle)
+    }
 
-        Ok(Ref::new(val))
+    #[inline]
+    pub fn get_storage_ref(&self, handle: Handle<Storages>) -> Result<Arc<Storages>, CoreError> {
+        self.storages.get_shared(handle)
     }
 
     #[inline]
-    pub fn take_storage(&mut self, id: usize) -> Result<Storages, CoreError> {
-        self.storages
-            .remove(&id)
-            .ok_or(CoreError::ContextFieldIsNotExist(
-                "Storages".to_string(),
-                id,
-            ))
+    pub fn take_storage(&mut self, handle: Handle<Storages>) -> Result<Storages, CoreError> {
+        take_tracked(&mut self.storages, handle)
     }
 
     // Uniforms
     #[inline]
-    pub fn add_uniform(&mut self, u: Uniforms) {
-        if self.uniforms.contains_key(&u.id) {
-            warn!("Uniforms with id: {} exist in `context`", u.id);
-        } else {
-            let _ = self.ids.insert(u.id);
-            let _ = self.uniforms.insert(u.id, u);
-        }
+    pub fn reserve_uniform(&self) -> Handle<Uniforms> {
+        self.uniforms.reserve()
     }
 
     #[inline]
-    pub fn replace_uniform(&mut self, id: usize, mut u: Uniforms) -> Result<(), CoreError> {
-        if self.uniforms.contains_key(&id) {
-            u.id = id;
-            *(self.get_uniform_mut(id)?) = u;
-        } else {
-            warn!("Uniforms with id: {id} doesn't exist in `context`");
-        }
-
-        Ok(())
+    pub fn add_uniform(&mut self, v: Uniforms) -> Handle<Uniforms> {
+        self.uniforms.insert(v)
     }
 
     #[inline]
-    pub fn get_uniform(&self, id: usize) -> Result<&Uniforms, CoreError> {
-        self.uniforms
-            .get(&id)
-            .ok_or(CoreError::ContextFieldIsNotExist(
-                "Uniforms".to_string(),
-                id,
-            ))
+    pub fn replace_uniform(
+        &mut self,
+        handle: Handle<Uniforms>,
+        v: Uniforms,
+    ) -> Result<(), CoreError> {
+        replace_tracked(&mut self.uniforms, handle, v)
     }
 
     #[inline]
-    pub fn get_uniform_mut(&mut self, id: usize) -> Result<&mut Uniforms, CoreError> {
-        self.uniforms
-            .get_mut(&id)
-            .ok_or(CoreError::ContextFieldIsNotExist(
-                "Uniforms".to_string(),
-                id,
-            ))
+    pub fn get_uniform(&self, handle: Handle<Uniforms>) -> Result<&Uniforms, CoreError> {
+        self.uniforms.get(handle)
     }
 
     #[inline]
-    pub fn get_uniform_ref(&self, id: usize) -> Result<Ref<Uniforms>, CoreError> {
-        let val = self
-            .uniforms
-            .get(&id)
-            .ok_or(CoreError::ContextFieldIsNotExist(
-                "Uniforms".to_string(),
-                id,
-            ))?;
+    pub fn get_uniform_mut(
+        &mut self,
+        handle: Handle<Uniforms>,
+    ) -> Result<&mut Uniforms, CoreError> {
+        get_tracked_mut(&mut self.uniforms, handle)
+    }
 
-        Ok(Ref::new(val))
+    #[inline]
+    pub fn get_uniform_ref(&self, handle: Handle<Uniforms>) -> Result<Arc<Uniforms>, CoreError> {
+        self.uniforms.get_shared(handle)
     }
 
     #[inline]
-    pub fn take_uniform(&mut self, id: usize) -> Result<Uniforms, CoreError> {
-        self.uniforms
-            .remove(&id)
-            .ok_or(CoreError::ContextFieldIsNotExist(
-                "Uniforms".to_string(),
-                id,
-            ))
+    pub fn take_uniform(&mut self, handle: Handle<Uniforms>) -> Result<Uniforms, CoreError> {
+        take_tracked(&mut self.uniforms, handle)
     }
 
     // Model
     #[inline]
-    pub fn add_model(&mut self, m: Model) {
-        if self.models.contains_key(&m.id) {
-            warn!("Model with id: {} exist in `context`", m.id);
-        } else {
-            let _ = self.ids.insert(m.id);
-            let _ = self.models.insert(m.id, m);
-        }
+    pub fn reserve_model(&self) -> Handle<Model> {
+        self.models.reserve()
     }
 
     #[inline]
-    pub fn replace_model(&mut self, id: usize, mut m: Model) -> Result<(), CoreError> {
-        if self.models.contains_key(&id) {
-            m.id = id;
-            *(self.get_model_mut(id)?) = m;
-        } else {
-            warn!("Model with id: {id} doesn't exist in `context`");
-        }
-
-        Ok(())
+    pub fn add_model(&mut self, v: Model) -> Handle<Model> {
+        self.models.insert(v)
     }
 
     #[inline]
-    pub fn get_model(&self, id: usize) -> Result<&Model, CoreError> {
-        self.models
-            .get(&id)
-            .ok_or(CoreError::ContextFieldIsNotExist("Model".to_string(), id))
+    pub fn replace_model(&mut self, handle: Handle<Model>, v: Model) -> Result<(), CoreError> {
+        replace_tracked(&mut self.models, handle, v)
     }
 
     #[inline]
-    pub fn get_model_mut(&mut self, id: usize) -> Result<&mut Model, CoreError> {
-        self.models
-            .get_mut(&id)
-            .ok_or(CoreError::ContextFieldIsNotExist("Model".to_string(), id))
+    pub fn get_model(&self, handle: Handle<Model>) -> Result<&Model, CoreError> {
+        self.models.get(handle)
     }
 
     #[inline]
-    pub fn get_model_ref(&self, id: usize) -> Result<Ref<Model>, CoreError> {
-        let val = self
-            .models
-            .get(&id)
-            .ok_or(CoreError::ContextFieldIsNotExist("Model".to_string(), id))?;
+    pub fn get_model_mut(&mut self, handle: Handle<Model>) -> Result<&mut Model, CoreError> {
+        get_tracked_mut(&mut self.models, handle)
+    }
 
-        Ok(Ref::new(val))
+    #[inline]
+    pub fn get_model_ref(&self, handle: Handle<Model>) -> Result<Arc<Model>, CoreError> {
+        self.models.get_shared(handle)
     }
 
     #[inline]
-    pub fn take_model(&mut self, id: usize) -> Result<Model, CoreError> {
-        self.models
-            .remove(&id)
-            .ok_or(CoreError::ContextFieldIsNotExist("Model".to_string(), id))
+    pub fn take_model(&mut self, handle: Handle<Model>) -> Result<Model, CoreError> {
+        take_tracked(&mut self.models, handle)
     }
 
     // Buffer
     #[inline]
-    pub fn add_buffer(&mut self, b: Buffer) {
-        if self.buffers.contains_key(&b.id) {
-            warn!("Buffer with id: {} exist in `context`", b.id);
-        } else {
-            let _ = self.ids.insert(b.id);
-            let _ = self.buffers.insert(b.id, b);
-        }
+    pub fn reserve_buffer(&self) -> Handle<Buffer> {
+        self.buffers.reserve()
     }
 
     #[inline]
-    pub fn replace_buffer(&mut self, id: usize, mut b: Buffer) -> Result<(), CoreError> {
-        if self.buffers.contains_key(&id) {
-            b.id = id;
-            *(self.get_buffer_mut(id)?) = b;
-        } else {
-            warn!("Buffer with id: {id} doesn't exist in `context`");
-        }
+    pub fn add_buffer(&mut self, v: Buffer) -> Handle<Buffer> {
+        self.buffers.insert(v)
+    }
 
-        Ok(())
+    #[inline]
+    pub fn replace_buffer(&mut self, handle: Handle<Buffer>, v: Buffer) -> Result<(), CoreError> {
+        replace_tracked(&mut self.buffers, handle, v)
     }
 
     #[inline]
-    pub fn get_buffer(&self, id: usize) -> Result<&Buffer, CoreError> {
-        self.buffers
-            .get(&id)
-            .ok_or(CoreError::ContextFieldIsNotExist("Buffer".to_string(), id))
+    pub fn get_buffer(&self, handle: Handle<Buffer>) -> Result<&Buffer, CoreError> {
+        self.buffers.get(handle)
     }
 
     #[inline]
-    pub fn get_buffer_mut(&mut self, id: usize) -> Result<&mut Buffer, CoreError> {
-        self.buffers
-            .get_mut(&id)
-            .ok_or(CoreError::ContextFieldIsNotExist("Buffer".to_string(), id))
+    pub fn get_buffer_mut(&mut self, handle: Handle<Buffer>) -> Result<&mut Buffer, CoreError> {
+        get_tracked_mut(&mut self.buffers, handle)
     }
 
     #[inline]
-    pub fn get_buffer_ref(&self, id: usize) -> Result<Ref<Buffer>, CoreError> {
-        let val = self
-            .buffers
-            .get(&id)
-            .ok_or(CoreError::ContextFieldIsNotExist("Buffer".to_string(), id))?;
+    pub fn get_buffer_ref(&self, handle: Handle<Buffer>) -> Result<Arc<Buffer>, CoreError> {
+        self.buffers.get_shared(handle)
+    }
 
-        Ok(Ref::new(val))
+    #[inline]
+    pub fn take_buffer(&mut self, handle: Handle<Buffer>) -> Result<Buffer, CoreError> {
+        take_tracked(&mut self.buffers, handle)
     }
 
+    // Bind Group Layout
     #[inline]
-    pub fn take_buffer(&mut self, id: usize) -> Result<Buffer, CoreError> {
-        self.buffers
-            .remove(&id)
-            .ok_or(CoreError::ContextFieldIsNotExist("Buffer".to_string(), id))
+    pub fn reserve_bind_group_layout(&self) -> Handle<BindGroupLayout> {
+        self.bind_group_layouts.reserve()
     }
 
-    // Bind group
     #[inline]
-    pub fn add_bind_group_layout(&mut self, bgl: BindGroupLayout) {
-        if self.bind_group_layouts.contains_key(&bgl.id) {
-            warn!("BindGroupLayout with id: {} exist in `context`", bgl.id);
-        } else {
-            let _ = self.ids.insert(bgl.id);
-            let _ = self.bind_group_layouts.insert(bgl.id, bgl);
-        }
+    pub fn add_bind_group_layout(&mut self, v: BindGroupLayout) -> Handle<BindGroupLayout> {
+        self.bind_group_layouts.insert(v)
     }
 
     #[inline]
     pub fn replace_bind_group_layout(
         &mut self,
-        id: usize,
-        mut bgl: BindGroupLayout,
+        handle: Handle<BindGroupLayout>,
+        v: BindGroupLayout,
     ) -> Result<(), CoreError> {
-        if self.bind_group_layouts.contains_key(&id) {
-            bgl.id = id;
-            *(self.get_bind_group_layout_mut(id)?) = bgl;
-        } else {
-            warn!("BindGroupLayout with id: {id} doesn't exist in `context`",);
-        }
-
-        Ok(())
+        self.bind_group_layouts.replace(handle, v)
     }
 
     #[inline]
-    pub fn get_bind_group_layout(&self, id: usize) -> Result<&BindGroupLayout, CoreError> {
-        self.bind_group_layouts
-            .get(&id)
-            .ok_or(CoreError::ContextFieldIsNotExist(
-                "BindGroupLayout".to_string(),
-                id,
-            ))
+    pub fn get_bind_group_layout(
+        &self,
+        handle: Handle<BindGroupLayout>,
+    ) -> Result<&BindGroupLayout, CoreError> {
+        self.bind_group_layouts.get(handle)
     }
 
     #[inline]
     pub fn get_bind_group_layout_mut(
         &mut self,
-        id: usize,
+        handle: Handle<BindGroupLayout>,
     ) -> Result<&mut BindGroupLayout, CoreError> {
-        self.bind_group_layouts
-            .get_mut(&id)
-            .ok_or(CoreError::ContextFieldIsNotExist(
-                "BindGroupLayout".to_string(),
-                id,
-            ))
+        self.bind_group_layouts.get_mut(handle)
     }
 
     #[inline]
-    pub fn get_bind_group_layout_ref(&self, id: usize) -> Result<Ref<BindGroupLayout>, CoreError> {
-        let val = self
-            .bind_group_layouts
-            .get(&id)
-            .ok_or(CoreError::ContextFieldIsNotExist(
-                "BindGroupLayout".to_string(),
-                id,
-            ))?;
-
-        Ok(Ref::new(val))
+    pub fn get_bind_group_layout_ref(
+        &self,
+        handle: Handle<BindGroupLayout>,
+    ) -> Result<Arc<BindGroupLayout>, CoreError> {
+        self.bind_group_layouts.get_shared(handle)
     }
 
     #[inline]
-    pub fn take_bind_group_layout(&mut self, id: usize) -> Result<BindGroupLayout, CoreError> {
-        self.bind_group_layouts
-            .remove(&id)
-            .ok_or(CoreError::ContextFieldIsNotExist(
-                "BindGroupLayout".to_string(),
-                id,
-            ))
+    pub fn take_bind_group_layout(
+        &mut self,
+        handle: Handle<BindGroupLayout>,
+    ) -> Result<BindGroupLayout, CoreError> {
+        self.bind_group_layouts.take(handle)
     }
 
+    // Bind Group
     #[inline]
-    pub fn add_bind_group(&mut self, bg: BindGroup) {
-        if self.bind_groups.contains_key(&bg.id) {
-            warn!("BindGroup with id: {} exist in `context`", bg.id);
-        } else {
-            let _ = self.ids.insert(bg.id);
-            let _ = self.bind_groups.insert(bg.id, bg);
-        }
+    pub fn reserve_bind_group(&self) -> Handle<BindGroup> {
+        self.bind_groups.reserve()
     }
 
     #[inline]
-    pub fn replace_bind_group(&mut self, id: usize, mut bg: BindGroup) -> Result<(), CoreError> {
-        if self.bind_groups.contains_key(&id) {
-            bg.id = id;
-            *(self.get_bind_group_mut(id)?) = bg;
-        } else {
-            warn!("BindGroup with id: {id} doesn't exist in `context`",);
-        }
+    pub fn add_bind_group(&mut self, v: BindGroup) -> Handle<BindGroup> {
+        self.bind_groups.insert(v)
+    }
 
-        Ok(())
+    #[inline]
+    pub fn replace_bind_group(
+        &mut self,
+        handle: Handle<BindGroup>,
+        v: BindGroup,
+    ) -> Result<(), CoreError> {
+        replace_tracked(&mut self.bind_groups, handle, v)
     }
 
     #[inline]
-    pub fn get_bind_group(&self, id: usize) -> Result<&BindGroup, CoreError> {
-        self.bind_groups
-            .get(&id)
-            .ok_or(CoreError::ContextFieldIsNotExist(
-                "BindGroup".to_string(),
-                id,
-            ))
+    pub fn get_bind_group(&self, handle: Handle<BindGroup>) -> Result<&BindGroup, CoreError> {
+        self.bind_groups.get(handle)
     }
 
     #[inline]
-    pub fn get_bind_group_mut(&mut self, id: usize) -> Result<&mut BindGroup, CoreError> {
-        self.bind_groups
-            .get_mut(&id)
-            .ok_or(CoreError::ContextFieldIsNotExist(
-                "BindGroup".to_string(),
-                id,
-            ))
+    pub fn get_bind_group_mut(
+        &mut self,
+        handle: Handle<BindGroup>,
+    ) -> Result<&mut BindGroup, CoreError> {
+        get_tracked_mut(&mut self.bind_groups, handle)
     }
 
     #[inline]
-    pub fn get_bind_group_ref(&self, id: usize) -> Result<Ref<BindGroup>, CoreError> {
-        let val = self
-            .bind_groups
-            .get(&id)
-            .ok_or(CoreError::ContextFieldIsNotExist(
-                "BindGroup".to_string(),
-                id,
-            ))?;
+    pub fn get_bind_group_ref(
+        &self,
+        handle: Handle<BindGroup>,
+    ) -> Result<Arc<BindGroup>, CoreError> {
+        self.bind_groups.get_shared(handle)
+    }
 
-        Ok(Ref::new(val))
+    #[inline]
+    pub fn take_bind_group(&mut self, handle: Handle<BindGroup>) -> Result<BindGroup, CoreError> {
+        take_tracked(&mut self.bind_groups, handle)
     }
 
+    // Pipeline Layout
     #[inline]
-    pub fn take_bind_group(&mut self, id: usize) -> Result<BindGroup, CoreError> {
-        self.bind_groups
-            .remove(&id)
-            .ok_or(CoreError::ContextFieldIsNotExist(
-                "BindGroup".to_string(),
-                id,
-            ))
+    pub fn reserve_pipeline_layout(&self) -> Handle<PipelineLayout> {
+        self.pipeline_layouts.reserve()
     }
 
-    // Pipeline
     #[inline]
-    pub fn add_pipeline_layout(&mut self, pl: PipelineLayout) {
-        if self.pipeline_layouts.contains_key(&pl.id) {
-            warn!("PipelineLayout with id: {} exist in `context`", pl.id);
-        } else {
-            let _ = self.ids.insert(pl.id);
-            let _ = self.pipeline_layouts.insert(pl.id, pl);
-        }
+    pub fn add_pipeline_layout(&mut self, v: PipelineLayout) -> Handle<PipelineLayout> {
+        self.pipeline_layouts.insert(v)
     }
 
     #[inline]
     pub fn replace_pipeline_layout(
         &mut self,
-        id: usize,
-        mut pl: PipelineLayout,
+        handle: Handle<PipelineLayout>,
+        v: PipelineLayout,
     ) -> Result<(), CoreError> {
-        if self.pipeline_layouts.contains_key(&id) {
-            pl.id = id;
-            *(self.get_pipeline_layout_mut(id)?) = pl;
-        } else {
-            warn!("PipelineLayout with id: {id} doesn't exist in `context`",);
-        }
-
-        Ok(())
+        self.pipeline_layouts.replace(handle, v)
     }
 
     #[inline]
-    pub fn get_pipeline_layout(&self, id: usize) -> Result<&PipelineLayout, CoreError> {
-        self.pipeline_layouts
-            .get(&id)
-            .ok_or(CoreError::ContextFieldIsNotExist(
-                "PipelineLayout".to_string(),
-                id,
-            ))
+    pub fn get_pipeline_layout(
+        &self,
+        handle: Handle<PipelineLayout>,
+    ) -> Result<&PipelineLayout, CoreError> {
+        self.pipeline_layouts.get(handle)
     }
 
     #[inline]
-    pub fn get_pipeline_layout_mut(&mut self, id: usize) -> Result<&mut PipelineLayout, CoreError> {
-        self.pipeline_layouts
-            .get_mut(&id)
-            .ok_or(CoreError::ContextFieldIsNotExist(
-                "PipelineLayout".to_string(),
-                id,
-            ))
+    pub fn get_pipeline_layout_mut(
+        &mut self,
+        handle: Handle<PipelineLayout>,
+    ) -> Result<&mut PipelineLayout, CoreError> {
+        self.pipeline_layouts.get_mut(handle)
     }
 
     #[inline]
-    pub fn get_pipeline_layout_ref(&self, id: usize) -> Result<Ref<PipelineLayout>, CoreError> {
-        let val = self
-            .pipeline_layouts
-            .get(&id)
-            .ok_or(CoreError::ContextFieldIsNotExist(
-                "PipelineLayout".to_string(),
-                id,
-            ))?;
-
-        Ok(Ref::new(val))
+    pub fn get_pipeline_layout_ref(
+        &self,
+        handle: Handle<PipelineLayout>,
+    ) -> Result<Arc<PipelineLayout>, CoreError> {
+        self.pipeline_layouts.get_shared(handle)
     }
 
     #[inline]
-    pub fn take_pipeline_layout(&mut self, id: usize) -> Result<PipelineLayout, CoreError> {
-        self.pipeline_layouts
-            .remove(&id)
-            .ok_or(CoreError::ContextFieldIsNotExist(
-                "PipelineLayout".to_string(),
-                id,
-            ))
+    pub fn take_pipeline_layout(
+        &mut self,
+        handle: Handle<PipelineLayout>,
+    ) -> Result<PipelineLayout, CoreError> {
+        self.pipeline_layouts.take(handle)
     }
 
+    // Pipeline
     #[inline]
-    pub fn add_pipeline(&mut self, p: Pipeline) {
-        if self.pipelines.contains_key(&p.id) {
-            warn!("Pipeline with id: {} exist in `context`", p.id);
-        } else {
-            let _ = self.ids.insert(p.id);
-            let _ = self.pipelines.insert(p.id, p);
-        }
+    pub fn reserve_pipeline(&self) -> Handle<Pipeline> {
+        self.pipelines.reserve()
     }
 
     #[inline]
-    pub fn replace_pipeline(&mut self, id: usize, mut p: Pipeline) -> Result<(), CoreError> {
-        if self.pipelines.contains_key(&id) {
-            p.id = id;
-            *(self.get_pipeline_mut(id)?) = p;
-        } else {
-            warn!("Pipeline with id: {id} doesn't exist in `context`",);
-        }
-
-        Ok(())
+    pub fn add_pipeline(&mut self, v: Pipeline) -> Handle<Pipeline> {
+        self.pipelines.insert(v)
     }
 
     #[inline]
-    pub fn get_pipeline(&self, id: usize) -> Result<&Pipeline, CoreError> {
-        self.pipelines
-            .get(&id)
-            .ok_or(CoreError::ContextFieldIsNotExist(
-                "Pipeline".to_string(),
-                id,
-            ))
+    pub fn replace_pipeline(
+        &mut self,
+        handle: Handle<Pipeline>,
+        v: Pipeline,
+    ) -> Result<(), CoreError> {
+        replace_tracked(&mut self.pipelines, handle, v)
     }
 
     #[inline]
-    pub fn get_pipeline_mut(&mut self, id: usize) -> Result<&mut Pipeline, CoreError> {
-        self.pipelines
-            .get_mut(&id)
-            .ok_or(CoreError::ContextFieldIsNotExist(
-                "Pipeline".to_string(),
-                id,
-            ))
+    pub fn get_pipeline(&self, handle: Handle<Pipeline>) -> Result<&Pipeline, CoreError> {
+        self.pipelines.get(handle)
     }
 
     #[inline]
-    pub fn get_pipeline_ref(&self, id: usize) -> Result<Ref<Pipeline>, CoreError> {
-        let val = self
-            .pipelines
-            .get(&id)
-            .ok_or(CoreError::ContextFieldIsNotExist(
-                "Pipeline".to_string(),
-                id,
-            ))?;
+    pub fn get_pipeline_mut(
+        &mut self,
+        handle: Handle<Pipeline>,
+    ) -> Result<&mut Pipeline, CoreError> {
+        get_tracked_mut(&mut self.pipelines, handle)
+    }
 
-        Ok(Ref::new(val))
+    #[inline]
+    pub fn get_pipeline_ref(&self, handle: Handle<Pipeline>) -> Result<Arc<Pipeline>, CoreError> {
+        self.pipelines.get_shared(handle)
     }
 
     #[inline]
-    pub fn take_pipeline(&mut self, id: usize) -> Result<Pipeline, CoreError> {
-        self.pipelines
-            .remove(&id)
-            .ok_or(CoreError::ContextFieldIsNotExist(
-                "Pipeline".to_string(),
-                id,
-            ))
+    pub fn take_pipeline(&mut self, handle: Handle<Pipeline>) -> Result<Pipeline, CoreError> {
+        take_tracked(&mut self.pipelines, handle)
     }
 
     // Shader
     #[inline]
-    pub fn add_shader(&mut self, sh: Shader) {
-        if self.shaders.contains_key(&sh.id()) {
-            warn!("Shader with id: {} exist in `context`", sh.id());
-        } else {
-            let _ = self.ids.insert(sh.id());
-            let _ = self.shaders.insert(sh.id(), sh);
-        }
+    pub fn reserve_shader(&self) -> Handle<Shader> {
+        self.shaders.reserve()
     }
 
     #[inline]
-    pub fn replace_shader(&mut self, id: usize, mut sh: Shader) -> Result<(), CoreError> {
-        if self.shaders.contains_key(&id) {
-            *(sh.id_mut()) = id;
-            *(self.get_shader_mut(id)?) = sh;
-        } else {
-            warn!("Shader with id: {id} doesn't exist in `context`",);
-        }
-
-        Ok(())
+    pub fn add_shader(&mut self, v: Shader) -> Handle<Shader> {
+        self.shaders.insert(v)
     }
 
     #[inline]
-    pub fn get_shader(&self, id: usize) -> Result<&Shader, CoreError> {
-        self.shaders
-            .get(&id)
-            .ok_or(CoreError::ContextFieldIsNotExist("Shader".to_string(), id))
+    pub fn replace_shader(&mut self, handle: Handle<Shader>, v: Shader) -> Result<(), CoreError> {
+        self.shaders.replace(handle, v)
     }
 
     #[inline]
-    pub fn get_shader_mut(&mut self, id: usize) -> Result<&mut Shader, CoreError> {
-        self.shaders
-            .get_mut(&id)
-            .ok_or(CoreError::ContextFieldIsNotExist("Shader".to_string(), id))
+    pub fn get_shader(&self, handle: Handle<Shader>) -> Result<&Shader, CoreError> {
+        self.shaders.get(handle)
     }
 
     #[inline]
-    pub fn get_shader_ref(&self, id: usize) -> Result<Ref<Shader>, CoreError> {
-        let val = self
-            .shaders
-            .get(&id)
-            .ok_or(CoreError::ContextFieldIsNotExist("Shader".to_string(), id))?;
+    pub fn get_shader_mut(&mut self, handle: Handle<Shader>) -> Result<&mut Shader, CoreError> {
+        self.shaders.get_mut(handle)
+    }
 
-        Ok(Ref::new(val))
+    #[inline]
+    pub fn get_shader_ref(&self, handle: Handle<Shader>) -> Result<Arc<Shader>, CoreError> {
+        self.shaders.get_shared(handle)
     }
 
     #[inline]
-    pub fn take_shader(&mut self, id: usize) -> Result<Shader, CoreError> {
-        self.shaders
-            .remove(&id)
-            .ok_or(CoreError::ContextFieldIsNotExist("Shader".to_string(), id))
+    pub fn take_shader(&mut self, handle: Handle<Shader>) -> Result<Shader, CoreError> {
+        self.shaders.take(handle)
     }
 
     // Render Texture
     #[inline]
-    pub fn add_render_texture(&mut self, rt: RenderTexture) {
-        if self.render_textures.contains_key(&rt.id) {
-            warn!("Render Texture with id: {} exist in `context`", rt.id);
-        } else {
-            let _ = self.ids.insert(rt.id);
-            let _ = self.render_textures.insert(rt.id, rt);
-        }
+    pub fn reserve_render_texture(&self) -> Handle<RenderTexture> {
+        self.render_textures.reserve()
+    }
+
+    #[inline]
+    pub fn add_render_texture(&mut self, v: RenderTexture) -> Handle<RenderTexture> {
+        self.render_textures.insert(v)
     }
 
     #[inline]
     pub fn replace_render_texture(
         &mut self,
-        id: usize,
-        mut rt: RenderTexture,
+        handle: Handle<RenderTexture>,
+        v: RenderTexture,
     ) -> Result<(), CoreError> {
-        if self.render_textures.contains_key(&id) {
-            rt.id = id;
-            *(self.get_render_texture_mut(id)?) = rt;
-        } else {
-            warn!("Render Texture with id: {id} doesn't exist in `context`",);
-        }
-
-        Ok(())
+        replace_tracked(&mut self.render_textures, handle, v)
     }
 
     #[inline]
-    pub fn get_render_texture(&self, id: usize) -> Result<&RenderTexture, CoreError> {
-        self.render_textures
-            .get(&id)
-            .ok_or(CoreError::ContextFieldIsNotExist(
-                "Render Texture".to_string(),
-                id,
-            ))
+    pub fn get_render_texture(
+        &self,
+        handle: Handle<RenderTexture>,
+    ) -> Result<&RenderTexture, CoreError> {
+        self.render_textures.get(handle)
     }
 
     #[inline]
-    pub fn get_render_texture_mut(&mut self, id: usize) -> Result<&mut RenderTexture, CoreError> {
-        self.render_textures
-            .get_mut(&id)
-            .ok_or(CoreError::ContextFieldIsNotExist(
-                "Render Texture".to_string(),
-                id,
-            ))
+    pub fn get_render_texture_mut(
+        &mut self,
+        handle: Handle<RenderTexture>,
+    ) -> Result<&mut RenderTexture, CoreError> {
+        get_tracked_mut(&mut self.render_textures, handle)
     }
 
     #[inline]
-    pub fn get_render_texture_ref(&self, id: usize) -> Result<Ref<RenderTexture>, CoreError> {
-        let val = self
-            .render_textures
-            .get(&id)
-            .ok_or(CoreError::ContextFieldIsNotExist(
-                "Render Texture".to_string(),
-                id,
-            ))?;
-
-        Ok(Ref::new(val))
+    pub fn get_render_texture_ref(
+        &self,
+        handle: Handle<RenderTexture>,
+    ) -> Result<Arc<RenderTexture>, CoreError> {
+        self.render_textures.get_shared(handle)
     }
 
     #[inline]
-    pub fn take_render_texture(&mut self, id: usize) -> Result<RenderTexture, CoreError> {
-        self.render_textures
-            .remove(&id)
-            .ok_or(CoreError::ContextFieldIsNotExist(
-                "Render Texture".to_string(),
-                id,
-            ))
+    pub fn take_render_texture(
+        &mut self,
+        handle: Handle<RenderTexture>,
+    ) -> Result<RenderTexture, CoreError> {
+        take_tracked(&mut self.render_textures, handle)
     }
 
     // Depth Texture
     #[inline]
-    pub fn add_depth_texture(&mut self, rt: DepthTexture) {
-        if self.depth_textures.contains_key(&rt.id) {
-            warn!("Depth Texture with id: {} exist in `context`", rt.id);
-        } else {
-            let _ = self.ids.insert(rt.id);
-            let _ = self.depth_textures.insert(rt.id, rt);
-        }
+    pub fn reserve_depth_texture(&self) -> Handle<DepthTexture> {
+        self.depth_textures.reserve()
+    }
+
+    #[inline]
+    pub fn add_depth_texture(&mut self, v: DepthTexture) -> Handle<DepthTexture> {
+        self.depth_textures.insert(v)
     }
 
     #[inline]
     pub fn replace_depth_texture(
         &mut self,
-        id: usize,
-        mut rt: DepthTexture,
+        handle: Handle<DepthTexture>,
+        v: DepthTexture,
     ) -> Result<(), CoreError> {
-        if self.depth_textures.contains_key(&id) {
-            rt.id = id;
-            *(self.get_depth_texture_mut(id)?) = rt;
-        } else {
-            warn!("Depth Texture with id: {id} doesn't exist in `context`",);
-        }
-
-        Ok(())
+        self.depth_textures.replace(handle, v)
     }
 
     #[inline]
-    pub fn get_depth_texture(&self, id: usize) -> Result<&DepthTexture, CoreError> {
-        self.depth_textures
-            .get(&id)
-            .ok_or(CoreError::ContextFieldIsNotExist(
-                "Depth Texture".to_string(),
-                id,
-            ))
+    pub fn get_depth_texture(
+        &self,
+        handle: Handle<DepthTexture>,
+    ) -> Result<&DepthTexture, CoreError> {
+        self.depth_textures.get(handle)
     }
 
     #[inline]
-    pub fn get_depth_texture_mut(&mut self, id: usize) -> Result<&mut DepthTexture, CoreError> {
-        self.depth_textures
-            .get_mut(&id)
-            .ok_or(CoreError::ContextFieldIsNotExist(
-                "Depth Texture".to_string(),
-                id,
-            ))
+    pub fn get_depth_texture_mut(
+        &mut self,
+        handle: Handle<DepthTexture>,
+    ) -> Result<&mut DepthTexture, CoreError> {
+        self.depth_textures.get_mut(handle)
     }
 
     #[inline]
-    pub fn get_depth_texture_ref(&self, id: usize) -> Result<Ref<DepthTexture>, CoreError> {
-        let val = self
-            .depth_textures
-            .get(&id)
-            .ok_or(CoreError::ContextFieldIsNotExist(
-                "Depth Texture".to_string(),
-                id,
-            ))?;
-
-        Ok(Ref::new(val))
+    pub fn get_depth_texture_ref(
+        &self,
+        handle: Handle<DepthTexture>,
+    ) -> Result<Arc<DepthTexture>, CoreError> {
+        self.depth_textures.get_shared(handle)
     }
 
     #[inline]
-    pub fn take_depth_texture(&mut self, id: usize) -> Result<DepthTexture, CoreError> {
-        self.depth_textures
-            .remove(&id)
-            .ok_or(CoreError::ContextFieldIsNotExist(
-                "Depth Texture".to_string(),
-                id,
-            ))
+    pub fn take_depth_texture(
+        &mut self,
+        handle: Handle<DepthTexture>,
+    ) -> Result<DepthTexture, CoreError> {
+        self.depth_textures.take(handle)
     }
 
     // Process Texture
     #[inline]
-    pub fn add_process_texture(&mut self, rt: RenderTexture) {
-        if self.process_textures.contains_key(&rt.id) {
-            warn!("Process Texture with id: {} exist in `context`", rt.id);
-        } else {
-            let _ = self.ids.insert(rt.id);
-            let _ = self.process_textures.insert(rt.id, rt);
-        }
+    pub fn reserve_process_texture(&self) -> Handle<RenderTexture> {
+        self.process_textures.reserve()
+    }
+
+    #[inline]
+    pub fn add_process_texture(&mut self, v: RenderTexture) -> Handle<RenderTexture> {
+        self.process_textures.insert(v)
     }
 
     #[inline]
     pub fn replace_process_texture(
         &mut self,
-        id: usize,
-        mut rt: RenderTexture,
+        handle: Handle<RenderTexture>,
+        v: RenderTexture,
     ) -> Result<(), CoreError> {
-        if self.process_textures.contains_key(&id) {
-            rt.id = id;
-            *(self.get_process_texture_mut(id)?) = rt;
-        } else {
-            warn!("Process Texture with id: {id} doesn't exist in `context`",);
-        }
+        replace_tracked(&mut self.process_textures, handle, v)
+    }
+
+    #[inline]
+    pub fn get_process_texture(
+        &self,
+        handle: Handle<RenderTexture>,
+    ) -> Result<&RenderTexture, CoreError> {
+        self.process_textures.get(handle)
+    }
+
+    #[inline]
+    pub fn get_process_texture_mut(
+        &mut self,
+        handle: Handle<RenderTexture>,
+    ) -> Result<&mut RenderTexture, CoreError> {
+        get_tracked_mut(&mut self.process_textures, handle)
+    }
+
+    #[inline]
+    pub fn get_process_texture_ref(
+        &self,
+        handle: Handle<RenderTexture>,
+    ) -> Result<Arc<RenderTexture>, CoreError> {
+        self.process_textures.get_shared(handle)
+    }
+
+    #[inline]
+    pub fn take_process_texture(
+        &mut self,
+        handle: Handle<RenderTexture>,
+    ) -> Result<RenderTexture, CoreError> {
+        take_tracked(&mut self.process_textures, handle)
+    }
 
-        Ok(())
+    // Render bundles
+    #[inline]
+    pub fn reserve_render_bundle(&self) -> Handle<RenderBundle> {
+        self.render_bundles.reserve()
     }
 
     #[inline]
-    pub fn get_process_texture(&self, id: usize) -> Result<&RenderTexture, CoreError> {
-        self.process_textures
-            .get(&id)
-            .ok_or(CoreError::ContextFieldIsNotExist(
-                "Process Texture".to_string(),
-                id,
-            ))
+    pub fn add_render_bundle(&mut self, v: RenderBundle) -> Handle<RenderBundle> {
+        self.render_bundles.insert(v)
     }
 
     #[inline]
-    pub fn get_process_texture_mut(&mut self, id: usize) -> Result<&mut RenderTexture, CoreError> {
-        self.process_textures
-            .get_mut(&id)
-            .ok_or(CoreError::ContextFieldIsNotExist(
-                "Process Texture".to_string(),
-                id,
-            ))
+    pub fn replace_render_bundle(
+        &mut self,
+        handle: Handle<RenderBundle>,
+        v: RenderBundle,
+    ) -> Result<(), CoreError> {
+        self.render_bundles.replace(handle, v)
     }
 
     #[inline]
-    pub fn get_process_texture_ref(&self, id: usize) -> Result<Ref<RenderTexture>, CoreError> {
-        let val = self
-            .process_textures
-            .get(&id)
-            .ok_or(CoreError::ContextFieldIsNotExist(
-                "Process Texture".to_string(),
-                id,
-            ))?;
+    pub fn get_render_bundle(
+        &self,
+        handle: Handle<RenderBundle>,
+    ) -> Result<&RenderBundle, CoreError> {
+        self.render_bundles.get(handle)
+    }
 
-        Ok(Ref::new(val))
+    #[inline]
+    pub fn get_render_bundle_ref(
+        &self,
+        handle: Handle<RenderBundle>,
+    ) -> Result<Arc<RenderBundle>, CoreError> {
+        self.render_bundles.get_shared(handle)
     }
 
     #[inline]
-    pub fn take_process_texture(&mut self, id: usize) -> Result<RenderTexture, CoreError> {
-        self.process_textures
-            .remove(&id)
-            .ok_or(CoreError::ContextFieldIsNotExist(
-                "Process Texture".to_string(),
-                id,
-            ))
+    pub fn take_render_bundle(
+        &mut self,
+        handle: Handle<RenderBundle>,
+    ) -> Result<RenderBundle, CoreError> {
+        self.render_bundles.take(handle)
+    }
+}
+
+// Render bundles, which recorded the resource, are invalidated by the changed version
+fn replace_tracked<T: PoolItem + Tracked>(
+    pool: &mut Pool<T>,
+    handle: Handle<T>,
+    v: T,
+) -> Result<(), CoreError> {
+    // The replaced value can still be shared, so it isn't enough to drop it
+    pool.get(handle)?.touch();
+    pool.replace(handle, v)
+}
+
+fn get_tracked_mut<T: PoolItem + Tracked>(
+    pool: &mut Pool<T>,
+    handle: Handle<T>,
+) -> Result<&mut T, CoreError> {
+    let v = pool.get_mut(handle)?;
+    v.touch();
+
+    Ok(v)
+}
+
+fn take_tracked<T: PoolItem + Tracked>(
+    pool: &mut Pool<T>,
+    handle: Handle<T>,
+) -> Result<T, CoreError> {
+    let v = pool.take(handle)?;
+    v.touch();
+
+    Ok(v)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Stands for a model, the nested meshes have their own versions
+    #[derive(Debug, Default)]
+    struct Item {
+        id: usize,
+        version: ResourceVersion,
+        meshes: Vec<ResourceVersion>,
+    }
+
+    impl PoolItem for Item {
+        fn pool_index(&self) -> usize {
+            self.id
+        }
+
+        fn set_pool_index(&mut self, index: usize) {
+            self.id = index;
+        }
+    }
+
+    impl Tracked for Item {
+        fn record(&self, resources: &mut RecordedResources) {
+            resources.push(&self.version);
+            self.meshes.iter().for_each(|m| resources.push(m));
+        }
+
+        fn touch(&self) {
+            self.version.bump();
+        }
+    }
+
+    fn item() -> Item {
+        Item {
+            meshes: vec![Default::default(), Default::default()],
+            ..Default::default()
+        }
+    }
+
+    fn record(item: &Item) -> RecordedResources {
+        let mut resources = RecordedResources::default();
+        resources.record(item);
+        resources.record(item);
+        resources.dedup();
+
+        resources
+    }
+
+    #[test]
+    fn replaced_model_invalidates_bundle() {
+        let mut pool = Pool::new("Item");
+        let handle = pool.insert(item());
+        let other = pool.insert(item());
+
+        let resources = record(pool.get(handle).unwrap());
+        let other_resources = record(pool.get(other).unwrap());
+        assert_eq!(resources.len(), 3);
+        assert!(resources.is_current());
+
+        // The render pass still holds the replaced model
+        let shared = pool.get_shared(handle).unwrap();
+        replace_tracked(&mut pool, handle, item()).unwrap();
+        assert!(!resources.is_current());
+        assert!(other_resources.is_current());
+        drop(shared);
+
+        let resources = record(pool.get(handle).unwrap());
+        get_tracked_mut(&mut pool, handle).unwrap();
+        assert!(!resources.is_current());
+
+        let resources = record(pool.get(handle).unwrap());
+        take_tracked(&mut pool, handle).unwrap();
+        assert!(!resources.is_current());
+        assert!(other_resources.is_current());
+    }
+
+    #[test]
+    fn dropped_resources_invalidate_bundle() {
+        // Resources built outside of `context` are tracked by their versions too
+        let mut outside = item();
+        let resources = record(&outside);
+
+        outside.meshes[1] = Default::default();
+        assert!(!resources.is_current());
+
+        // A new resource never reuses the counter of the dropped one
+        let resources = record(&outside);
+        drop(outside);
+        let _new = item();
+        assert!(!resources.is_current());
     }
 }
@@ -0,0 +1,369 @@
+use std::{
+    cell::RefCell,
+    fmt,
+    hash::{Hash, Hasher},
+    marker::PhantomData,
+    sync::Arc,
+};
+
+use log::warn;
+
+use crate::{
+    bind_group::{layout::BindGroupLayout, BindGroup},
+    buffer::Buffer,
+    errors::CoreError,
+    model::Model,
+    pipeline::{layout::PipelineLayout, Pipeline},
+    render_pass::bundle::RenderBundle,
+    shader::Shader,
+    storage::Storages,
+    texture::{DepthTexture, RenderTexture},
+    uniform::Uniforms,
+};
+
+// Index `0` is never allocated, so the default handle is always invalid
+pub struct Handle<T> {
+    index: u32,
+    generation: u32,
+    _marker: PhantomData<fn() -> T>,
+}
+
+impl<T> Handle<T> {
+    fn new(index: u32, generation: u32) -> Self {
+        Self {
+            index,
+            generation,
+            _marker: PhantomData,
+        }
+    }
+
+    pub fn index(&self) -> usize {
+        self.index as usize
+    }
+
+    pub fn generation(&self) -> u32 {
+        self.generation
+    }
+
+    pub fn is_valid(&self) -> bool {
+        self.index != 0
+    }
+}
+
+impl<T> Clone for Handle<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for Handle<T> {}
+
+impl<T> PartialEq for Handle<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.index == other.index && self.generation == other.generation
+    }
+}
+
+impl<T> Eq for Handle<T> {}
+
+impl<T> Hash for Handle<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.index.hash(state);
+        self.generation.hash(state);
+    }
+}
+
+impl<T> Default for Handle<T> {
+    fn default() -> Self {
+        Self::new(0, 0)
+    }
+}
+
+impl<T> fmt::Debug for Handle<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Handle")
+            .field("index", &self.index)
+            .field("generation", &self.generation)
+            .finish()
+    }
+}
+
+pub trait PoolItem {
+    fn pool_index(&self) -> usize;
+    fn set_pool_index(&mut self, index: usize);
+}
+
+macro_rules! impl_pool_item {
+    ($($t:ty),*) => {
+        $(
+            impl PoolItem for $t {
+                fn pool_index(&self) -> usize {
+                    self.id
+                }
+
+                fn set_pool_index(&mut self, index: usize) {
+                    self.id = index;
+                }
+            }
+        )*
+    };
+}
+
+impl_pool_item!(
+    Buffer,
+    BindGroup,
+    BindGroupLayout,
+    Pipeline,
+    PipelineLayout,
+    RenderTexture,
+    DepthTexture,
+    Model,
+    Uniforms,
+    Storages,
+    RenderBundle
+);
+
+impl PoolItem for Shader {
+    fn pool_index(&self) -> usize {
+        self.id()
+    }
+
+    fn set_pool_index(&mut self, index: usize) {
+        *self.id_mut() = index;
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SlotState {
+    Free,
+    Reserved,
+    Occupied,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Slot {
+    generation: u32,
+    state: SlotState,
+}
+
+#[derive(Debug, Default)]
+struct Allocator {
+    slots: Vec<Slot>,
+    free: Vec<u32>,
+}
+
+impl Allocator {
+    fn allocate(&mut self, state: SlotState) -> (u32, u32) {
+        if self.slots.is_empty() {
+            self.slots.push(Slot {
+                generation: 0,
+                state: SlotState::Occupied,
+            });
+        }
+
+        if let Some(index) = self.free.pop() {
+            let slot = &mut self.slots[index as usize];
+            slot.state = state;
+
+            (index, slot.generation)
+        } else {
+            self.slots.push(Slot {
+                generation: 0,
+                state,
+            });
+
+            ((self.slots.len() - 1) as u32, 0)
+        }
+    }
+}
+
+// Resources are shared by `Arc`, so the render pass can hold them while the pool is mutated
+#[derive(Debug)]
+pub struct Pool<T> {
+    name: &'static str,
+    allocator: RefCell<Allocator>,
+    values: Vec<Option<Arc<T>>>,
+}
+
+impl<T: PoolItem> Pool<T> {
+    pub fn new(name: &'static str) -> Self {
+        Self {
+            name,
+            allocator: Default::default(),
+            values: vec![],
+        }
+    }
+
+    // Reserve the slot before the resource is built, `insert` fills it by the index of resource
+    pub fn reserve(&self) -> Handle<T> {
+        let (index, generation) = self.allocator.borrow_mut().allocate(SlotState::Reserved);
+
+        Handle::new(index, generation)
+    }
+
+    pub fn insert(&mut self, mut value: T) -> Handle<T> {
+        let index = value.pool_index();
+        let allocator = self.allocator.get_mut();
+
+        let (index, generation) = match allocator.slots.get_mut(index) {
+            Some(slot) if index != 0 && slot.state == SlotState::Reserved => {
+                slot.state = SlotState::Occupied;
+
+                (index as u32, slot.generation)
+            }
+            _ => {
+                if index != 0 {
+                    warn!(
+                        "{} with id: {index} is not reserved in `context`. Allocate a new one",
+                        self.name
+                    );
+                }
+
+                allocator.allocate(SlotState::Occupied)
+            }
+        };
+
+        let i = index as usize;
+        if self.values.len() <= i {
+            self.values.resize_with(i + 1, || None);
+        }
+        value.set_pool_index(i);
+        self.values[i] = Some(Arc::new(value));
+
+        Handle::new(index, generation)
+    }
+
+    pub fn replace(&mut self, handle: Handle<T>, mut value: T) -> Result<(), CoreError> {
+        let i = self.check(handle)?;
+
+        value.set_pool_index(i);
+        self.values[i] = Some(Arc::new(value));
+
+        Ok(())
+    }
+
+    pub fn get(&self, handle: Handle<T>) -> Result<&T, CoreError> {
+        let i = self.check(handle)?;
+
+        self.values[i].as_deref().ok_or(self.not_exist(i))
+    }
+
+    pub fn get_shared(&self, handle: Handle<T>) -> Result<Arc<T>, CoreError> {
+        let i = self.check(handle)?;
+
+        self.values[i].clone().ok_or(self.not_exist(i))
+    }
+
+    // Mutable access is allowed only while the resource isn't shared
+    pub fn get_mut(&mut self, handle: Handle<T>) -> Result<&mut T, CoreError> {
+        let i = self.check(handle)?;
+        let err = self.in_use(i);
+
+        self.values[i].as_mut().and_then(Arc::get_mut).ok_or(err)
+    }
+
+    pub fn take(&mut self, handle: Handle<T>) -> Result<T, CoreError> {
+        let i = self.check(handle)?;
+        let value = match self.values[i].take().map(Arc::try_unwrap) {
+            Some(Ok(value)) => value,
+            Some(Err(shared)) => {
+                self.values[i] = Some(shared);
+                return Err(self.in_use(i));
+            }
+            None => return Err(self.not_exist(i)),
+        };
+
+        let allocator = self.allocator.get_mut();
+        let slot = &mut allocator.slots[i];
+        slot.generation = slot.generation.wrapping_add(1);
+        slot.state = SlotState::Free;
+        allocator.free.push(i as u32);
+
+        Ok(value)
+    }
+
+    pub fn contains(&self, handle: Handle<T>) -> bool {
+        self.check(handle).is_ok()
+    }
+
+    pub fn len(&self) -> usize {
+        self.values.iter().filter(|v| v.is_some()).count()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.values.iter().all(Option::is_none)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &T> {
+        self.values.iter().filter_map(Option::as_deref)
+    }
+
+    fn check(&self, handle: Handle<T>) -> Result<usize, CoreError> {
+        let i = handle.index();
+
+        match self.allocator.borrow().slots.get(i) {
+            Some(slot) if i != 0 && slot.generation != handle.generation => Err(
+                CoreError::StaleHandle(self.name.to_string(), i, handle.generation),
+            ),
+            Some(slot) if i != 0 && slot.state == SlotState::Occupied => Ok(i),
+            _ => Err(self.not_exist(i)),
+        }
+    }
+
+    fn not_exist(&self, i: usize) -> CoreError {
+        CoreError::ContextFieldIsNotExist(self.name.to_string(), i)
+    }
+
+    fn in_use(&self, i: usize) -> CoreError {
+        CoreError::ContextFieldInUse(self.name.to_string(), i)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, PartialEq)]
+    struct Item {
+        id: usize,
+    }
+
+    impl PoolItem for Item {
+        fn pool_index(&self) -> usize {
+            self.id
+        }
+
+        fn set_pool_index(&mut self, index: usize) {
+            self.id = index;
+        }
+    }
+
+    #[test]
+    fn pool_handles() {
+        let mut pool = Pool::new("Item");
+
+        let reserved = pool.reserve();
+        let first = pool.insert(Item { id: 0 });
+        assert_eq!((reserved.index(), first.index()), (1, 2));
+
+        assert_eq!(pool.insert(Item { id: 1 }), reserved);
+        assert_eq!(pool.get(reserved).unwrap().id, 1);
+
+        let shared = pool.get_shared(first).unwrap();
+        assert!(matches!(
+            pool.take(first),
+            Err(CoreError::ContextFieldInUse(_, 2))
+        ));
+        drop(shared);
+
+        assert!(pool.take(first).is_ok());
+        assert!(matches!(
+            pool.get(first),
+            Err(CoreError::StaleHandle(_, 2, 0))
+        ));
+
+        let second = pool.insert(Item { id: 0 });
+        assert_eq!((second.index(), second.generation()), (2, 1));
+        assert!(!Handle::<Item>::default().is_valid());
+    }
+}
@@ -0,0 +1,171 @@
+use std::sync::{
+    atomic::{AtomicU32, Ordering},
+    Arc, Weak,
+};
+
+use crate::{
+    bind_group::BindGroup, buffer::Buffer, model::Model, pipeline::Pipeline, storage::Storages,
+    texture::RenderTexture, uniform::Uniforms,
+};
+
+// Counter of the resource changes, render bundles record it with the referenced resources
+#[derive(Debug, Default)]
+pub struct ResourceVersion(Arc<AtomicU32>);
+
+impl ResourceVersion {
+    #[inline]
+    pub fn bump(&self) {
+        self.0.fetch_add(1, Ordering::Relaxed);
+    }
+
+    #[inline]
+    pub fn get(&self) -> u32 {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+// The weak reference keeps the counter allocated, so a new resource never reuses it
+#[derive(Debug)]
+struct RecordedVersion {
+    version: Weak<AtomicU32>,
+    value: u32,
+}
+
+#[derive(Debug, Default)]
+pub struct RecordedResources(Vec<RecordedVersion>);
+
+impl RecordedResources {
+    pub fn record(&mut self, resource: &impl Tracked) {
+        resource.record(self);
+    }
+
+    pub fn push(&mut self, version: &ResourceVersion) {
+        self.0.push(RecordedVersion {
+            version: Arc::downgrade(&version.0),
+            value: version.get(),
+        });
+    }
+
+    // Recorded resources are alive, so their counters are never shared with others
+    pub fn dedup(&mut self) {
+        self.0.sort_unstable_by_key(|r| r.version.as_ptr() as usize);
+        self.0.dedup_by(|a, b| a.version.ptr_eq(&b.version));
+    }
+
+    // Dropped, replaced or mutated resources make the recording stale
+    pub fn is_current(&self) -> bool {
+        self.0.iter().all(|r| {
+            r.version
+                .upgrade()
+                .is_some_and(|v| v.load(Ordering::Relaxed) == r.value)
+        })
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+pub trait Tracked {
+    // Versions of the resource and of every nested resource, which is recorded with it
+    fn record(&self, resources: &mut RecordedResources);
+
+    // Marks the resource as changed for the render bundles, which recorded it
+    fn touch(&self);
+}
+
+macro_rules! impl_tracked {
+    ($($t:ty),*) => {
+        $(
+            impl Tracked for $t {
+                fn record(&self, resources: &mut RecordedResources) {
+                    resources.push(&self.version);
+                }
+
+                fn touch(&self) {
+                    self.version.bump();
+                }
+            }
+        )*
+    };
+}
+
+impl_tracked!(Buffer, BindGroup, Pipeline);
+
+impl Tracked for Model {
+    fn record(&self, resources: &mut RecordedResources) {
+        resources.push(&self.version);
+
+        for mesh in self.meshes() {
+            resources.record(mesh.vertex_buffer());
+            resources.record(mesh.index_buffer());
+            if let Some(b) = mesh.morph_deltas_buffer() {
+                resources.record(b);
+            }
+        }
+        for material in self.materials() {
+            resources.record(material.bind_group());
+        }
+        for node in self.nodes() {
+            resources.record(node.transform_buffer());
+            node.bind_groups()
+                .iter()
+                .for_each(|bg| resources.record(bg));
+            node.morph_weights()
+                .iter()
+                .for_each(|mw| resources.record(mw.weights_buffer()));
+        }
+        for skin in self.skins() {
+            resources.record(skin.joints_buffer());
+        }
+    }
+
+    // Nested resources can't be reached mutably, the version of the model covers them
+    fn touch(&self) {
+        self.version.bump();
+    }
+}
+
+impl Tracked for Uniforms {
+    fn record(&self, resources: &mut RecordedResources) {
+        resources.record(self.get_group());
+        self.buffers().for_each(|b| resources.record(b));
+    }
+
+    fn touch(&self) {
+        self.get_group().touch();
+        self.buffers().for_each(Tracked::touch);
+    }
+}
+
+impl Tracked for Storages {
+    fn record(&self, resources: &mut RecordedResources) {
+        resources.record(self.get_group());
+        self.buffers().for_each(|b| resources.record(b));
+        self.textures().for_each(|t| resources.record(t));
+    }
+
+    fn touch(&self) {
+        self.get_group().touch();
+        self.buffers().for_each(Tracked::touch);
+        self.textures().for_each(Tracked::touch);
+    }
+}
+
+impl Tracked for RenderTexture {
+    fn record(&self, resources: &mut RecordedResources) {
+        if let Ok(bg) = self.bind_group() {
+            resources.record(bg);
+        }
+    }
+
+    fn touch(&self) {
+        if let Ok(bg) = self.bind_group() {
+            bg.touch();
+        }
+    }
+}
@@ -50,8 +50,36 @@ pub enum CoreError {
     EmptyRenderPassColorAttachemnts(String),
     #[error("query type in `{0}` is not set")]
     EmptyQueryType(String),
+    #[error("query `{1}` is out of range of `{0}`")]
+    QueryOutOfRange(String, u32),
+    #[error("query set `{0}` is not occlusion")]
+    NotOcclusionQuerySet(String),
+    #[error("occlusion query set in `Render Stage {0}` is not set")]
+    EmptyOcclusionQuerySet(usize),
+    #[error("push constant range for `{1:?}` is not declared in the layout of `{0}`")]
+    PushConstantRangeNotFound(String, wgpu::ShaderStages),
+    #[error("push constant of {1} bytes is bigger than its range in the layout of `{0}`")]
+    PushConstantSize(String, usize),
+    #[error("uniform `{0}` has no dynamic records")]
+    NotDynamicUniform(String),
+    #[error("records of the dynamic uniform `{0}` are exhausted")]
+    UniformRecordsExhausted(String),
+    #[error("color formats in `{0}` is not set")]
+    EmptyRenderBundleFormats(String),
+    #[error("draws in `{0}` is not set")]
+    EmptyRenderBundleDraws(String),
+    #[error("render bundle `{0}` is invalidated by replaced resources, record it again")]
+    RenderBundleInvalidated(String),
+    #[error("`{0}` expects the bind group at the group {1}, but it's bound at the group {2}")]
+    UnexpectedBindGroup(String, u32, u32),
     #[error("{0} with id: {1} is not exist in `context`")]
     ContextFieldIsNotExist(String, usize),
+    #[error("{0} with id: {1} and generation: {2} is removed from `context`")]
+    StaleHandle(String, usize, u32),
+    #[error("{0} with id: {1} is shared outside of `context`")]
+    ContextFieldInUse(String, usize),
+    #[error("material {1} of `Model {0}` isn't found")]
+    MaterialNotFound(usize, usize),
     #[error("cannot create image buffer")]
     ImageBufferCreate,
     #[error("model file in `{0} is not set`")]
@@ -68,6 +96,42 @@ pub enum CoreError {
     WrongBufferSize,
     #[error("request adapter in `fn init()` of Runtime")]
     RequestAdapter,
+    #[error("required features `{0:?}` are not supported by the adapter")]
+    UnsupportedFeatures(wgpu::Features),
+    #[error("limits are not supported by the adapter: {0}")]
+    UnsupportedLimits(String),
+    #[error("texture format `{0:?}` with aspect `{1:?}` can't be read back")]
+    UnsupportedReadbackFormat(wgpu::TextureFormat, wgpu::TextureAspect),
+    #[error("texture format `{0:?}` can't be converted to an image")]
+    UnsupportedImageFormat(wgpu::TextureFormat),
+    #[error("texture with usage `{0:?}` can't be read back, `COPY_SRC` is required")]
+    TextureNotCopySrc(wgpu::TextureUsages),
+    #[error("mipmaps can't be generated for format `{0:?}` with usage `{1:?}`")]
+    MipmapsNotSupported(wgpu::TextureFormat, wgpu::TextureUsages),
+    #[error("texture container is invalid: {0}")]
+    InvalidTextureContainer(String),
+    #[error("texture container is not supported: {0}")]
+    UnsupportedTextureContainer(String),
+    #[error("compressed format `{0:?}` is not supported by the device and can't be decoded")]
+    UnsupportedCompressedFormat(wgpu::TextureFormat),
+    #[error("model `{0}` has skins or morph targets, but storage buffers aren't supported in vertex shaders")]
+    VertexStorageNotSupported(String),
+    #[error("texture layers have different formats, sizes or mip levels")]
+    TextureLayersMismatch,
+    #[error("cube texture `{0}` needs square faces and six layers per cube")]
+    InvalidCubeTexture(String),
+    #[error("mip level {0} or array layer {1} is out of the texture")]
+    TextureSubresourceOutOfRange(u32, u32),
+    #[error("sample count {1} is not supported by format `{0:?}`")]
+    UnsupportedSampleCount(wgpu::TextureFormat, u32),
+    #[error("transient resource `{0}` isn't found in `render graph`")]
+    RenderGraphResource(String),
+    #[error("passes `{0}` of `render graph` form a cycle")]
+    RenderGraphCycle(String),
+    #[error("pipeline `{0}` has {1} color targets, but {2} color attachments are set")]
+    ColorAttachmentsCount(String, usize, usize),
+    #[error("color target {1} of pipeline `{0}` is `{2:?}`, but the attachment is `{3:?}`")]
+    ColorAttachmentFormat(String, usize, wgpu::TextureFormat, wgpu::TextureFormat),
     #[error("entities in `Render Stage {0}` is not set")]
     EmptyEntities(usize),
     #[error("instances in `Render Stage {0}` is not set")]
@@ -1,10 +1,13 @@
 pub mod bind_group;
 pub mod buffer;
+pub mod clock;
 pub mod context;
 pub mod errors;
 pub mod instance;
 pub mod model;
 pub mod pipeline;
+pub mod profiler;
+pub mod render_graph;
 pub mod render_pass;
 pub mod runtime;
 pub mod shader;
@@ -1,18 +1,34 @@
 pub mod material;
 pub mod mesh;
+pub mod morph;
+pub mod node;
+pub mod skin;
+
+use std::collections::HashMap;
 
 use custom_engine_derive::VertexLayout;
-use custom_engine_models::{gltf::GltfFile, obj::ObjFile};
+use custom_engine_models::{
+    gltf::{GltfFile, Primitive, Skeleton, Transform},
+    obj::ObjFile,
+};
 
-use cgmath::{Vector2, Vector3};
+use cgmath::{Matrix4, SquareMatrix, Vector2, Vector3};
 use log::{debug, error};
 
 use crate::{
     bind_group::layout::{BindGroupLayout, BindGroupLayoutBuilder},
+    buffer::BufferBuilder,
+    context::ResourceVersion,
     errors::CoreError,
     model::{
         material::{Material, MaterialBuilder, MaterialTextureParams},
         mesh::{Mesh, MeshBuilder},
+        morph::{MorphDeltaRaw, MorphWeights},
+        node::{
+            Node, NodeBuilder, NodeRaw, NODE_JOINTS_BINDING, NODE_MORPH_DELTAS_BINDING,
+            NODE_MORPH_WEIGHTS_BINDING, NODE_TRANSFORM_BINDING,
+        },
+        skin::{Skin, SkinBuilder},
     },
     traits::{Builder, VertexLayout},
 };
@@ -47,6 +63,16 @@ impl TextureParams {
                 count: None,
             })
     }
+
+    fn material_params<'a>(&self, texture_data: &'a [u8]) -> MaterialTextureParams<'a> {
+        MaterialTextureParams {
+            format: self.format,
+            texture_data: Some(texture_data),
+            fallback_data: None,
+            view_binding: self.view_binding,
+            sampler_binding: self.sampler_binding,
+        }
+    }
 }
 
 #[derive(Debug)]
@@ -76,10 +102,17 @@ impl From<(usize, GltfFile)> for ModelFile {
 #[derive(Debug)]
 pub struct Model {
     pub id: usize,
+    pub(crate) version: ResourceVersion,
 
     bind_group_layout: BindGroupLayout,
     meshes: Vec<Mesh>,
     materials: Vec<Material>,
+
+    skeleton: Option<Skeleton>,
+    node_bind_group_layout: BindGroupLayout,
+    nodes: Vec<Node>,
+    skins: Vec<Skin>,
+    deformable: bool,
 }
 
 impl Model {
@@ -106,6 +139,80 @@ impl Model {
     pub fn materials(&self) -> &[Material] {
         &self.materials
     }
+
+    pub fn skeleton(&self) -> Option<&Skeleton> {
+        self.skeleton.as_ref()
+    }
+
+    // Layout of the node transform, deformed models add the joints and morph targets storages
+    pub fn node_bind_group_layout(&self) -> &BindGroupLayout {
+        &self.node_bind_group_layout
+    }
+
+    // Skins or morph targets need the shader with the deformation, e.g. `skinned_model.wgsl`
+    pub fn deformable(&self) -> bool {
+        self.deformable
+    }
+
+    pub fn nodes(&self) -> &[Node] {
+        &self.nodes
+    }
+
+    pub fn skins(&self) -> &[Skin] {
+        &self.skins
+    }
+
+    // Joint matrices of every skin for the local transforms of the nodes
+    pub fn joint_matrices(&self, pose: &[Transform]) -> Vec<(&Skin, Vec<[[f32; 4]; 4]>)> {
+        let Some(skeleton) = self.skeleton.as_ref() else {
+            return vec![];
+        };
+        let globals = skeleton.global_transforms(pose);
+
+        self.skins
+            .iter()
+            .filter_map(|s| {
+                let matrices = skeleton.skins.get(s.skin)?.joint_matrices(&globals);
+
+                Some((s, matrices.into_iter().map(Into::into).collect()))
+            })
+            .collect()
+    }
+
+    // World transforms of the nodes for the local transforms, skinned nodes keep the identity
+    pub fn node_transforms(&self, pose: &[Transform]) -> Vec<(&Node, NodeRaw)> {
+        let Some(skeleton) = self.skeleton.as_ref() else {
+            return vec![];
+        };
+        let globals = skeleton.global_transforms(pose);
+
+        self.nodes
+            .iter()
+            .filter_map(|n| {
+                let transform = if n.skin.is_some() {
+                    Matrix4::identity()
+                } else {
+                    *globals.get(n.id)?
+                };
+
+                Some((n, transform.into()))
+            })
+            .collect()
+    }
+
+    // Morph target weights of every node mesh for the node weights of the pose
+    pub fn morph_weights<'p>(&self, pose: &'p [Transform]) -> Vec<(&MorphWeights, &'p [f32])> {
+        self.nodes
+            .iter()
+            .filter_map(|n| Some((n, &pose.get(n.id)?.weights)))
+            .flat_map(|(n, weights)| {
+                n.morph_weights()
+                    .iter()
+                    .filter(|mw| mw.targets > 0)
+                    .map(|mw| (mw, &weights[..weights.len().min(mw.targets)]))
+            })
+            .collect()
+    }
 }
 
 #[derive(Debug)]
@@ -114,12 +221,15 @@ pub struct ModelBuilder<'a> {
     file: Option<ModelFile>,
 
     mesh_vertex_binding: Option<u32>,
+    node_binding: u32,
 
     diffuse: Option<TextureParams>,
     normal: Option<TextureParams>,
     mr: Option<TextureParams>,
     emissive: Option<TextureParams>,
     occlusion: Option<TextureParams>,
+    mipmaps: bool,
+    vertex_storage: bool,
 
     device: &'a wgpu::Device,
 }
@@ -136,12 +246,15 @@ impl<'a> Builder<'a> for ModelBuilder<'a> {
             file: None,
 
             mesh_vertex_binding: None,
+            node_binding: 3,
 
             diffuse: None,
             normal: None,
             mr: None,
             emissive: None,
             occlusion: None,
+            mipmaps: true,
+            vertex_storage: true,
 
             device,
         }
@@ -156,12 +269,15 @@ impl<'a> Builder<'a> for ModelBuilder<'a> {
             file: None,
 
             mesh_vertex_binding: None,
+            node_binding: 3,
 
             diffuse: None,
             normal: None,
             mr: None,
             emissive: None,
             occlusion: None,
+            mipmaps: true,
+            vertex_storage: true,
 
             device,
         }
@@ -210,7 +326,9 @@ impl<'a> Builder<'a> for ModelBuilder<'a> {
                     .materials
                     .iter()
                     .map(|(i, lm)| -> Result<Material, CoreError> {
-                        let mut mb = MaterialBuilder::new(self.device).layout(&bind_group_layout);
+                        let mut mb = MaterialBuilder::new(self.device)
+                            .layout(&bind_group_layout)
+                            .mipmaps(self.mipmaps);
                         let texture_name = lm.material.name.to_string();
                         debug!(
                             "
@@ -230,6 +348,7 @@ Proceed material: `{texture_name}:{i}`:
                         let diffuse = MaterialTextureParams {
                             format: diffuse.format,
                             texture_data: Some(diffuse_texture_data),
+                            fallback_data: None,
                             view_binding: diffuse.view_binding,
                             sampler_binding: diffuse.sampler_binding,
                         };
@@ -242,6 +361,7 @@ Proceed material: `{texture_name}:{i}`:
                             let normal = MaterialTextureParams {
                                 format: normal.format,
                                 texture_data: normal_texture_data,
+                                fallback_data: None,
                                 view_binding: normal.view_binding,
                                 sampler_binding: normal.sampler_binding,
                             };
@@ -251,15 +371,22 @@ Proceed material: `{texture_name}:{i}`:
 
                         Ok(mb.build()?)
                     })
-                    .filter_map(|m_res| {
-                        if let Err(e) = m_res {
-                            error!("{e}");
-                            None
-                        } else {
-                            m_res.ok()
-                        }
+                    // Failed materials are replaced to keep the material ids of the meshes
+                    .map(|m_res| {
+                        m_res.or_else(|e| {
+                            error!("{e}. Use the default material");
+                            default_material(
+                                self.device,
+                                &bind_group_layout,
+                                &diffuse,
+                                self.normal.as_ref(),
+                                self.mr.as_ref(),
+                                self.emissive.as_ref(),
+                                self.occlusion.as_ref(),
+                            )
+                        })
                     })
-                    .collect::<Vec<_>>();
+                    .collect::<Result<Vec<_>, _>>()?;
 
                 let meshes = obj_file
                     .models
@@ -281,6 +408,8 @@ Proceed material: `{texture_name}:{i}`:
                                 ],
                                 tangent: [0.0; 3],
                                 bitangent: [0.0; 3],
+                                joints: [0; 4],
+                                weights: [0.0; 4],
                             })
                             .collect::<Vec<_>>();
 
@@ -362,107 +491,255 @@ Proceed material: `{texture_name}:{i}`:
                     })
                     .collect::<Vec<_>>();
 
+                // Meshes of the file aren't transformed, they're drawn by the identity node
+                let node_bind_group_layout =
+                    node_bind_group_layout(self.device, &model_name, false)?;
+                let node = meshes
+                    .iter()
+                    .enumerate()
+                    .fold(
+                        NodeBuilder::new(self.device)
+                            .name(&model_name)
+                            .layout(&node_bind_group_layout)
+                            .node_binding(self.node_binding),
+                        |nb, (i, m)| nb.mesh(i, m),
+                    )
+                    .build()?;
+
                 Ok(Model {
                     id,
+                    version: Default::default(),
                     meshes,
                     materials,
                     bind_group_layout,
+                    skeleton: None,
+                    node_bind_group_layout,
+                    nodes: vec![node],
+                    skins: vec![],
+                    deformable: false,
                 })
             }
             Gltf((scene_id, mut gltf_file)) => {
                 let scene = gltf_file.scene(scene_id)?;
+                let skeleton = gltf_file.skeleton();
+                let root = &gltf_file.root;
+
+                let deformable = !skeleton.skins.is_empty()
+                    || root
+                        .meshes
+                        .iter()
+                        .any(|m| m.primitives.iter().any(|p| !p.morph_targets.is_empty()));
+                // Joints and morph targets are read from storage buffers in the vertex shader
+                let storage_buffers = self.device.limits().max_storage_buffers_per_shader_stage;
+                if deformable && (!self.vertex_storage || storage_buffers < 3) {
+                    return Err(CoreError::VertexStorageNotSupported(model_name));
+                }
+                let node_bind_group_layout =
+                    node_bind_group_layout(self.device, &model_name, deformable)?;
 
+                // Joints start in the rest pose, they're updated by `Worker::update_model_pose`
+                let globals = skeleton.global_transforms(&skeleton.rest_pose);
+                let skins = skeleton
+                    .skins
+                    .iter()
+                    .map(|s| {
+                        let joint_matrices = s
+                            .joint_matrices(&globals)
+                            .into_iter()
+                            .map(Into::into)
+                            .collect::<Vec<[[f32; 4]; 4]>>();
+
+                        SkinBuilder::new_indexed(self.device, s.index)
+                            .name(s.name.as_deref().unwrap_or(&model_name))
+                            .skin(s.index)
+                            .joint_matrices(&joint_matrices)
+                            .binding(NODE_JOINTS_BINDING)
+                            .build()
+                    })
+                    .collect::<Result<Vec<_>, _>>()?;
+                let fallback_joints = if deformable {
+                    let identity: [[f32; 4]; 4] = Matrix4::identity().into();
+
+                    Some(
+                        BufferBuilder::new(self.device)
+                            .label(&format!("Fallback joints buffer: {model_name}"))
+                            .usage(wgpu::BufferUsages::STORAGE)
+                            .binding(NODE_JOINTS_BINDING)
+                            .data(&[identity])
+                            .build()?,
+                    )
+                } else {
+                    None
+                };
+
+                // Primitives are built once, the nodes of the same mesh share them
                 let mut materials = vec![];
+                let mut default_id = None;
                 let mut meshes = vec![];
+                let mut mesh_ids = HashMap::new();
 
-                for n_id in scene.nodes.iter() {
-                    if let Some(node) = gltf_file.root.nodes.get(*n_id) {
-                        if let Some(mesh) = node.mesh.as_ref() {
-                            materials.extend(
-                                mesh.primitives
-                                    .iter()
-                                    .map(|p| p.material.clone())
-                                    .enumerate()
-                                    .filter_map(|(i, m)| {
-                                        let mut mb = MaterialBuilder::new(self.device)
-                                            .layout(&bind_group_layout);
-                                        let texture_name = m.name.clone().unwrap();
-                                        debug!(
-                                            "
-Proceed material: `{texture_name}:{i}`:
-            "
-                                        );
-
-                                        if let Some(base_color) = m.base_color.as_ref() {
-                                            let diffuse_texture_data =
-                                                &base_color.texture.dyn_image;
-                                            let diffuse = MaterialTextureParams {
-                                                format: diffuse.format,
-                                                texture_data: Some(&diffuse_texture_data),
-                                                view_binding: diffuse.view_binding,
-                                                sampler_binding: diffuse.sampler_binding,
-                                            };
-                                            mb = mb.diffuse(diffuse);
-
-                                            if let Some(normal) = self.normal.as_ref() {
-                                                let normal_texture_data =
-                                                    &m.normal.as_ref().unwrap().texture.dyn_image;
-                                                let normal = MaterialTextureParams {
-                                                    format: normal.format,
-                                                    texture_data: Some(normal_texture_data),
-                                                    view_binding: normal.view_binding,
-                                                    sampler_binding: normal.sampler_binding,
-                                                };
-
-                                                mb = mb.normal(normal);
-                                            }
-                                        }
-
-                                        if let Ok(m) = mb.build() {
-                                            Some(m)
-                                        } else {
-                                            None
-                                        }
-                                    }),
-                            );
-                            meshes.extend(mesh.primitives.iter().filter_map(|p| {
-                                if let Some(indices) = &p.indices {
-                                    let verticies = p
-                                        .vertices
-                                        .iter()
-                                        .map(|v| ModelRaw {
-                                            normal: v.normal.into(),
-                                            tangent: v.tangent.clone().truncate().into(),
-                                            position: v.position.into(),
-                                            bitangent: Default::default(),
-                                            tex_coords: v.tex_coord_0.into(),
-                                        })
-                                        .collect::<Vec<_>>();
-
-                                    if let Ok(m) = MeshBuilder::new(self.device)
-                                        .name("Some")
-                                        .num_elements(indices.len() as u32)
-                                        .material(p.index)
-                                        .vertex_buffer_data(&verticies)
-                                        .index_buffer_data(&indices)
-                                        .vertex_buffer_binding(mesh_vertex_binding)
-                                        .build()
-                                    {
-                                        return Some(m);
+                for mesh in root.meshes.iter() {
+                    let mut ids = vec![];
+
+                    for p in mesh.primitives.iter() {
+                        let Some(indices) = p.indices.as_ref() else {
+                            continue;
+                        };
+
+                        let m = &p.material;
+                        let mut mb = MaterialBuilder::new(self.device)
+                            .layout(&bind_group_layout)
+                            .mipmaps(self.mipmaps);
+                        let texture_name = m.name.clone().unwrap_or_default();
+                        debug!(
+                            "
+Proceed material: `{texture_name}:{}`:
+            ",
+                            p.index
+                        );
+
+                        if let Some(base_color) = m.base_color.as_ref() {
+                            let diffuse = MaterialTextureParams {
+                                format: diffuse.format,
+                                texture_data: Some(&base_color.texture.dyn_image),
+                                fallback_data: base_color.texture.fallback_image.as_deref(),
+                                view_binding: diffuse.view_binding,
+                                sampler_binding: diffuse.sampler_binding,
+                            };
+                            mb = mb.diffuse(diffuse);
+
+                            if let (Some(normal), Some(normal_texture)) =
+                                (self.normal.as_ref(), m.normal.as_ref())
+                            {
+                                let normal = MaterialTextureParams {
+                                    format: normal.format,
+                                    texture_data: Some(&normal_texture.texture.dyn_image),
+                                    fallback_data: normal_texture.texture.fallback_image.as_deref(),
+                                    view_binding: normal.view_binding,
+                                    sampler_binding: normal.sampler_binding,
+                                };
+
+                                mb = mb.normal(normal);
+                            }
+                        }
+
+                        // Primitives, whose material fails, share the default one
+                        let material = match mb.build() {
+                            Ok(material) => {
+                                materials.push(material);
+                                materials.len() - 1
+                            }
+                            Err(e) => {
+                                error!("{e}. Use the default material");
+                                match default_id {
+                                    Some(id) => id,
+                                    None => {
+                                        materials.push(default_material(
+                                            self.device,
+                                            &bind_group_layout,
+                                            &diffuse,
+                                            self.normal.as_ref(),
+                                            self.mr.as_ref(),
+                                            self.emissive.as_ref(),
+                                            self.occlusion.as_ref(),
+                                        )?);
+                                        *default_id.insert(materials.len() - 1)
                                     }
                                 }
+                            }
+                        };
+
+                        let vertices = p
+                            .vertices
+                            .iter()
+                            .map(|v| ModelRaw {
+                                normal: v.normal.into(),
+                                tangent: v.tangent.truncate().into(),
+                                position: v.position.into(),
+                                bitangent: Default::default(),
+                                tex_coords: v.tex_coord_0.into(),
+                                joints: v.joints_0.map(u32::from).into(),
+                                weights: v.weights_0.into(),
+                            })
+                            .collect::<Vec<_>>();
+                        let deltas = morph_deltas(p);
 
-                                None
-                            }));
+                        let mut mb = MeshBuilder::new_indexed(self.device, meshes.len())
+                            .name(mesh.name.as_deref().unwrap_or(&model_name))
+                            .num_elements(indices.len() as u32)
+                            .material(material)
+                            .vertex_buffer_data(&vertices)
+                            .index_buffer_data(indices)
+                            .vertex_buffer_binding(mesh_vertex_binding);
+                        if deformable {
+                            mb = mb.morph_deltas(&deltas, NODE_MORPH_DELTAS_BINDING);
                         }
+
+                        match mb.build() {
+                            Ok(m) => {
+                                ids.push(meshes.len());
+                                meshes.push(m);
+                            }
+                            Err(e) => error!("{e}"),
+                        }
+                    }
+
+                    mesh_ids.insert(mesh.index, ids);
+                }
+
+                // Every node of the scene tree with a mesh is drawn by its world transform
+                let mut nodes = vec![];
+                let mut stack = scene.nodes.clone();
+                while let Some(n_id) = stack.pop() {
+                    let Some(node) = root.nodes.get(n_id) else {
+                        continue;
+                    };
+                    stack.extend(node.children.iter().rev());
+
+                    let Some(mesh) = node.mesh.as_ref() else {
+                        continue;
+                    };
+                    let transform = if node.skin.is_some() {
+                        Matrix4::identity()
+                    } else {
+                        globals[n_id]
+                    };
+
+                    let mut nb = NodeBuilder::new_indexed(self.device, n_id)
+                        .name(node.name.as_deref().unwrap_or(&model_name))
+                        .layout(&node_bind_group_layout)
+                        .node_binding(self.node_binding)
+                        .transform(transform)
+                        .weights(&skeleton.rest_pose[n_id].weights);
+                    for id in mesh_ids.get(&mesh.index).into_iter().flatten() {
+                        nb = nb.mesh(*id, &meshes[*id]);
+                    }
+                    if let Some(skin) = node.skin {
+                        nb = nb.skin(skin);
                     }
+                    let joints = node
+                        .skin
+                        .and_then(|s| skins.get(s))
+                        .map(Skin::joints_buffer)
+                        .or(fallback_joints.as_ref());
+                    if let Some(joints) = joints {
+                        nb = nb.joints_buffer(joints);
+                    }
+
+                    nodes.push(nb.build()?);
                 }
 
                 Ok(Model {
                     id,
+                    version: Default::default(),
                     meshes,
                     materials,
                     bind_group_layout,
+                    skeleton: Some(skeleton),
+                    node_bind_group_layout,
+                    nodes,
+                    skins,
+                    deformable,
                 })
             }
         }
@@ -480,6 +757,23 @@ impl<'a> ModelBuilder<'a> {
         self
     }
 
+    // Group of the node transform, joints and morph targets, which is bound for every mesh
+    pub fn node_binding(mut self, node_binding: u32) -> Self {
+        self.node_binding = node_binding;
+        self
+    }
+
+    // Whether the adapter supports storage buffers in vertex shaders, WebGL doesn't
+    pub fn vertex_storage(mut self, vertex_storage: bool) -> Self {
+        self.vertex_storage = vertex_storage;
+        self
+    }
+
+    pub fn mipmaps(mut self, mipmaps: bool) -> Self {
+        self.mipmaps = mipmaps;
+        self
+    }
+
     pub fn mr_texture_params(mut self, tp: TextureParams) -> Self {
         self.mr = Some(tp);
         self
@@ -506,14 +800,124 @@ impl<'a> ModelBuilder<'a> {
     }
 }
 
+// Material for the failed ones, it's white with the flat normal
+fn default_material(
+    device: &wgpu::Device,
+    layout: &wgpu::BindGroupLayout,
+    diffuse: &TextureParams,
+    normal: Option<&TextureParams>,
+    mr: Option<&TextureParams>,
+    emissive: Option<&TextureParams>,
+    occlusion: Option<&TextureParams>,
+) -> Result<Material, CoreError> {
+    let white = pixel_png([255, 255, 255, 255])?;
+    let flat_normal = pixel_png([128, 128, 255, 255])?;
+    // Roughness is in the green channel and metalness is in the blue one
+    let rough = pixel_png([0, 255, 0, 255])?;
+    let black = pixel_png([0, 0, 0, 255])?;
+
+    let mut mb = MaterialBuilder::new(device)
+        .name("Default material")
+        .layout(layout)
+        .mipmaps(false)
+        .diffuse(diffuse.material_params(&white));
+    if let Some(tp) = normal {
+        mb = mb.normal(tp.material_params(&flat_normal));
+    }
+    if let Some(tp) = mr {
+        mb = mb.mr(tp.material_params(&rough));
+    }
+    if let Some(tp) = emissive {
+        mb = mb.emissive(tp.material_params(&black));
+    }
+    if let Some(tp) = occlusion {
+        mb = mb.occlusion(tp.material_params(&white));
+    }
+
+    mb.build()
+}
+
+// Material textures are loaded from the encoded images
+fn pixel_png(color: [u8; 4]) -> Result<Vec<u8>, CoreError> {
+    let mut png = vec![];
+    image::RgbaImage::from_pixel(1, 1, image::Rgba(color))
+        .write_to(&mut std::io::Cursor::new(&mut png), image::ImageFormat::Png)?;
+
+    Ok(png)
+}
+
+// Storages of the deformation are visible to the vertex stage only
+fn node_bind_group_layout(
+    device: &wgpu::Device,
+    model_name: &str,
+    deformable: bool,
+) -> Result<BindGroupLayout, CoreError> {
+    let bgl_name = format!("Node bind group layout of `{model_name}`");
+    let mut bgl = BindGroupLayoutBuilder::new(device)
+        .label(&bgl_name)
+        .entries(wgpu::BindGroupLayoutEntry {
+            binding: NODE_TRANSFORM_BINDING,
+            visibility: wgpu::ShaderStages::VERTEX,
+            ty: wgpu::BindingType::Buffer {
+                ty: wgpu::BufferBindingType::Uniform,
+                has_dynamic_offset: false,
+                min_binding_size: None,
+            },
+            count: None,
+        });
+
+    if deformable {
+        for binding in [
+            NODE_JOINTS_BINDING,
+            NODE_MORPH_DELTAS_BINDING,
+            NODE_MORPH_WEIGHTS_BINDING,
+        ] {
+            bgl = bgl.entries(wgpu::BindGroupLayoutEntry {
+                binding,
+                visibility: wgpu::ShaderStages::VERTEX,
+                ty: wgpu::BindingType::Buffer {
+                    ty: wgpu::BufferBindingType::Storage { read_only: true },
+                    has_dynamic_offset: false,
+                    min_binding_size: None,
+                },
+                count: None,
+            });
+        }
+    }
+
+    bgl.build()
+}
+
+// Deltas of the primitive targets, they're laid out by targets
+fn morph_deltas(primitive: &Primitive) -> Vec<MorphDeltaRaw> {
+    primitive
+        .morph_targets
+        .iter()
+        .flat_map(|t| {
+            t.positions
+                .iter()
+                .zip(&t.normals)
+                .zip(&t.tangents)
+                .map(|((p, n), t)| MorphDeltaRaw {
+                    position: p.extend(0.).into(),
+                    normal: n.extend(0.).into(),
+                    tangent: t.extend(0.).into(),
+                })
+        })
+        .collect()
+}
+
 #[repr(C)]
 #[derive(Debug, Clone, Copy, bytemuck::Pod, bytemuck::Zeroable, VertexLayout)]
 #[attributes("Vertex")]
-#[attributes("0 => Float32x3, 1 => Float32x2, 2 => Float32x3, 3 => Float32x3, 4 => Float32x3")]
+// Joints and weights follow the instance locations, shaders without skinning skip them
+#[attributes("0 => Float32x3, 1 => Float32x2, 2 => Float32x3, 3 => Float32x3, 4 => Float32x3, 12 => Uint32x4, 13 => Float32x4")]
 struct ModelRaw {
     position: [f32; 3],
     tex_coords: [f32; 2],
     normal: [f32; 3],
     tangent: [f32; 3],
     bitangent: [f32; 3],
+    joints: [u32; 4],
+    weights: [f32; 4],
 }
@@ -1,3 +1,5 @@
+use std::iter::once;
+
 use log::debug;
 
 use crate::{
@@ -12,9 +14,29 @@ pub struct MaterialTextureParams<'a> {
     pub view_binding: u32,
     pub sampler_binding: u32,
     pub texture_data: Option<&'a [u8]>,
+    // Image, which is loaded when `texture_data` is an unsupported compressed container
+    pub fallback_data: Option<&'a [u8]>,
     pub format: wgpu::TextureFormat,
 }
 
+impl<'a> MaterialTextureParams<'a> {
+    fn texture_builder(
+        &self,
+        device: &'a wgpu::Device,
+        texture_data: &'a [u8],
+    ) -> RenderTextureBuilder<'a> {
+        let builder = RenderTextureBuilder::new(device)
+            .bytes(texture_data)
+            .format(self.format);
+
+        if let Some(fallback_data) = self.fallback_data {
+            builder.fallback_bytes(fallback_data)
+        } else {
+            builder
+        }
+    }
+}
+
 #[derive(Debug)]
 pub struct Material {
     pub id: usize,
@@ -46,6 +68,19 @@ impl Material {
         }
     }
 
+    pub fn textures(&self) -> impl Iterator<Item = &RenderTexture> {
+        once(&self.diffuse_texture).chain(
+            [
+                &self.normal_texture,
+                &self.occlusion_texture,
+                &self.mr_texture,
+                &self.emissive_texture,
+            ]
+            .into_iter()
+            .flatten(),
+        )
+    }
+
     pub fn bind_group(&self) -> &BindGroup {
         &self.bind_group
     }
@@ -63,6 +98,7 @@ pub struct MaterialBuilder<'a> {
     emissive: Option<MaterialTextureParams<'a>>,
 
     material_binding: u32,
+    mipmaps: bool,
 
     device: &'a wgpu::Device,
 }
@@ -84,6 +120,7 @@ impl<'a> Builder<'a> for MaterialBuilder<'a> {
             occlusion: None,
             layout: None,
             material_binding: 0,
+            mipmaps: true,
             device,
         }
     }
@@ -102,6 +139,7 @@ impl<'a> Builder<'a> for MaterialBuilder<'a> {
             occlusion: None,
             layout: None,
             material_binding: 0,
+            mipmaps: true,
             device,
         }
     }
@@ -115,6 +153,7 @@ impl<'a> Builder<'a> for MaterialBuilder<'a> {
 
         let name = self.name.unwrap_or(&material_name);
         let material_binding = self.material_binding;
+        let mipmaps = self.mipmaps;
 
         let diffuse = self
             .diffuse
@@ -124,10 +163,10 @@ impl<'a> Builder<'a> for MaterialBuilder<'a> {
         let diffuse_texture_data = diffuse
             .texture_data
             .ok_or(CoreError::EmptyDiffuseTexture(name.to_string()))?;
-        let diffuse_texture = RenderTextureBuilder::new(self.device)
+        let diffuse_texture = diffuse
+            .texture_builder(self.device, diffuse_texture_data)
             .label(&format!("Diffuse texture: {name}"))
-            .bytes(diffuse_texture_data)
-            .format(diffuse.format)
+            .mipmaps(mipmaps)
             .usage(wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST)
             .build()?;
         let diff_view = diffuse_texture.view();
@@ -154,10 +193,9 @@ impl<'a> Builder<'a> for MaterialBuilder<'a> {
                 .texture_data
                 .ok_or(CoreError::EmptyNormalTexture(name.to_string()))?;
             normal_texture = Some(
-                RenderTextureBuilder::new(&self.device)
+                mtp.texture_builder(self.device, texture_data)
                     .label(&format!("Texture: {name}"))
-                    .bytes(&texture_data)
-                    .format(mtp.format)
+                    .mipmaps(mipmaps)
                     .usage(wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST)
                     .build()?,
             );
@@ -180,10 +218,9 @@ impl<'a> Builder<'a> for MaterialBuilder<'a> {
                 .texture_data
                 .ok_or(CoreError::EmptyNormalTexture(name.to_string()))?;
             occlusion_texture = Some(
-                RenderTextureBuilder::new(&self.device)
+                mtp.texture_builder(self.device, texture_data)
                     .label(&format!("Texture: {name}"))
-                    .bytes(&texture_data)
-                    .format(mtp.format)
+                    .mipmaps(mipmaps)
                     .usage(wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST)
                     .build()?,
             );
@@ -206,10 +243,9 @@ impl<'a> Builder<'a> for MaterialBuilder<'a> {
                 .texture_data
                 .ok_or(CoreError::EmptyNormalTexture(name.to_string()))?;
             emissive_texture = Some(
-                RenderTextureBuilder::new(&self.device)
+                mtp.texture_builder(self.device, texture_data)
                     .label(&format!("Texture: {name}"))
-                    .bytes(&texture_data)
-                    .format(mtp.format)
+                    .mipmaps(mipmaps)
                     .usage(wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST)
                     .build()?,
             );
@@ -232,10 +268,9 @@ impl<'a> Builder<'a> for MaterialBuilder<'a> {
                 .texture_data
                 .ok_or(CoreError::EmptyNormalTexture(name.to_string()))?;
             mr_texture = Some(
-                RenderTextureBuilder::new(&self.device)
+                mtp.texture_builder(self.device, texture_data)
                     .label(&format!("Texture: {name}"))
-                    .bytes(&texture_data)
-                    .format(mtp.format)
+                    .mipmaps(mipmaps)
                     .usage(wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST)
                     .build()?,
             );
@@ -319,4 +354,10 @@ impl<'a> MaterialBuilder<'a> {
         self.material_binding = binding;
         self
     }
+
+    // Material textures get the full mip chain by default
+    pub fn mipmaps(mut self, mipmaps: bool) -> Self {
+        self.mipmaps = mipmaps;
+        self
+    }
 }
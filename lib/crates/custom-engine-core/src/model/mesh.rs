@@ -3,6 +3,7 @@ use log::debug;
 use crate::{
     buffer::{Buffer, BufferBuilder},
     errors::CoreError,
+    model::morph::MorphDeltaRaw,
     traits::Builder,
 };
 
@@ -13,9 +14,12 @@ pub struct Mesh {
 
     pub num_elements: u32,
     pub material: usize,
+    pub vertex_count: usize,
+    pub morph_targets: usize,
 
     vertex_buffer: Buffer,
     index_buffer: Buffer,
+    morph_deltas_buffer: Option<Buffer>,
 }
 
 impl Mesh {
@@ -26,6 +30,11 @@ impl Mesh {
     pub fn vertex_buffer(&self) -> &Buffer {
         &self.vertex_buffer
     }
+
+    // Deltas of all targets of the mesh, they're shared by the nodes of the mesh
+    pub fn morph_deltas_buffer(&self) -> Option<&Buffer> {
+        self.morph_deltas_buffer.as_ref()
+    }
 }
 
 #[derive(Debug)]
@@ -37,6 +46,7 @@ pub struct MeshBuilder<'a, T: bytemuck::Pod + bytemuck::Zeroable> {
     vertex_buffer_binding: Option<u32>,
     material: Option<usize>,
     num_elements: Option<u32>,
+    morph_deltas: Option<(&'a [MorphDeltaRaw], u32)>,
 
     device: &'a wgpu::Device,
 }
@@ -56,6 +66,7 @@ impl<'a, T: bytemuck::Pod + bytemuck::Zeroable> Builder<'a> for MeshBuilder<'a,
             vertex_buffer_binding: None,
             num_elements: None,
             material: None,
+            morph_deltas: None,
             device,
         }
     }
@@ -72,6 +83,7 @@ impl<'a, T: bytemuck::Pod + bytemuck::Zeroable> Builder<'a> for MeshBuilder<'a,
             vertex_buffer_binding: None,
             num_elements: None,
             material: None,
+            morph_deltas: None,
             device,
         }
     }
@@ -100,12 +112,34 @@ impl<'a, T: bytemuck::Pod + bytemuck::Zeroable> Builder<'a> for MeshBuilder<'a,
         let vertex_buffer_data = self
             .vertex_buffer_data
             .ok_or(CoreError::EmptyData(name.to_string()))?;
+        let vertex_count = vertex_buffer_data.len();
         let vertex_buffer = BufferBuilder::new(self.device)
             .label(&format!("Vertex buffer: {name}"))
             .usage(wgpu::BufferUsages::VERTEX)
             .binding(vertex_buffer_binding)
             .data(vertex_buffer_data)
             .build()?;
+
+        // Deltas are laid out by targets, meshes without targets get the zero delta
+        let (morph_targets, morph_deltas_buffer) = match self.morph_deltas {
+            Some((deltas, binding)) => {
+                let morph_targets = deltas.len().checked_div(vertex_count).unwrap_or_default();
+                if morph_targets * vertex_count != deltas.len() {
+                    return Err(CoreError::WrongBufferSize);
+                }
+                let fallback = [MorphDeltaRaw::default()];
+
+                let buffer = BufferBuilder::new(self.device)
+                    .label(&format!("Morph deltas buffer: {name}"))
+                    .usage(wgpu::BufferUsages::STORAGE)
+                    .binding(binding)
+                    .data(if deltas.is_empty() { &fallback } else { deltas })
+                    .build()?;
+
+                (morph_targets, Some(buffer))
+            }
+            None => (0, None),
+        };
         let name = name.to_string();
 
         debug!(
@@ -113,6 +147,7 @@ impl<'a, T: bytemuck::Pod + bytemuck::Zeroable> Builder<'a> for MeshBuilder<'a,
 Build `{name}`:
     Number elements: {num_elements},
     Material id: {material},
+    Morph targets: {morph_targets},
             "
         );
 
@@ -121,8 +156,11 @@ Build `{name}`:
             name,
             vertex_buffer,
             index_buffer,
+            morph_deltas_buffer,
             num_elements,
             material,
+            vertex_count,
+            morph_targets,
         })
     }
 }
@@ -157,4 +195,9 @@ impl<'a, T: bytemuck::Pod + bytemuck::Zeroable> MeshBuilder<'a, T> {
         self.vertex_buffer_binding = Some(vertex_buffer_binding);
         self
     }
+
+    pub fn morph_deltas(mut self, deltas: &'a [MorphDeltaRaw], binding: u32) -> Self {
+        self.morph_deltas = Some((deltas, binding));
+        self
+    }
 }
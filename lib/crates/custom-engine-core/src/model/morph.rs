@@ -0,0 +1,155 @@
+use log::debug;
+
+use crate::{
+    buffer::{Buffer, BufferBuilder},
+    errors::CoreError,
+    traits::Builder,
+};
+
+// Weights follow the header of the targets and vertex count
+pub const MORPH_WEIGHTS_OFFSET: u64 = 16;
+
+#[repr(C)]
+#[derive(Copy, Clone, Debug, Default, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct MorphDeltaRaw {
+    pub position: [f32; 4],
+    pub normal: [f32; 4],
+    pub tangent: [f32; 4],
+}
+
+// Weights of the mesh targets for one node, the deltas are shared by the mesh
+#[derive(Debug)]
+pub struct MorphWeights {
+    pub id: usize,
+    pub name: String,
+
+    pub targets: usize,
+    pub vertex_count: usize,
+
+    weights_buffer: Buffer,
+}
+
+impl MorphWeights {
+    pub fn weights_buffer(&self) -> &Buffer {
+        &self.weights_buffer
+    }
+}
+
+pub struct MorphWeightsBuilder<'a> {
+    id: Option<usize>,
+    name: Option<&'a str>,
+
+    targets: usize,
+    vertex_count: usize,
+    weights: Option<&'a [f32]>,
+    binding: u32,
+
+    device: &'a wgpu::Device,
+}
+
+impl<'a> Builder<'a> for MorphWeightsBuilder<'a> {
+    type Final = MorphWeights;
+
+    fn new(device: &'a wgpu::Device) -> Self
+    where
+        Self: Sized,
+    {
+        Self {
+            id: None,
+            name: None,
+            targets: 0,
+            vertex_count: 0,
+            weights: None,
+            binding: 0,
+            device,
+        }
+    }
+
+    fn new_indexed(device: &'a wgpu::Device, id: usize) -> Self
+    where
+        Self: Sized,
+    {
+        Self {
+            id: Some(id),
+            name: None,
+            targets: 0,
+            vertex_count: 0,
+            weights: None,
+            binding: 0,
+            device,
+        }
+    }
+
+    fn build(self) -> Result<Self::Final, CoreError>
+    where
+        Self: Sized,
+    {
+        let id = self.id.unwrap_or_default();
+        let morph_name = format!("Morph weights: {id}");
+
+        let name = self.name.unwrap_or(&morph_name);
+        let targets = self.targets;
+        let vertex_count = self.vertex_count;
+        let binding = self.binding;
+
+        // Meshes without targets get the zero count, storage bindings can't be empty
+        let mut weights = self.weights.unwrap_or_default().to_vec();
+        weights.resize(targets.max(1), 0.);
+
+        let weights_data = [targets as u32, vertex_count as u32, 0, 0]
+            .into_iter()
+            .chain(weights.iter().map(|w| w.to_bits()))
+            .collect::<Vec<_>>();
+        let weights_buffer = BufferBuilder::new(self.device)
+            .label(&format!("Morph weights buffer: {name}"))
+            .usage(wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_DST)
+            .binding(binding)
+            .data(&weights_data)
+            .build()?;
+        let name = name.to_string();
+
+        debug!(
+            "
+Build `{name}`:
+    Targets: {targets},
+    Vertex count: {vertex_count},
+    Binding: {binding},
+            "
+        );
+
+        Ok(MorphWeights {
+            id,
+            name,
+            targets,
+            vertex_count,
+            weights_buffer,
+        })
+    }
+}
+
+impl<'a> MorphWeightsBuilder<'a> {
+    pub fn name(mut self, name: &'a str) -> Self {
+        self.name = Some(name);
+        self
+    }
+
+    pub fn targets(mut self, targets: usize) -> Self {
+        self.targets = targets;
+        self
+    }
+
+    pub fn vertex_count(mut self, vertex_count: usize) -> Self {
+        self.vertex_count = vertex_count;
+        self
+    }
+
+    pub fn weights(mut self, weights: &'a [f32]) -> Self {
+        self.weights = Some(weights);
+        self
+    }
+
+    pub fn binding(mut self, binding: u32) -> Self {
+        self.binding = binding;
+        self
+    }
+}
@@ -0,0 +1,251 @@
+use cgmath::{Matrix, Matrix3, Matrix4, SquareMatrix};
+use log::debug;
+
+use crate::{
+    bind_group::{BindGroup, BindGroupBuilder},
+    buffer::{Buffer, BufferBuilder},
+    errors::CoreError,
+    model::{
+        mesh::Mesh,
+        morph::{MorphWeights, MorphWeightsBuilder},
+    },
+    traits::Builder,
+};
+
+// Node entries of the bind group, joints and morph targets are set for the deformed models
+pub const NODE_TRANSFORM_BINDING: u32 = 0;
+pub const NODE_JOINTS_BINDING: u32 = 1;
+pub const NODE_MORPH_DELTAS_BINDING: u32 = 2;
+pub const NODE_MORPH_WEIGHTS_BINDING: u32 = 3;
+
+#[repr(C)]
+#[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct NodeRaw {
+    model: [[f32; 4]; 4],
+    // Columns of `mat3x3` are aligned to 16 bytes in the uniform
+    normal: [[f32; 4]; 3],
+}
+
+impl From<Matrix4<f32>> for NodeRaw {
+    fn from(model: Matrix4<f32>) -> Self {
+        let linear = Matrix3::from_cols(model.x.truncate(), model.y.truncate(), model.z.truncate());
+        let normal = linear
+            .invert()
+            .map_or(Matrix3::identity(), |m| m.transpose());
+
+        Self {
+            model: model.into(),
+            normal: [normal.x, normal.y, normal.z].map(|c| c.extend(0.).into()),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct Node {
+    pub id: usize,
+    pub name: String,
+
+    // Indices in `Model::meshes`, the same mesh is shared by many nodes
+    pub meshes: Vec<usize>,
+    // Index in `Model::skins`, the world transform of the skinned node is ignored
+    pub skin: Option<usize>,
+
+    transform_buffer: Buffer,
+    morph_weights: Vec<MorphWeights>,
+    bind_groups: Vec<BindGroup>,
+}
+
+impl Node {
+    pub fn transform_buffer(&self) -> &Buffer {
+        &self.transform_buffer
+    }
+
+    // Weights of the meshes with the morph targets, it's empty for the static models
+    pub fn morph_weights(&self) -> &[MorphWeights] {
+        &self.morph_weights
+    }
+
+    // Bind groups in the order of `meshes`
+    pub fn bind_groups(&self) -> &[BindGroup] {
+        &self.bind_groups
+    }
+}
+
+pub struct NodeBuilder<'a> {
+    id: Option<usize>,
+    name: Option<&'a str>,
+    layout: Option<&'a wgpu::BindGroupLayout>,
+    node_binding: u32,
+
+    transform: Option<Matrix4<f32>>,
+    meshes: Vec<(usize, &'a Mesh)>,
+    skin: Option<usize>,
+    joints_buffer: Option<&'a Buffer>,
+    weights: Option<&'a [f32]>,
+
+    device: &'a wgpu::Device,
+}
+
+impl<'a> Builder<'a> for NodeBuilder<'a> {
+    type Final = Node;
+
+    fn new(device: &'a wgpu::Device) -> Self
+    where
+        Self: Sized,
+    {
+        Self {
+            id: None,
+            name: None,
+            layout: None,
+            node_binding: 0,
+            transform: None,
+            meshes: vec![],
+            skin: None,
+            joints_buffer: None,
+            weights: None,
+            device,
+        }
+    }
+
+    fn new_indexed(device: &'a wgpu::Device, id: usize) -> Self
+    where
+        Self: Sized,
+    {
+        Self {
+            id: Some(id),
+            name: None,
+            layout: None,
+            node_binding: 0,
+            transform: None,
+            meshes: vec![],
+            skin: None,
+            joints_buffer: None,
+            weights: None,
+            device,
+        }
+    }
+
+    fn build(self) -> Result<Self::Final, CoreError>
+    where
+        Self: Sized,
+    {
+        let id = self.id.unwrap_or_default();
+        let node_name = format!("Node: {id}");
+
+        let name = self.name.unwrap_or(&node_name);
+        let node_binding = self.node_binding;
+        let skin = self.skin;
+        let layout = self
+            .layout
+            .ok_or(CoreError::EmptyLayout(name.to_string()))?;
+
+        let transform = NodeRaw::from(self.transform.unwrap_or(Matrix4::identity()));
+        let transform_buffer = BufferBuilder::new(self.device)
+            .label(&format!("Transform buffer: {name}"))
+            .usage(wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST)
+            .binding(NODE_TRANSFORM_BINDING)
+            .data(&[transform])
+            .build()?;
+
+        let mut morph_weights = vec![];
+        let mut bind_groups = vec![];
+        for (mesh_id, mesh) in &self.meshes {
+            let bg_name = format!("Bind group of: {name}, mesh {mesh_id}");
+            let bgb = BindGroupBuilder::new(self.device)
+                .label(&bg_name)
+                .layout(layout)
+                .binding(node_binding)
+                .entries_buffer(&transform_buffer);
+
+            // Deformed models bind every entry, the meshes without them get the fallbacks
+            let bind_group = match mesh.morph_deltas_buffer() {
+                Some(deltas) => {
+                    let joints = self
+                        .joints_buffer
+                        .ok_or(CoreError::EmptyData(name.to_string()))?;
+                    let weights = MorphWeightsBuilder::new_indexed(self.device, *mesh_id)
+                        .name(&format!("{name}, mesh {mesh_id}"))
+                        .targets(mesh.morph_targets)
+                        .vertex_count(mesh.vertex_count)
+                        .weights(self.weights.unwrap_or_default())
+                        .binding(NODE_MORPH_WEIGHTS_BINDING)
+                        .build()?;
+
+                    let bind_group = bgb
+                        .entries_buffers(vec![joints, deltas, weights.weights_buffer()])
+                        .build()?;
+                    morph_weights.push(weights);
+
+                    bind_group
+                }
+                None => bgb.build()?,
+            };
+            bind_groups.push(bind_group);
+        }
+        let meshes = self.meshes.iter().map(|(id, _)| *id).collect::<Vec<_>>();
+        let name = name.to_string();
+
+        debug!(
+            "
+Build `{name}`:
+    Meshes: {meshes:?},
+    Skin id: {skin:?},
+    Bind group binding: {node_binding},
+            "
+        );
+
+        Ok(Node {
+            id,
+            name,
+            meshes,
+            skin,
+            transform_buffer,
+            morph_weights,
+            bind_groups,
+        })
+    }
+}
+
+impl<'a> NodeBuilder<'a> {
+    pub fn name(mut self, name: &'a str) -> Self {
+        self.name = Some(name);
+        self
+    }
+
+    pub fn layout(mut self, layout: &'a wgpu::BindGroupLayout) -> Self {
+        self.layout = Some(layout);
+        self
+    }
+
+    pub fn node_binding(mut self, node_binding: u32) -> Self {
+        self.node_binding = node_binding;
+        self
+    }
+
+    // World transform of the node in the rest pose
+    pub fn transform(mut self, transform: Matrix4<f32>) -> Self {
+        self.transform = Some(transform);
+        self
+    }
+
+    pub fn mesh(mut self, mesh_id: usize, mesh: &'a Mesh) -> Self {
+        self.meshes.push((mesh_id, mesh));
+        self
+    }
+
+    pub fn skin(mut self, skin: usize) -> Self {
+        self.skin = Some(skin);
+        self
+    }
+
+    // Joints of the node skin or the identity fallback of the model
+    pub fn joints_buffer(mut self, joints_buffer: &'a Buffer) -> Self {
+        self.joints_buffer = Some(joints_buffer);
+        self
+    }
+
+    pub fn weights(mut self, weights: &'a [f32]) -> Self {
+        self.weights = Some(weights);
+        self
+    }
+}
@@ -0,0 +1,133 @@
+use log::debug;
+
+use crate::{
+    buffer::{Buffer, BufferBuilder},
+    errors::CoreError,
+    traits::Builder,
+};
+
+#[derive(Debug)]
+pub struct Skin {
+    pub id: usize,
+    pub name: String,
+
+    // Index of the skin in `Skeleton::skins`
+    pub skin: usize,
+    pub joints_count: usize,
+
+    joints_buffer: Buffer,
+}
+
+impl Skin {
+    pub fn joints_buffer(&self) -> &Buffer {
+        &self.joints_buffer
+    }
+}
+
+pub struct SkinBuilder<'a> {
+    id: Option<usize>,
+    name: Option<&'a str>,
+
+    skin: usize,
+    joint_matrices: Option<&'a [[[f32; 4]; 4]]>,
+    binding: u32,
+
+    device: &'a wgpu::Device,
+}
+
+impl<'a> Builder<'a> for SkinBuilder<'a> {
+    type Final = Skin;
+
+    fn new(device: &'a wgpu::Device) -> Self
+    where
+        Self: Sized,
+    {
+        Self {
+            id: None,
+            name: None,
+            skin: 0,
+            joint_matrices: None,
+            binding: 0,
+            device,
+        }
+    }
+
+    fn new_indexed(device: &'a wgpu::Device, id: usize) -> Self
+    where
+        Self: Sized,
+    {
+        Self {
+            id: Some(id),
+            name: None,
+            skin: 0,
+            joint_matrices: None,
+            binding: 0,
+            device,
+        }
+    }
+
+    fn build(self) -> Result<Self::Final, CoreError>
+    where
+        Self: Sized,
+    {
+        let id = self.id.unwrap_or_default();
+        let skin_name = format!("Skin: {id}");
+
+        let name = self.name.unwrap_or(&skin_name);
+        let skin = self.skin;
+        let binding = self.binding;
+
+        let joint_matrices = self
+            .joint_matrices
+            .filter(|m| !m.is_empty())
+            .ok_or(CoreError::EmptyData(name.to_string()))?;
+        let joints_count = joint_matrices.len();
+
+        let joints_buffer = BufferBuilder::new(self.device)
+            .label(&format!("Joints buffer: {name}"))
+            .usage(wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_DST)
+            .binding(binding)
+            .data(joint_matrices)
+            .build()?;
+        let name = name.to_string();
+
+        debug!(
+            "
+Build `{name}`:
+    Skin index: {skin},
+    Joints count: {joints_count},
+    Binding: {binding},
+            "
+        );
+
+        Ok(Skin {
+            id,
+            name,
+            skin,
+            joints_count,
+            joints_buffer,
+        })
+    }
+}
+
+impl<'a> SkinBuilder<'a> {
+    pub fn name(mut self, name: &'a str) -> Self {
+        self.name = Some(name);
+        self
+    }
+
+    pub fn skin(mut self, skin: usize) -> Self {
+        self.skin = skin;
+        self
+    }
+
+    pub fn joint_matrices(mut self, joint_matrices: &'a [[[f32; 4]; 4]]) -> Self {
+        self.joint_matrices = Some(joint_matrices);
+        self
+    }
+
+    pub fn binding(mut self, binding: u32) -> Self {
+        self.binding = binding;
+        self
+    }
+}
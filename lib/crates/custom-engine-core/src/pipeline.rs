@@ -1,11 +1,18 @@
 pub mod layout;
+pub mod push_constants;
 
-use std::num::NonZeroU32;
+use std::{num::NonZeroU32, sync::Arc};
 
 use derive_more::{Deref, DerefMut};
 use log::debug;
 
-use crate::{errors::CoreError, pipeline::layout::PipelineLayout, shader::Shader, traits::Builder};
+use crate::{
+    context::ResourceVersion,
+    errors::CoreError,
+    pipeline::{layout::PipelineLayout, push_constants::PushConstants},
+    shader::Shader,
+    traits::Builder,
+};
 
 #[derive(Debug)]
 pub enum InnerPipeline {
@@ -39,12 +46,27 @@ pub struct Pipeline {
     pub depth_stencil: Option<wgpu::DepthStencilState>,
     pub multisample: wgpu::MultisampleState,
     pub multiview: Option<NonZeroU32>,
+    pub color_targets: Vec<Option<wgpu::ColorTargetState>>,
+
+    push_constants: Option<Arc<PushConstants>>,
+    pub(crate) version: ResourceVersion,
 
     #[deref]
     #[deref_mut]
     inner_pipeline: InnerPipeline,
 }
 
+impl Pipeline {
+    #[inline]
+    pub fn push_constants(&self) -> Option<&PushConstants> {
+        self.push_constants.as_deref()
+    }
+
+    pub(crate) fn shared_push_constants(&self) -> Option<&Arc<PushConstants>> {
+        self.push_constants.as_ref()
+    }
+}
+
 pub struct PipelineBuilder<'a> {
     id: Option<usize>,
     label: Option<&'a str>,
@@ -133,6 +155,7 @@ Build `{label}`:
         );
         let is_compute = self.is_compute;
 
+        let mut color_targets = vec![];
         let inner_pipeline = if is_compute {
             let c_s = shader
                 .compute()
@@ -157,6 +180,7 @@ Build `{label}`:
             let r_s = shader
                 .render()
                 .ok_or(CoreError::NotRenderShader(label.to_string()))?;
+            color_targets.clone_from(&r_s.fs_options);
 
             InnerPipeline::Render(self.device.create_render_pipeline(
                 &wgpu::RenderPipelineDescriptor {
@@ -179,6 +203,9 @@ Build `{label}`:
             depth_stencil,
             multisample,
             multiview,
+            color_targets,
+            push_constants: layout.shared_push_constants(),
+            version: Default::default(),
 
             inner_pipeline,
         })
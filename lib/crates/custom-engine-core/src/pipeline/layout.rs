@@ -1,22 +1,40 @@
 use std::ops::Deref;
+use std::{mem::size_of, sync::Arc};
 
 use derive_more::{Deref, DerefMut};
 use log::debug;
 
-use crate::{bind_group::layout::BindGroupLayout, errors::CoreError, traits::Builder};
+use crate::{
+    bind_group::layout::BindGroupLayout, errors::CoreError,
+    pipeline::push_constants::PushConstants, traits::Builder,
+};
 
 #[derive(Debug, Deref, DerefMut)]
 pub struct PipelineLayout {
     pub id: usize,
 
+    push_constants: Option<Arc<PushConstants>>,
+
     #[deref]
     #[deref_mut]
     inner_pl: wgpu::PipelineLayout,
 }
 
+impl PipelineLayout {
+    #[inline]
+    pub fn push_constants(&self) -> Option<&PushConstants> {
+        self.push_constants.as_deref()
+    }
+
+    pub(crate) fn shared_push_constants(&self) -> Option<Arc<PushConstants>> {
+        self.push_constants.clone()
+    }
+}
+
 pub struct PipelineLayoutBuilder<'a> {
     id: Option<usize>,
     entries: Option<Vec<&'a wgpu::BindGroupLayout>>,
+    push_constants: Vec<(wgpu::ShaderStages, u32)>,
     label: Option<&'a str>,
 
     device: &'a wgpu::Device,
@@ -34,6 +52,7 @@ impl<'a> Builder<'a> for PipelineLayoutBuilder<'a> {
             label: None,
             id: None,
             entries: None,
+            push_constants: vec![],
         }
     }
 
@@ -46,6 +65,7 @@ impl<'a> Builder<'a> for PipelineLayoutBuilder<'a> {
             label: None,
             id: Some(id),
             entries: None,
+            push_constants: vec![],
         }
     }
 
@@ -57,23 +77,45 @@ impl<'a> Builder<'a> for PipelineLayoutBuilder<'a> {
         let layout_name = format!("Pipeline layout: {id}");
 
         let label = self.label.unwrap_or(&layout_name);
-        let entries = self.entries.unwrap_or_default();
+        let mut entries = self.entries.unwrap_or_default();
+        let push_constants = (!self.push_constants.is_empty()).then(|| {
+            PushConstants::new(
+                self.device,
+                label,
+                self.push_constants,
+                entries.len() as u32,
+            )
+        });
 
         debug!(
             "
 Build `{label}`:
-    Entries: {entries:#?},"
+    Entries: {entries:#?},
+    Push constants: {push_constants:#?},"
         );
 
+        if let Some(layout) = push_constants
+            .as_ref()
+            .and_then(|p_c| p_c.fallback_layout())
+        {
+            entries.push(layout);
+        }
         let inner_pl = self
             .device
             .create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
                 label: Some(label),
                 bind_group_layouts: entries.as_slice(),
-                push_constant_ranges: &[],
+                push_constant_ranges: push_constants
+                    .as_ref()
+                    .map(|p_c| p_c.native_ranges())
+                    .unwrap_or_default(),
             });
 
-        Ok(PipelineLayout { id, inner_pl })
+        Ok(PipelineLayout {
+            id,
+            push_constants: push_constants.map(Arc::new),
+            inner_pl,
+        })
     }
 }
 
@@ -88,6 +130,13 @@ impl<'a> PipelineLayoutBuilder<'a> {
         self
     }
 
+    // Ranges are packed in the declaration order. Without `PUSH_CONSTANTS` they are
+    // replaced by the uniform at the group after the last entry
+    pub fn push_constant<T: bytemuck::Pod>(mut self, stages: wgpu::ShaderStages) -> Self {
+        self.push_constants.push((stages, size_of::<T>() as u32));
+        self
+    }
+
     pub fn entries(mut self, bgls: Vec<&'a BindGroupLayout>) -> Self {
         self.entries
             .get_or_insert(vec![])
@@ -0,0 +1,377 @@
+use std::{
+    cell::RefCell,
+    num::NonZeroU64,
+    ptr,
+    sync::{Arc, Weak},
+};
+
+use log::debug;
+use wgpu::util::DeviceExt;
+
+use crate::{errors::CoreError, pipeline::Pipeline};
+
+// Initial size of the fallback buffer of the layout
+const FALLBACK_CAPACITY: u64 = 4096;
+
+// Push constant value of the draw or dispatch
+#[derive(Debug, Clone)]
+pub struct PushConstantValue {
+    pub stages: wgpu::ShaderStages,
+    pub data: Vec<u8>,
+}
+
+impl PushConstantValue {
+    pub fn new<T: bytemuck::Pod>(stages: wgpu::ShaderStages, value: &T) -> Self {
+        Self {
+            stages,
+            data: bytemuck::bytes_of(value).to_vec(),
+        }
+    }
+}
+
+// Push constant ranges of the pipeline layout, packed one after another
+#[derive(Debug)]
+pub struct PushConstants {
+    ranges: Vec<wgpu::PushConstantRange>,
+    // Uniform, which replaces push constants on backends without `PUSH_CONSTANTS`
+    fallback: Option<(u32, wgpu::BindGroupLayout)>,
+}
+
+impl PushConstants {
+    pub(crate) fn new(
+        device: &wgpu::Device,
+        label: &str,
+        ranges: Vec<(wgpu::ShaderStages, u32)>,
+        group: u32,
+    ) -> Self {
+        let mut offset = 0;
+        let ranges = ranges
+            .into_iter()
+            .map(|(stages, size)| {
+                let start = offset;
+                offset += align_to(size, wgpu::PUSH_CONSTANT_ALIGNMENT);
+
+                wgpu::PushConstantRange {
+                    stages,
+                    range: start..offset,
+                }
+            })
+            .collect::<Vec<_>>();
+
+        let is_supported = device.features().contains(wgpu::Features::PUSH_CONSTANTS)
+            && device.limits().max_push_constant_size >= offset;
+        let fallback = (!is_supported).then(|| {
+            debug!(
+                "`PUSH_CONSTANTS` are not supported, `{label}` uses the uniform of group {group}"
+            );
+
+            let visibility = ranges
+                .iter()
+                .fold(wgpu::ShaderStages::NONE, |v, r| v | r.stages);
+            let layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                label: Some(&format!("Push constants layout of `{label}`")),
+                entries: &[wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: true,
+                        min_binding_size: NonZeroU64::new(offset as u64),
+                    },
+                    count: None,
+                }],
+            });
+
+            (group, layout)
+        });
+
+        Self { ranges, fallback }
+    }
+
+    #[inline]
+    pub fn ranges(&self) -> &[wgpu::PushConstantRange] {
+        &self.ranges
+    }
+
+    #[inline]
+    pub fn size(&self) -> u32 {
+        self.ranges.last().map(|r| r.range.end).unwrap_or(0)
+    }
+
+    // Group of the fallback uniform in shaders, `None` when push constants are supported
+    #[inline]
+    pub fn fallback_group(&self) -> Option<u32> {
+        self.fallback.as_ref().map(|(group, _)| *group)
+    }
+
+    pub(crate) fn fallback_layout(&self) -> Option<&wgpu::BindGroupLayout> {
+        self.fallback.as_ref().map(|(_, layout)| layout)
+    }
+
+    pub(crate) fn native_ranges(&self) -> &[wgpu::PushConstantRange] {
+        if self.fallback.is_some() {
+            &[]
+        } else {
+            &self.ranges
+        }
+    }
+
+    pub(crate) fn offset(&self, label: &str, value: &PushConstantValue) -> Result<u32, CoreError> {
+        let range = self
+            .ranges
+            .iter()
+            .find(|r| r.stages == value.stages)
+            .ok_or(CoreError::PushConstantRangeNotFound(
+                label.to_string(),
+                value.stages,
+            ))?;
+
+        if value.data.len() as u32 > range.range.end - range.range.start {
+            return Err(CoreError::PushConstantSize(
+                label.to_string(),
+                value.data.len(),
+            ));
+        }
+
+        Ok(range.range.start)
+    }
+}
+
+// Uniform buffer and its bind group of the fallback push constants of one layout
+#[derive(Debug)]
+pub(crate) struct FallbackBinding {
+    buffer: wgpu::Buffer,
+    bind_group: wgpu::BindGroup,
+}
+
+impl FallbackBinding {
+    fn new(
+        device: &wgpu::Device,
+        label: &str,
+        push_constants: &PushConstants,
+        capacity: u64,
+        contents: Option<&[u8]>,
+    ) -> Option<Self> {
+        let layout = push_constants.fallback_layout()?;
+        let label = format!("Push constants of `{label}`");
+
+        let buffer = match contents {
+            Some(contents) => device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some(&label),
+                contents,
+                usage: wgpu::BufferUsages::UNIFORM,
+            }),
+            None => device.create_buffer(&wgpu::BufferDescriptor {
+                label: Some(&label),
+                size: capacity,
+                usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+                mapped_at_creation: false,
+            }),
+        };
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some(&label),
+            layout,
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: wgpu::BindingResource::Buffer(wgpu::BufferBinding {
+                    buffer: &buffer,
+                    offset: 0,
+                    size: NonZeroU64::new(push_constants.size() as u64),
+                }),
+            }],
+        });
+
+        Some(Self { buffer, bind_group })
+    }
+}
+
+#[derive(Debug)]
+struct FallbackBuffer {
+    push_constants: Weak<PushConstants>,
+    binding: Arc<FallbackBinding>,
+    capacity: u64,
+    cursor: u64,
+}
+
+// Fallback push constants of every layout, the worker keeps them between frames
+#[derive(Debug, Default)]
+pub(crate) struct PushConstantsBuffers {
+    buffers: RefCell<Vec<FallbackBuffer>>,
+}
+
+impl PushConstantsBuffers {
+    // Written data goes before the submitted commands, so the offsets are reused after submit
+    pub fn reset(&self) {
+        let mut buffers = self.buffers.borrow_mut();
+
+        buffers.retain(|b| b.push_constants.strong_count() > 0);
+        buffers.iter_mut().for_each(|b| b.cursor = 0);
+    }
+
+    // Returns the binding and the offset of the written data, the full buffer grows twice
+    fn write(
+        &self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        label: &str,
+        push_constants: &Arc<PushConstants>,
+        data: &[u8],
+    ) -> Option<(Arc<FallbackBinding>, u32)> {
+        let mut buffers = self.buffers.borrow_mut();
+        let size = data.len() as u64;
+
+        let index = match buffers
+            .iter()
+            .position(|b| ptr::eq(b.push_constants.as_ptr(), Arc::as_ptr(push_constants)))
+        {
+            Some(i) => i,
+            None => {
+                let capacity = FALLBACK_CAPACITY.max(size.next_power_of_two());
+                buffers.push(FallbackBuffer {
+                    push_constants: Arc::downgrade(push_constants),
+                    binding: Arc::new(FallbackBinding::new(
+                        device,
+                        label,
+                        push_constants,
+                        capacity,
+                        None,
+                    )?),
+                    capacity,
+                    cursor: 0,
+                });
+                buffers.len() - 1
+            }
+        };
+
+        let buffer = &mut buffers[index];
+        if buffer.cursor + size > buffer.capacity {
+            // Draws, which are already encoded, keep the old buffer alive
+            let capacity = (buffer.capacity * 2).max((buffer.cursor + size).next_power_of_two());
+            debug!("Grow push constants of `{label}` to {capacity} bytes");
+
+            buffer.binding = Arc::new(FallbackBinding::new(
+                device,
+                label,
+                push_constants,
+                capacity,
+                None,
+            )?);
+            buffer.capacity = capacity;
+            buffer.cursor = 0;
+        }
+
+        let offset = buffer.cursor;
+        queue.write_buffer(&buffer.binding.buffer, offset, data);
+        buffer.cursor += size;
+
+        Some((buffer.binding.clone(), offset as u32))
+    }
+}
+
+// Fallback push constants of the draws of the stage, every draw has its own dynamic offset
+#[derive(Debug, Default)]
+pub(crate) struct PushConstantsFallback {
+    bindings: Vec<(Arc<FallbackBinding>, u32)>,
+    draws: DrawSlots,
+}
+
+impl PushConstantsFallback {
+    // Render bundles are recorded once, so they own the buffers
+    pub fn new<'v>(
+        device: &wgpu::Device,
+        label: &str,
+        draws: impl Iterator<Item = (&'v Pipeline, &'v [PushConstantValue])>,
+    ) -> Result<Self, CoreError> {
+        let (layouts, draws) = pack(device, draws)?;
+        let bindings = layouts
+            .into_iter()
+            .filter_map(|(push_constants, data)| {
+                FallbackBinding::new(device, label, push_constants, 0, Some(&data))
+            })
+            .map(|binding| (Arc::new(binding), 0))
+            .collect();
+
+        Ok(Self { bindings, draws })
+    }
+
+    // Render and compute stages write into the buffers of the worker
+    pub fn write<'v>(
+        buffers: &PushConstantsBuffers,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        label: &str,
+        draws: impl Iterator<Item = (&'v Pipeline, &'v [PushConstantValue])>,
+    ) -> Result<Self, CoreError> {
+        let (layouts, draws) = pack(device, draws)?;
+        let bindings = layouts
+            .into_iter()
+            .filter_map(|(push_constants, data)| {
+                buffers.write(device, queue, label, push_constants, &data)
+            })
+            .collect();
+
+        Ok(Self { bindings, draws })
+    }
+
+    // Bind group and dynamic offset of the draw
+    pub fn get(&self, draw: usize) -> Option<(&wgpu::BindGroup, u32)> {
+        self.draws
+            .get(draw)
+            .copied()
+            .flatten()
+            .and_then(|(i, offset)| {
+                let (binding, base) = self.bindings.get(i)?;
+
+                Some((&binding.bind_group, base + offset))
+            })
+    }
+}
+
+type PackedLayouts<'v> = Vec<(&'v Arc<PushConstants>, Vec<u8>)>;
+// Layout and offset of the data of every draw
+type DrawSlots = Vec<Option<(usize, u32)>>;
+
+// Values of every draw are packed into the data of its layout by the uniform alignment
+fn pack<'v>(
+    device: &wgpu::Device,
+    draws: impl Iterator<Item = (&'v Pipeline, &'v [PushConstantValue])>,
+) -> Result<(PackedLayouts<'v>, DrawSlots), CoreError> {
+    let alignment = device.limits().min_uniform_buffer_offset_alignment;
+
+    let mut layouts: PackedLayouts<'v> = vec![];
+    let mut slots = vec![];
+    for (pipeline, values) in draws {
+        let Some(push_constants) = pipeline
+            .shared_push_constants()
+            .filter(|p_c| p_c.fallback_group().is_some())
+        else {
+            slots.push(None);
+            continue;
+        };
+
+        let layout_index = layouts
+            .iter()
+            .position(|(p_c, _)| Arc::ptr_eq(p_c, push_constants))
+            .unwrap_or_else(|| {
+                layouts.push((push_constants, vec![]));
+                layouts.len() - 1
+            });
+        let data = &mut layouts[layout_index].1;
+
+        let offset = data.len();
+        data.resize(offset + push_constants.size() as usize, 0);
+        for v in values {
+            let start = offset + push_constants.offset(&pipeline.label, v)? as usize;
+            data[start..start + v.data.len()].copy_from_slice(&v.data);
+        }
+        data.resize(align_to(data.len() as u32, alignment) as usize, 0);
+
+        slots.push(Some((layout_index, offset as u32)));
+    }
+
+    Ok((layouts, slots))
+}
+
+fn align_to(size: u32, alignment: u32) -> u32 {
+    size.div_ceil(alignment) * alignment
+}
@@ -0,0 +1,212 @@
+use std::{cell::RefCell, iter::once, mem::size_of};
+
+use flume::{bounded, Receiver, TryRecvError};
+use log::{debug, error, warn};
+
+// Max count of profiled stages per frame, every stage takes two queries
+const MAX_STAGES: u32 = 128;
+// Results are read back a few frames later, so the buffers are kept in a ring
+const READBACK_BUFFERS: usize = 3;
+
+#[derive(Debug, Clone)]
+pub struct StageProfile {
+    pub label: String,
+    pub gpu_ms: f64,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct FrameProfile {
+    pub frame: u64,
+    pub stages: Vec<StageProfile>,
+}
+
+impl FrameProfile {
+    pub fn total_ms(&self) -> f64 {
+        self.stages.iter().map(|s| s.gpu_ms).sum()
+    }
+}
+
+#[derive(Debug)]
+struct Readback {
+    buffer: wgpu::Buffer,
+    frame: u64,
+    labels: Vec<String>,
+    receiver: Option<Receiver<Result<(), wgpu::BufferAsyncError>>>,
+}
+
+#[derive(Debug, Default)]
+struct FrameQueries {
+    labels: Vec<String>,
+    overflowed: bool,
+}
+
+#[derive(Debug)]
+pub struct GpuProfiler {
+    query_set: wgpu::QuerySet,
+    resolve_buffer: wgpu::Buffer,
+    readbacks: Vec<Readback>,
+    period: f64,
+
+    queries: RefCell<FrameQueries>,
+    last: Option<FrameProfile>,
+}
+
+impl GpuProfiler {
+    pub(crate) fn new(device: &wgpu::Device, queue: &wgpu::Queue) -> Self {
+        let size = (MAX_STAGES * 2) as u64 * size_of::<u64>() as u64;
+
+        let query_set = device.create_query_set(&wgpu::QuerySetDescriptor {
+            label: Some("Profiler query set"),
+            ty: wgpu::QueryType::Timestamp,
+            count: MAX_STAGES * 2,
+        });
+        let resolve_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Profiler resolve buffer"),
+            size,
+            usage: wgpu::BufferUsages::QUERY_RESOLVE | wgpu::BufferUsages::COPY_SRC,
+            mapped_at_creation: false,
+        });
+        let readbacks = (0..READBACK_BUFFERS)
+            .map(|i| Readback {
+                buffer: device.create_buffer(&wgpu::BufferDescriptor {
+                    label: Some(&format!("Profiler readback buffer: {i}")),
+                    size,
+                    usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+                    mapped_at_creation: false,
+                }),
+                frame: 0,
+                labels: vec![],
+                receiver: None,
+            })
+            .collect();
+
+        Self {
+            query_set,
+            resolve_buffer,
+            readbacks,
+            period: queue.get_timestamp_period() as f64,
+            queries: Default::default(),
+            last: None,
+        }
+    }
+
+    // Begin and end query indices of the stage
+    pub(crate) fn allocate(&self, label: String) -> Option<(&wgpu::QuerySet, u32, u32)> {
+        let mut queries = self.queries.borrow_mut();
+        let index = queries.labels.len() as u32;
+
+        if index >= MAX_STAGES {
+            if !queries.overflowed {
+                warn!("More than {MAX_STAGES} stages in the frame, `{label}` is not profiled");
+                queries.overflowed = true;
+            }
+
+            return None;
+        }
+        queries.labels.push(label);
+
+        Some((&self.query_set, index * 2, index * 2 + 1))
+    }
+
+    pub(crate) fn end_frame(&mut self, device: &wgpu::Device, queue: &wgpu::Queue, frame: u64) {
+        self.collect(device);
+
+        let FrameQueries { labels, .. } = self.queries.take();
+        if labels.is_empty() {
+            return;
+        }
+
+        let Some(readback) = self.readbacks.iter_mut().find(|r| r.receiver.is_none()) else {
+            debug!("Profiler readback buffers are busy, frame {frame} is skipped");
+            return;
+        };
+
+        let count = labels.len() as u32 * 2;
+        let size = count as u64 * size_of::<u64>() as u64;
+
+        let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+            label: Some("Command Encoder of `Profiler`"),
+        });
+        encoder.resolve_query_set(&self.query_set, 0..count, &self.resolve_buffer, 0);
+        encoder.copy_buffer_to_buffer(&self.resolve_buffer, 0, &readback.buffer, 0, size);
+        queue.submit(once(encoder.finish()));
+
+        let (tx, rx) = bounded(1);
+        readback
+            .buffer
+            .slice(..size)
+            .map_async(wgpu::MapMode::Read, move |r| {
+                if let Err(e) = tx.send(r) {
+                    error!("Profiler buffer, map async error: {e}");
+                }
+            });
+
+        readback.frame = frame;
+        readback.labels = labels;
+        readback.receiver = Some(rx);
+    }
+
+    pub fn last_profile(&self) -> Option<&FrameProfile> {
+        self.last.as_ref()
+    }
+
+    fn collect(&mut self, device: &wgpu::Device) {
+        device.poll(wgpu::Maintain::Poll);
+
+        for readback in self.readbacks.iter_mut() {
+            let Some(rx) = readback.receiver.as_ref() else {
+                continue;
+            };
+
+            match rx.try_recv() {
+                Ok(Ok(())) => {
+                    let size = readback.labels.len() as u64 * 2 * size_of::<u64>() as u64;
+                    let timestamps: Vec<u64> = {
+                        let data = readback.buffer.slice(..size).get_mapped_range();
+                        bytemuck::cast_slice(&data).to_vec()
+                    };
+                    readback.buffer.unmap();
+
+                    let stages = readback
+                        .labels
+                        .drain(..)
+                        .zip(timestamps.chunks_exact(2))
+                        .map(|(label, t)| StageProfile {
+                            label,
+                            gpu_ms: t[1].saturating_sub(t[0]) as f64 * self.period / 1_000_000.,
+                        })
+                        .collect();
+                    let profile = FrameProfile {
+                        frame: readback.frame,
+                        stages,
+                    };
+
+                    debug!(
+                        "
+GPU profile of frame {}:
+    Total: {:.3} ms,
+    Stages: {:#?}",
+                        profile.frame,
+                        profile.total_ms(),
+                        profile.stages
+                    );
+
+                    if self.last.as_ref().is_none_or(|l| l.frame <= profile.frame) {
+                        self.last = Some(profile);
+                    }
+                }
+                Ok(Err(e)) => {
+                    error!(
+                        "Profiler buffer of frame {} is not mapped: {e}",
+                        readback.frame
+                    );
+                    readback.labels.clear();
+                }
+                Err(TryRecvError::Empty) => continue,
+                Err(TryRecvError::Disconnected) => readback.labels.clear(),
+            }
+
+            readback.receiver = None;
+        }
+    }
+}
@@ -0,0 +1,267 @@
+mod pass;
+mod transient;
+
+pub use pass::*;
+pub use transient::*;
+
+use std::{
+    cmp::Reverse,
+    collections::{BTreeMap, BTreeSet, BinaryHeap},
+};
+
+use log::debug;
+
+use crate::{
+    errors::CoreError,
+    render_pass::color_attachment::ColorAttachmentBuilder,
+    texture::{DepthTexture, RenderTexture},
+    worker::Worker,
+};
+
+#[derive(Debug, Default)]
+pub struct RenderGraph {
+    size: Option<(u32, u32)>,
+    sample_count: u32,
+    descs: BTreeMap<String, TransientTexture>,
+    textures: BTreeMap<String, TransientResource>,
+    // Multisampled colors, which are resolved into `textures`
+    msaa: BTreeMap<String, RenderTexture>,
+}
+
+impl RenderGraph {
+    pub fn new() -> Self {
+        Self {
+            ..Default::default()
+        }
+    }
+
+    pub fn transient<S: Into<String>>(mut self, name: S, texture: TransientTexture) -> Self {
+        self.descs.insert(name.into(), texture);
+        self.size = None;
+        self
+    }
+
+    // Allocate transient attachments, they are recreated on resize or a new sample count
+    pub fn prepare(&mut self, w: &Worker<'_>) -> Result<(), CoreError> {
+        let size = w.size();
+        let sample_count = w.sample_count();
+        if self.size == Some(size) && self.sample_count == sample_count {
+            return Ok(());
+        }
+
+        debug!(
+            "
+Allocate transient textures of `render graph`:
+    Size: {size:?},
+    Sample count: {sample_count},
+    Textures: {:#?}",
+            self.descs.keys()
+        );
+
+        for desc in self.descs.values().filter(|d| d.is_multisampled()) {
+            let format = desc.kind().format();
+            if !w.is_sample_count_supported(format, sample_count) {
+                return Err(CoreError::UnsupportedSampleCount(format, sample_count));
+            }
+        }
+
+        self.textures = self
+            .descs
+            .iter()
+            .map(|(name, desc)| {
+                Ok((
+                    name.clone(),
+                    desc.allocate(name, &w.device, size, sample_count)?,
+                ))
+            })
+            .collect::<Result<_, CoreError>>()?;
+        self.msaa = self
+            .descs
+            .iter()
+            .filter_map(|(name, desc)| {
+                desc.allocate_msaa(name, &w.device, size, sample_count)
+                    .transpose()
+                    .map(|msaa| Ok((name.clone(), msaa?)))
+            })
+            .collect::<Result<_, CoreError>>()?;
+        self.size = Some(size);
+        self.sample_count = sample_count;
+
+        Ok(())
+    }
+
+    pub fn resource(&self, name: &str) -> Result<&TransientResource, CoreError> {
+        self.textures
+            .get(name)
+            .ok_or(CoreError::RenderGraphResource(name.to_string()))
+    }
+
+    pub fn texture(&self, name: &str) -> Result<&RenderTexture, CoreError> {
+        match self.resource(name)? {
+            TransientResource::Color(rt) => Ok(rt),
+            TransientResource::Depth(_) => Err(CoreError::RenderGraphResource(name.to_string())),
+        }
+    }
+
+    pub fn depth_texture(&self, name: &str) -> Result<&DepthTexture, CoreError> {
+        match self.resource(name)? {
+            TransientResource::Depth(dt) => Ok(dt),
+            TransientResource::Color(_) => Err(CoreError::RenderGraphResource(name.to_string())),
+        }
+    }
+
+    pub fn view(&self, name: &str) -> Result<&wgpu::TextureView, CoreError> {
+        self.resource(name).map(TransientResource::view)
+    }
+
+    // Multisampled colors are resolved into the transient texture at the end of the pass
+    pub fn color_attachment(&self, name: &str) -> Result<ColorAttachmentBuilder<'_>, CoreError> {
+        let texture = self.texture(name)?;
+        let attachment = ColorAttachmentBuilder::new().format(texture.format());
+
+        let attachment = match self.msaa.get(name) {
+            Some(msaa) => attachment.view(msaa.view()).resolve_target(texture.view()),
+            None => attachment.view(texture.view()),
+        };
+
+        Ok(attachment)
+    }
+
+    // Readers go after the last writer declared before them, writers keep the declaration order
+    pub fn order<'a>(passes: Vec<GraphPass<'a>>) -> Result<Vec<GraphPass<'a>>, CoreError> {
+        let deps = passes
+            .iter()
+            .map(|p| (p.reads.as_slice(), p.writes.as_slice()))
+            .collect::<Vec<_>>();
+
+        let order = order_passes(&deps).map_err(|cycle| {
+            CoreError::RenderGraphCycle(
+                cycle
+                    .into_iter()
+                    .map(|i| passes[i].name)
+                    .collect::<Vec<_>>()
+                    .join(", "),
+            )
+        })?;
+
+        let mut passes = passes.into_iter().map(Some).collect::<Vec<_>>();
+
+        Ok(order.into_iter().filter_map(|i| passes[i].take()).collect())
+    }
+}
+
+fn order_passes(deps: &[(&[&str], &[&str])]) -> Result<Vec<usize>, Vec<usize>> {
+    let n = deps.len();
+    let mut edges = BTreeSet::new();
+
+    for (j, (reads, writes)) in deps.iter().enumerate() {
+        // Writers keep the declaration order
+        for (i, (_, other_writes)) in deps[..j].iter().enumerate() {
+            if writes.iter().any(|w| other_writes.contains(w)) {
+                edges.insert((i, j));
+            }
+        }
+
+        for r in reads.iter() {
+            let writers = (0..n)
+                .filter(|&i| i != j && deps[i].1.contains(r))
+                .collect::<Vec<_>>();
+
+            // The reader gets the output of the last writer declared before it,
+            // later writers overwrite the resource after it
+            match writers.iter().rev().find(|&&i| i < j) {
+                Some(&i) => {
+                    edges.insert((i, j));
+                    edges.extend(writers.iter().filter(|&&i| i > j).map(|&i| (j, i)));
+                }
+                // A pass, which updates the resource in place, is the first writer
+                None if writes.contains(r) => {}
+                // Readers declared before all of the writers get the final output
+                None => edges.extend(writers.last().map(|&i| (i, j))),
+            }
+        }
+    }
+
+    let mut adjacent = vec![vec![]; n];
+    let mut in_degree = vec![0; n];
+    for (i, j) in edges {
+        adjacent[i].push(j);
+        in_degree[j] += 1;
+    }
+
+    let mut ready = (0..n)
+        .filter(|&i| in_degree[i] == 0)
+        .map(Reverse)
+        .collect::<BinaryHeap<_>>();
+    let mut order = Vec::with_capacity(n);
+
+    while let Some(Reverse(i)) = ready.pop() {
+        order.push(i);
+
+        for &j in &adjacent[i] {
+            in_degree[j] -= 1;
+            if in_degree[j] == 0 {
+                ready.push(Reverse(j));
+            }
+        }
+    }
+
+    if order.len() == n {
+        Ok(order)
+    } else {
+        Err((0..n).filter(|&i| in_degree[i] > 0).collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn order_by_dependencies() {
+        let tonemap: (&[&str], &[&str]) = (&["hdr"], &["surface"]);
+        let scene: (&[&str], &[&str]) = (&["camera"], &["hdr", "depth"]);
+        let ui: (&[&str], &[&str]) = (&[], &["surface"]);
+
+        assert_eq!(order_passes(&[tonemap, ui, scene]), Ok(vec![2, 0, 1]));
+
+        let a: (&[&str], &[&str]) = (&["b"], &["a"]);
+        let b: (&[&str], &[&str]) = (&["a"], &["b"]);
+        assert_eq!(order_passes(&[a, b]), Err(vec![0, 1]));
+    }
+
+    #[test]
+    fn order_in_place_passes() {
+        let scene: (&[&str], &[&str]) = (&["camera"], &["hdr"]);
+        let bloom: (&[&str], &[&str]) = (&["hdr"], &["hdr"]);
+        let prepare: (&[&str], &[&str]) = (&["hdr"], &["hdr"]);
+        let tonemap: (&[&str], &[&str]) = (&["hdr"], &["surface"]);
+
+        assert_eq!(
+            order_passes(&[scene, bloom, prepare, tonemap]),
+            Ok(vec![0, 1, 2, 3])
+        );
+        assert_eq!(order_passes(&[bloom, prepare]), Ok(vec![0, 1]));
+    }
+
+    #[test]
+    fn order_reader_before_overwrite() {
+        let scene: (&[&str], &[&str]) = (&[], &["hdr"]);
+        let tonemap: (&[&str], &[&str]) = (&["hdr"], &["surface"]);
+        let debug: (&[&str], &[&str]) = (&[], &["hdr"]);
+        let overlay: (&[&str], &[&str]) = (&["hdr"], &["surface"]);
+
+        assert_eq!(
+            order_passes(&[scene, tonemap, debug, overlay]),
+            Ok(vec![0, 1, 2, 3])
+        );
+
+        // The overwrite is ready before the reader, but it waits for it
+        let tonemap: (&[&str], &[&str]) = (&["hdr", "lut"], &["surface"]);
+        let lut: (&[&str], &[&str]) = (&[], &["lut"]);
+        assert_eq!(
+            order_passes(&[scene, tonemap, debug, lut]),
+            Ok(vec![0, 3, 1, 2])
+        );
+    }
+}
@@ -0,0 +1,44 @@
+use crate::render_pass::RenderPass;
+
+#[derive(Debug)]
+pub struct GraphPass<'a> {
+    pub(crate) name: &'a str,
+    pub(crate) reads: Vec<&'a str>,
+    pub(crate) writes: Vec<&'a str>,
+    pub(crate) pass: RenderPass<'a>,
+}
+
+impl<'a> GraphPass<'a> {
+    pub fn new(name: &'a str, pass: RenderPass<'a>) -> Self {
+        Self {
+            name,
+            pass,
+            reads: vec![],
+            writes: vec![],
+        }
+    }
+
+    pub fn read(mut self, resource: &'a str) -> Self {
+        self.reads.push(resource);
+        self
+    }
+
+    pub fn reads(mut self, resources: Vec<&'a str>) -> Self {
+        self.reads.extend(resources);
+        self
+    }
+
+    pub fn write(mut self, resource: &'a str) -> Self {
+        self.writes.push(resource);
+        self
+    }
+
+    pub fn writes(mut self, resources: Vec<&'a str>) -> Self {
+        self.writes.extend(resources);
+        self
+    }
+
+    pub fn name(&self) -> &str {
+        self.name
+    }
+}
@@ -0,0 +1,210 @@
+use crate::{
+    errors::CoreError,
+    texture::{
+        DepthTexture, DepthTextureBuilder, RenderTexture, RenderTextureBuilder, TextureKind,
+    },
+    traits::Builder,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TextureSize {
+    // Scale of the surface size
+    Relative(f32),
+    Absolute(u32, u32),
+}
+
+impl Default for TextureSize {
+    fn default() -> Self {
+        TextureSize::Relative(1.)
+    }
+}
+
+impl TextureSize {
+    pub fn resolve(&self, surface_size: (u32, u32)) -> (u32, u32) {
+        let (w, h) = match *self {
+            TextureSize::Relative(scale) => (
+                (surface_size.0 as f32 * scale) as u32,
+                (surface_size.1 as f32 * scale) as u32,
+            ),
+            TextureSize::Absolute(w, h) => (w, h),
+        };
+
+        (w.max(1), h.max(1))
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum TransientKind {
+    Color(wgpu::TextureFormat),
+    Depth,
+}
+
+impl TransientKind {
+    pub fn format(&self) -> wgpu::TextureFormat {
+        match *self {
+            TransientKind::Color(format) => format,
+            TransientKind::Depth => TextureKind::Depth.into(),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct TransientTexture {
+    kind: TransientKind,
+    size: TextureSize,
+    usage: wgpu::TextureUsages,
+    filter: wgpu::FilterMode,
+    bind_group_binding: Option<u32>,
+    multisampled: bool,
+}
+
+impl TransientTexture {
+    pub fn color<T: Into<wgpu::TextureFormat>>(format: T) -> Self {
+        Self {
+            kind: TransientKind::Color(format.into()),
+            size: TextureSize::default(),
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::TEXTURE_BINDING,
+            filter: wgpu::FilterMode::Linear,
+            bind_group_binding: None,
+            multisampled: false,
+        }
+    }
+
+    pub fn depth() -> Self {
+        Self {
+            kind: TransientKind::Depth,
+            size: TextureSize::default(),
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::TEXTURE_BINDING,
+            filter: wgpu::FilterMode::Linear,
+            bind_group_binding: None,
+            multisampled: false,
+        }
+    }
+
+    pub fn size(mut self, size: TextureSize) -> Self {
+        self.size = size;
+        self
+    }
+
+    pub fn usage(mut self, usage: wgpu::TextureUsages) -> Self {
+        self.usage = usage;
+        self
+    }
+
+    pub fn filter(mut self, filter: wgpu::FilterMode) -> Self {
+        self.filter = filter;
+        self
+    }
+
+    // Creates the bind group with the view on binding `0` and the sampler on binding `1`
+    pub fn bind_group_binding(mut self, bind_group_binding: u32) -> Self {
+        self.bind_group_binding = Some(bind_group_binding);
+        self
+    }
+
+    // Uses the sample count of the worker, colors are resolved into a single sampled texture
+    pub fn multisampled(mut self, multisampled: bool) -> Self {
+        self.multisampled = multisampled;
+        self
+    }
+
+    pub fn kind(&self) -> TransientKind {
+        self.kind
+    }
+
+    pub fn is_multisampled(&self) -> bool {
+        self.multisampled
+    }
+
+    pub(crate) fn allocate(
+        &self,
+        label: &str,
+        device: &wgpu::Device,
+        surface_size: (u32, u32),
+        sample_count: u32,
+    ) -> Result<TransientResource, CoreError> {
+        let size = self.size.resolve(surface_size);
+        let sample_count = if self.multisampled { sample_count } else { 1 };
+
+        let resource = match self.kind {
+            TransientKind::Color(format) => {
+                let mut builder = RenderTextureBuilder::new(device)
+                    .label(label)
+                    .format(format)
+                    .usage(self.usage)
+                    .texture_size(size)
+                    .is_sampler(self.bind_group_binding.is_some());
+
+                if let Some(binding) = self.bind_group_binding {
+                    builder = builder
+                        .sampler_desc(wgpu::SamplerDescriptor {
+                            address_mode_u: wgpu::AddressMode::ClampToEdge,
+                            address_mode_v: wgpu::AddressMode::ClampToEdge,
+                            address_mode_w: wgpu::AddressMode::ClampToEdge,
+                            mag_filter: self.filter,
+                            min_filter: self.filter,
+                            mipmap_filter: wgpu::FilterMode::Nearest,
+                            ..Default::default()
+                        })
+                        .bind_group_binding(binding);
+                }
+
+                TransientResource::Color(builder.build()?)
+            }
+            TransientKind::Depth => TransientResource::Depth(
+                DepthTextureBuilder::new(device)
+                    .label(label)
+                    .usage(self.usage)
+                    .texture_size(size)
+                    .sample_count(sample_count)
+                    .is_sampler(false)
+                    .build()?,
+            ),
+        };
+
+        Ok(resource)
+    }
+
+    // Multisampled color, which is resolved into the allocated resource
+    pub(crate) fn allocate_msaa(
+        &self,
+        label: &str,
+        device: &wgpu::Device,
+        surface_size: (u32, u32),
+        sample_count: u32,
+    ) -> Result<Option<RenderTexture>, CoreError> {
+        let TransientKind::Color(format) = self.kind else {
+            return Ok(None);
+        };
+        if !self.multisampled || sample_count <= 1 {
+            return Ok(None);
+        }
+
+        let msaa_label = format!("MSAA {label}");
+        let msaa = RenderTextureBuilder::new(device)
+            .label(&msaa_label)
+            .format(format)
+            .usage(wgpu::TextureUsages::RENDER_ATTACHMENT)
+            .texture_size(self.size.resolve(surface_size))
+            .sample_count(sample_count)
+            .is_sampler(false)
+            .build()?;
+
+        Ok(Some(msaa))
+    }
+}
+
+#[derive(Debug)]
+pub enum TransientResource {
+    Color(RenderTexture),
+    Depth(DepthTexture),
+}
+
+impl TransientResource {
+    pub fn view(&self) -> &wgpu::TextureView {
+        match self {
+            TransientResource::Color(rt) => rt.view(),
+            TransientResource::Depth(dt) => &dt.view,
+        }
+    }
+}
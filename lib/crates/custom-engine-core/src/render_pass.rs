@@ -1,8 +1,10 @@
+pub mod bundle;
 pub mod color_attachment;
 pub mod depth_stencil;
+pub mod draw_item;
 pub mod query_set;
 
-use std::{collections::BTreeMap, iter::once, ops::Range};
+use std::{collections::BTreeMap, iter::once, mem, ops::Range};
 
 use log::{debug, warn};
 
@@ -11,9 +13,16 @@ use crate::{
     buffer::Buffer,
     errors::CoreError,
     model::Model,
-    pipeline::Pipeline,
+    pipeline::{
+        push_constants::{PushConstantValue, PushConstantsBuffers, PushConstantsFallback},
+        Pipeline,
+    },
+    profiler::GpuProfiler,
     render_pass::{
-        color_attachment::ColorAttachmentBuilder, depth_stencil::DepthStencilAttachmentBuilder,
+        bundle::RenderBundle,
+        color_attachment::{ColorAttachmentBuilder, ColorAttachments},
+        depth_stencil::DepthStencilAttachmentBuilder,
+        draw_item::{DrawItem, DrawState},
         query_set::QuerySet,
     },
     texture::CopyTextureParams,
@@ -30,21 +39,17 @@ impl<'a> Stage<'a> {
         self,
         index: usize,
         label: &str,
+        device: &wgpu::Device,
         encoder: &mut wgpu::CommandEncoder,
+        profiler: Option<&GpuProfiler>,
+        push_constants_buffers: Option<(&PushConstantsBuffers, &wgpu::Queue)>,
     ) -> Result<(), CoreError> {
         use Stage::*;
 
         match self {
-            Render(r_s) => {
+            Render(mut r_s) => {
+                let mut items = r_s.take_draw_items(index)?;
                 let RenderStage {
-                    pipeline,
-                    vertex_buffer,
-                    index_buffer,
-                    bind_groups,
-                    instances,
-                    model,
-                    entities,
-                    base_vertex,
                     color_attachments,
                     depth_stencil,
                     query_set,
@@ -52,71 +57,75 @@ impl<'a> Stage<'a> {
                     scissors,
                     blend_constant,
                     stencil_reference,
+                    bundles,
+                    sort_draws,
+                    ..
                 } = r_s;
 
-                let color_attachments = color_attachments
-                    .ok_or(CoreError::EmptyRenderPassColorAttachemnts(
+                if color_attachments.is_empty() {
+                    return Err(CoreError::EmptyRenderPassColorAttachemnts(
                         label.to_string(),
-                    ))?
-                    .build()?
-                    .into_render_pass();
+                    ));
+                }
+                let color_attachments = ColorAttachments::new(
+                    color_attachments
+                        .into_iter()
+                        .map(ColorAttachmentBuilder::build)
+                        .collect::<Result<_, _>>()?,
+                );
                 let depth_stencil_attachment = depth_stencil
                     .and_then(|d_s_b| d_s_b.build().ok())
                     .and_then(|d_s| d_s.into_render_pass());
-                let occlusion_query_set = query_set.as_deref();
-
-                /*
-                    let query_set_desc_label = format!("QuerySet Descriptior Label: {label}");
-                    let query_set_desc = wgpu::QuerySetDescriptor {
-                        label: Some(&query_set_desc_label),
-                        ty: wgpu::QueryType::Timestamp,
-                        count: wgpu::QUERY_SET_MAX_QUERIES - 1,
-                    };
-
-                    let timestamp_query_set = self.device.create_query_set(&query_set_desc);
-                    let timestamp_writes = Some(wgpu::RenderPassTimestampWrites {
-                        query_set: &timestamp_query_set,
-                        beginning_of_pass_write_index: Some(0),
-                        end_of_pass_write_index: None,
-                    });
+                let occlusion_query_set = query_set.map(|q_s| &**q_s);
 
-                    encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
-                        label: Some(label),
-                        color_attachments: &[color_attachments],
-                        timestamp_writes,
-                        occlusion_query_set,
-                        depth_stencil_attachment,
+                if items.is_empty() && bundles.is_empty() {
+                    return Err(CoreError::EmptyEntities(index));
+                }
+                // Stale bundles are skipped until they're recorded again by `replace_render_bundle`
+                let bundles = bundles
+                    .into_iter()
+                    .filter(|b| {
+                        if !b.is_valid() {
+                            warn!(
+                                "Render bundle `{}` is invalidated by replaced resources, skip it",
+                                b.label()
+                            );
+                        }
+
+                        b.is_valid()
                     })
-                */
-
-                let entities = entities.ok_or(CoreError::EmptyEntities(index))?;
-                let instances = instances.ok_or(CoreError::EmptyInstances(index))?;
-                let indexed = r_s.index_buffer.is_some();
+                    .collect::<Vec<_>>();
+                for item in &items {
+                    color_attachments.validate(item.pipeline())?;
+                }
+                if sort_draws {
+                    items.sort_by_key(DrawItem::sort_key);
+                }
+                let push_constants = push_constants_fallback(
+                    device,
+                    label,
+                    push_constants_buffers,
+                    items.iter().map(DrawItem::pipeline_push_constants),
+                )?;
+
+                // Queries are allocated after the checks, failed stages don't leave unwritten ones
+                let timestamp_writes = profiler
+                    .and_then(|p| p.allocate(format!("{label}: render stage {index}")))
+                    .map(|(query_set, begin, end)| wgpu::RenderPassTimestampWrites {
+                        query_set,
+                        beginning_of_pass_write_index: Some(begin),
+                        end_of_pass_write_index: Some(end),
+                    });
 
+                let color_attachments = color_attachments.into_render_pass();
                 let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
                     label: Some(label),
-                    color_attachments: &[color_attachments],
-                    timestamp_writes: None,
+                    color_attachments: &color_attachments,
+                    timestamp_writes,
                     occlusion_query_set,
                     depth_stencil_attachment,
                 });
 
-                if let Some(vb) = vertex_buffer.as_ref() {
-                    render_pass.set_vertex_buffer(vb.binding, vb.slice(..));
-                }
-                if let Some(ib) = index_buffer.as_ref() {
-                    render_pass.set_index_buffer(ib.slice(..), wgpu::IndexFormat::Uint16);
-                }
-
-                if let Some(b_gs) = bind_groups.as_ref() {
-                    b_gs.iter()
-                        .for_each(|bg| render_pass.set_bind_group(bg.binding, bg, &[]));
-                }
-                render_pass.set_pipeline(
-                    pipeline
-                        .render()
-                        .ok_or(CoreError::NotRenderPipeline(label.to_string()))?,
-                );
                 if let Some(v) = viewport.as_ref() {
                     let (x, y) = v.coords;
                     let (w, h) = v.size;
@@ -136,69 +145,107 @@ impl<'a> Stage<'a> {
                     render_pass.set_stencil_reference(*s_r);
                 }
 
+                let mut state = DrawState::new(query_set);
+                for (i, item) in items.iter().enumerate() {
+                    item.draw(
+                        index,
+                        label,
+                        &mut state,
+                        push_constants.get(i),
+                        &mut render_pass,
+                    )?;
+                }
+                // Bundles reset the state of the pass, so they are replayed after the draws
+                if !bundles.is_empty() {
+                    render_pass.execute_bundles(bundles.iter().map(|b| &***b));
+                }
+                drop(render_pass);
+
+                // Results are read by `Worker::read_occlusion_queries`
+                if let Some(q_s) = query_set.filter(|q_s| q_s.is_occlusion()) {
+                    q_s.resolve(encoder)?;
+                }
+
                 debug!(
                     "
 Process `render stage: {index}`
-    Pipeline: {pipeline:#?},
-    Model: {model:#?},
-    Vertex Buffer: {vertex_buffer:#?},
-    Index Buffer: {index_buffer:#?},
-    Bind Groups: {bind_groups:#?},
-    Entities: {entities:?},
-    Instances: {instances:?},
+    Draw Items: {items:#?},
+    Bundles: {},
     Viewport: {viewport:#?},
     Scissors: {scissors:#?},
     Stencil Reference: {stencil_reference:#?},
     Blend Constant: {blend_constant:#?},
-"
+    Draws: {},
+    State Changes: {},
+    Skipped State Changes: {},
+",
+                    bundles.len(),
+                    state.draws,
+                    state.state_changes,
+                    state.skipped_changes
                 );
-
-                if let Some(m) = model {
-                    let meshes = m.meshes();
-                    let materials = m.materials();
-
-                    for mesh in meshes {
-                        let material = &materials[mesh.material];
-                        let bg = material.bind_group();
-
-                        let v_b = mesh.vertex_buffer();
-                        let i_b = mesh.index_buffer();
-
-                        render_pass.set_vertex_buffer(v_b.binding, v_b.slice(..));
-                        render_pass.set_index_buffer(i_b.slice(..), wgpu::IndexFormat::Uint32);
-                        render_pass.set_bind_group(bg.binding, &bg, &[]);
-
-                        render_pass.draw_indexed(0..mesh.num_elements, 0, instances.clone());
-                    }
-                } else if indexed {
-                    let base_vertex = base_vertex.unwrap_or(0);
-                    render_pass.draw_indexed(entities, base_vertex, instances);
-                } else {
-                    render_pass.draw(entities, instances);
-                }
             }
             Compute(c_s) => {
                 let ComputeStage {
                     pipeline,
                     bind_groups,
+                    dynamic_offsets,
                     x_dimension,
                     y_dimension,
                     z_dimension,
+                    push_constants,
                 } = c_s;
 
+                let fallback = push_constants_fallback(
+                    device,
+                    label,
+                    push_constants_buffers,
+                    once((pipeline, push_constants.as_slice())),
+                )?;
+
+                let compute_pipeline = pipeline
+                    .compute()
+                    .ok_or(CoreError::NotComputePipeline(label.to_string()))?;
+                let push_constant_offsets = match pipeline.push_constants() {
+                    Some(p_c) if p_c.fallback_group().is_none() => push_constants
+                        .iter()
+                        .map(|v| p_c.offset(&pipeline.label, v))
+                        .collect::<Result<Vec<_>, _>>()?,
+                    _ => vec![],
+                };
+
+                let timestamp_writes = profiler
+                    .and_then(|p| p.allocate(format!("{label}: compute stage {index}")))
+                    .map(|(query_set, begin, end)| wgpu::ComputePassTimestampWrites {
+                        query_set,
+                        beginning_of_pass_write_index: Some(begin),
+                        end_of_pass_write_index: Some(end),
+                    });
+
                 let mut compute_pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
                     label: Some(label),
-                    timestamp_writes: None,
+                    timestamp_writes,
                 });
 
-                compute_pass.set_pipeline(
-                    pipeline
-                        .compute()
-                        .ok_or(CoreError::NotComputePipeline(label.to_string()))?,
-                );
+                compute_pass.set_pipeline(compute_pipeline);
                 if let Some(b_gs) = bind_groups.as_ref() {
-                    b_gs.iter()
-                        .for_each(|bg| compute_pass.set_bind_group(bg.binding, bg, &[]));
+                    b_gs.iter().for_each(|bg| {
+                        let offsets = dynamic_offsets
+                            .get(&bg.binding)
+                            .map(Vec::as_slice)
+                            .unwrap_or_default();
+                        compute_pass.set_bind_group(bg.binding, bg, offsets)
+                    });
+                }
+                if let Some(p_c) = pipeline.push_constants() {
+                    if let (Some(group), Some((bg, offset))) =
+                        (p_c.fallback_group(), fallback.get(0))
+                    {
+                        compute_pass.set_bind_group(group, bg, &[offset]);
+                    }
+                }
+                for (v, offset) in push_constants.iter().zip(push_constant_offsets) {
+                    compute_pass.set_push_constants(offset, &v.data);
                 }
                 debug!(
                     "
@@ -219,15 +266,33 @@ Process `compute stage: {index}`
     }
 }
 
+// Passes without the buffers of the worker allocate the fallback push constants by themselves
+fn push_constants_fallback<'v>(
+    device: &wgpu::Device,
+    label: &str,
+    buffers: Option<(&PushConstantsBuffers, &wgpu::Queue)>,
+    draws: impl Iterator<Item = (&'v Pipeline, &'v [PushConstantValue])>,
+) -> Result<PushConstantsFallback, CoreError> {
+    match buffers {
+        Some((buffers, queue)) => {
+            PushConstantsFallback::write(buffers, device, queue, label, draws)
+        }
+        None => PushConstantsFallback::new(device, label, draws),
+    }
+}
+
 #[derive(Debug)]
 pub struct ComputeStage<'a> {
     pipeline: &'a Pipeline,
 
     bind_groups: Option<Vec<&'a BindGroup>>,
+    dynamic_offsets: BTreeMap<u32, Vec<u32>>,
 
     x_dimension: u32,
     y_dimension: u32,
     z_dimension: u32,
+
+    push_constants: Vec<PushConstantValue>,
 }
 
 impl<'a> ComputeStage<'a> {
@@ -235,17 +300,35 @@ impl<'a> ComputeStage<'a> {
         Self {
             pipeline,
             bind_groups: None,
+            dynamic_offsets: BTreeMap::default(),
             x_dimension: 1,
             y_dimension: 1,
             z_dimension: 1,
+            push_constants: vec![],
         }
     }
 
+    pub fn push_constants<T: bytemuck::Pod>(
+        mut self,
+        stages: wgpu::ShaderStages,
+        value: &T,
+    ) -> Self {
+        self.push_constants
+            .push(PushConstantValue::new(stages, value));
+        self
+    }
+
     pub fn bind_groups(mut self, bind_groups: Vec<&'a BindGroup>) -> Self {
         self.bind_groups.get_or_insert(vec![]).extend(bind_groups);
         self
     }
 
+    // Offsets of the dynamic entries of the bind group, in the order of their bindings
+    pub fn dynamic_offsets(mut self, bind_group_binding: u32, offsets: Vec<u32>) -> Self {
+        self.dynamic_offsets.insert(bind_group_binding, offsets);
+        self
+    }
+
     pub fn x_dimension(mut self, x_dimension: u32) -> Self {
         self.x_dimension = x_dimension;
         self
@@ -264,7 +347,7 @@ impl<'a> ComputeStage<'a> {
 
 #[derive(Debug)]
 pub struct RenderStage<'a> {
-    pipeline: &'a Pipeline,
+    pipeline: Option<&'a Pipeline>,
 
     vertex_buffer: Option<&'a Buffer>,
     index_buffer: Option<&'a Buffer>,
@@ -275,20 +358,35 @@ pub struct RenderStage<'a> {
     base_vertex: Option<i32>,
     entities: Option<Range<u32>>,
 
-    color_attachments: Option<ColorAttachmentBuilder<'a>>,
+    color_attachments: Vec<ColorAttachmentBuilder<'a>>,
     depth_stencil: Option<DepthStencilAttachmentBuilder<'a>>,
-    query_set: Option<QuerySet>,
+    dynamic_offsets: BTreeMap<u32, Vec<u32>>,
+    query_set: Option<&'a QuerySet>,
+    occlusion_query: Option<u32>,
+    push_constants: Vec<PushConstantValue>,
 
     viewport: Option<ViewportRect>,
     scissors: Option<ScissorsRect>,
     blend_constant: Option<wgpu::Color>,
     stencil_reference: Option<u32>,
+
+    draws: Vec<DrawItem<'a>>,
+    bundles: Vec<&'a RenderBundle>,
+    sort_draws: bool,
 }
 
 impl<'a> RenderStage<'a> {
     pub fn new(pipeline: &'a Pipeline) -> Self {
         Self {
-            pipeline,
+            pipeline: Some(pipeline),
+            ..Self::batch()
+        }
+    }
+
+    // Stage without own pipeline, all draws are added by `draw`
+    pub fn batch() -> Self {
+        Self {
+            pipeline: None,
 
             model: None,
             bind_groups: None,
@@ -299,27 +397,85 @@ impl<'a> RenderStage<'a> {
             base_vertex: None,
             entities: None,
 
+            dynamic_offsets: BTreeMap::default(),
             query_set: None,
+            occlusion_query: None,
+            push_constants: vec![],
             depth_stencil: None,
-            color_attachments: None,
+            color_attachments: vec![],
 
             viewport: None,
             scissors: None,
             blend_constant: None,
             stencil_reference: None,
+
+            draws: vec![],
+            bundles: vec![],
+            sort_draws: false,
         }
     }
 
-    pub fn query_set(mut self, query_set: QuerySet) -> Self {
+    // Pre-recorded draws, which are replayed after the draws of the stage
+    pub fn bundle(mut self, bundle: &'a RenderBundle) -> Self {
+        self.bundles.push(bundle);
+        self
+    }
+
+    pub fn bundles(mut self, bundles: Vec<&'a RenderBundle>) -> Self {
+        self.bundles.extend(bundles);
+        self
+    }
+
+    pub fn draw(mut self, draw: DrawItem<'a>) -> Self {
+        self.draws.push(draw);
+        self
+    }
+
+    pub fn draws(mut self, draws: Vec<DrawItem<'a>>) -> Self {
+        self.draws.extend(draws);
+        self
+    }
+
+    // Draws are grouped by the pipeline and the material, it reorders the blended draws too
+    pub fn sort_draws(mut self, sort_draws: bool) -> Self {
+        self.sort_draws = sort_draws;
+        self
+    }
+
+    pub fn query_set(mut self, query_set: &'a QuerySet) -> Self {
         self.query_set = Some(query_set);
         self
     }
 
+    pub fn occlusion_query(mut self, query: u32) -> Self {
+        self.occlusion_query = Some(query);
+        self
+    }
+
+    pub fn push_constants<T: bytemuck::Pod>(
+        mut self,
+        stages: wgpu::ShaderStages,
+        value: &T,
+    ) -> Self {
+        self.push_constants
+            .push(PushConstantValue::new(stages, value));
+        self
+    }
+
+    // Attachments are bound in the order they are added, one per fragment target of the pipeline
     pub fn color_attachments_builder(
         mut self,
         color_attachments: ColorAttachmentBuilder<'a>,
     ) -> Self {
-        self.color_attachments = Some(color_attachments);
+        self.color_attachments.push(color_attachments);
+        self
+    }
+
+    pub fn color_attachments_builders(
+        mut self,
+        color_attachments: Vec<ColorAttachmentBuilder<'a>>,
+    ) -> Self {
+        self.color_attachments.extend(color_attachments);
         self
     }
 
@@ -371,6 +527,12 @@ impl<'a> RenderStage<'a> {
         self
     }
 
+    // Offsets of the dynamic entries of the bind group, in the order of their bindings
+    pub fn dynamic_offsets(mut self, bind_group_binding: u32, offsets: Vec<u32>) -> Self {
+        self.dynamic_offsets.insert(bind_group_binding, offsets);
+        self
+    }
+
     pub fn viewport(mut self, viewport: ViewportRect) -> Self {
         self.viewport = Some(viewport);
         self
@@ -390,6 +552,51 @@ impl<'a> RenderStage<'a> {
         self.stencil_reference = Some(index);
         self
     }
+
+    pub(crate) fn into_draw_items(mut self, index: usize) -> Result<Vec<DrawItem<'a>>, CoreError> {
+        self.take_draw_items(index)
+    }
+
+    // The stage pipeline with its resources is the first draw of the batch
+    fn take_draw_items(&mut self, index: usize) -> Result<Vec<DrawItem<'a>>, CoreError> {
+        let mut items = vec![];
+
+        if let Some(pipeline) = self.pipeline.take() {
+            let instances = self
+                .instances
+                .take()
+                .ok_or(CoreError::EmptyInstances(index))?;
+            let mut item = DrawItem::new(pipeline)
+                .instances(instances)
+                .base_vertex(self.base_vertex.take().unwrap_or(0))
+                .bind_groups(self.bind_groups.take().unwrap_or_default());
+
+            if let Some(e) = self.entities.take() {
+                item = item.entities(e);
+            }
+            if let Some(vb) = self.vertex_buffer.take() {
+                item = item.vertex_buffer(vb);
+            }
+            if let Some(ib) = self.index_buffer.take() {
+                item = item.index_buffer(ib);
+            }
+            if let Some(m) = self.model.take() {
+                item = item.model(m);
+            }
+            if let Some(q) = self.occlusion_query.take() {
+                item = item.occlusion_query(q);
+            }
+            item = item.push_constant_values(self.push_constants.drain(..));
+            for (binding, offsets) in mem::take(&mut self.dynamic_offsets) {
+                item = item.dynamic_offsets(binding, offsets);
+            }
+
+            items.push(item);
+        }
+        items.append(&mut self.draws);
+
+        Ok(items)
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -414,6 +621,8 @@ pub struct RenderPass<'a> {
     stages: BTreeMap<usize, Stage<'a>>,
 
     copy_params: Option<CopyTextureParams<'a>>,
+    profiler: Option<&'a GpuProfiler>,
+    push_constants_buffers: Option<(&'a PushConstantsBuffers, &'a wgpu::Queue)>,
 
     device: &'a wgpu::Device,
 }
@@ -425,6 +634,8 @@ impl<'a> RenderPass<'a> {
             device,
             label: None,
             copy_params: None,
+            profiler: None,
+            push_constants_buffers: None,
 
             stages: BTreeMap::default(),
         }
@@ -440,6 +651,20 @@ impl<'a> RenderPass<'a> {
         self
     }
 
+    pub(crate) fn profiler(mut self, profiler: Option<&'a GpuProfiler>) -> Self {
+        self.profiler = profiler;
+        self
+    }
+
+    pub(crate) fn push_constants_buffers(
+        mut self,
+        buffers: &'a PushConstantsBuffers,
+        queue: &'a wgpu::Queue,
+    ) -> Self {
+        self.push_constants_buffers = Some((buffers, queue));
+        self
+    }
+
     pub fn render_stage(self, index: usize, stage: RenderStage<'a>) -> Self {
         self.stage(index, Stage::Render(stage))
     }
@@ -451,15 +676,30 @@ impl<'a> RenderPass<'a> {
     pub fn render(self, queue: &'a wgpu::Queue) -> Result<(), CoreError> {
         let id = self.id;
         let render_pass_name = format!("Render pass: {id}");
-
         let label = self.label.unwrap_or(&render_pass_name);
-        let copy_params = self.copy_params;
 
         let mut encoder = self
             .device
             .create_command_encoder(&wgpu::CommandEncoderDescriptor {
                 label: Some(&format!("Command Encoder of `{label}`")),
             });
+        let push_constants_buffers = self.push_constants_buffers;
+        self.encode(&mut encoder)?;
+
+        queue.submit(once(encoder.finish()));
+        if let Some((buffers, _)) = push_constants_buffers {
+            buffers.reset();
+        }
+
+        Ok(())
+    }
+
+    pub fn encode(self, encoder: &mut wgpu::CommandEncoder) -> Result<(), CoreError> {
+        let id = self.id;
+        let render_pass_name = format!("Render pass: {id}");
+
+        let label = self.label.unwrap_or(&render_pass_name);
+        let copy_params = self.copy_params;
 
         debug!(
             "
@@ -469,15 +709,20 @@ Process `{label}`:
         );
 
         for (i, s) in self.stages {
-            s.process(i, label, &mut encoder)?;
+            s.process(
+                i,
+                label,
+                self.device,
+                encoder,
+                self.profiler,
+                self.push_constants_buffers,
+            )?;
         }
 
         if let Some(c_p) = copy_params {
-            c_p.process(&mut encoder);
+            c_p.process(encoder)?;
         }
 
-        queue.submit(once(encoder.finish()));
-
         Ok(())
     }
 
@@ -0,0 +1,231 @@
+use derive_more::Deref;
+use log::{debug, warn};
+
+use crate::{
+    context::RecordedResources,
+    errors::CoreError,
+    pipeline::push_constants::PushConstantsFallback,
+    render_pass::{
+        draw_item::{DrawItem, DrawState},
+        RenderStage,
+    },
+    traits::Builder,
+};
+
+#[derive(Debug, Deref)]
+pub struct RenderBundle {
+    pub id: usize,
+
+    label: String,
+    // Versions of the recorded resources
+    resources: RecordedResources,
+
+    #[deref]
+    inner_rb: wgpu::RenderBundle,
+}
+
+impl RenderBundle {
+    #[inline]
+    pub fn label(&self) -> &str {
+        &self.label
+    }
+
+    // The bundle can't be replayed, when any of the recorded resources is replaced or dropped
+    #[inline]
+    pub fn is_valid(&self) -> bool {
+        self.resources.is_current()
+    }
+}
+
+#[derive(Debug)]
+pub struct RenderBundleBuilder<'a> {
+    id: Option<usize>,
+    label: Option<&'a str>,
+    color_formats: Vec<Option<wgpu::TextureFormat>>,
+    depth_stencil: Option<wgpu::RenderBundleDepthStencil>,
+    sample_count: u32,
+
+    draws: Vec<DrawItem<'a>>,
+    stages: Vec<RenderStage<'a>>,
+    sort_draws: bool,
+
+    device: &'a wgpu::Device,
+}
+
+impl<'a> Builder<'a> for RenderBundleBuilder<'a> {
+    type Final = RenderBundle;
+
+    fn new(device: &'a wgpu::Device) -> Self
+    where
+        Self: Sized,
+    {
+        Self {
+            device,
+            id: None,
+            label: None,
+            color_formats: vec![],
+            depth_stencil: None,
+            sample_count: 1,
+            draws: vec![],
+            stages: vec![],
+            sort_draws: false,
+        }
+    }
+
+    fn new_indexed(device: &'a wgpu::Device, id: usize) -> Self
+    where
+        Self: Sized,
+    {
+        Self {
+            device,
+            id: Some(id),
+            label: None,
+            color_formats: vec![],
+            depth_stencil: None,
+            sample_count: 1,
+            draws: vec![],
+            stages: vec![],
+            sort_draws: false,
+        }
+    }
+
+    fn build(self) -> Result<Self::Final, CoreError>
+    where
+        Self: Sized,
+    {
+        let id = self.id.unwrap_or_default();
+        let render_bundle_name = format!("Render bundle: {id}");
+
+        let label = self.label.unwrap_or(&render_bundle_name);
+        let color_formats = self.color_formats;
+        let depth_stencil = self.depth_stencil;
+        let sample_count = self.sample_count;
+
+        if color_formats.is_empty() {
+            return Err(CoreError::EmptyRenderBundleFormats(label.to_string()));
+        }
+
+        let mut items = self.draws;
+        for (i, stage) in self.stages.into_iter().enumerate() {
+            items.extend(stage.into_draw_items(i)?);
+        }
+        if items.is_empty() {
+            return Err(CoreError::EmptyRenderBundleDraws(label.to_string()));
+        }
+        if self.sort_draws {
+            items.sort_by_key(DrawItem::sort_key);
+        }
+
+        let push_constants = PushConstantsFallback::new(
+            self.device,
+            label,
+            items.iter().map(DrawItem::pipeline_push_constants),
+        )?;
+
+        let mut encoder =
+            self.device
+                .create_render_bundle_encoder(&wgpu::RenderBundleEncoderDescriptor {
+                    label: Some(label),
+                    color_formats: &color_formats,
+                    depth_stencil,
+                    sample_count,
+                    multiview: None,
+                });
+
+        let mut state = DrawState::new(None);
+        let mut resources = RecordedResources::default();
+        for (i, item) in items.iter().enumerate() {
+            if item.has_occlusion_query() {
+                warn!("Occlusion queries are not recorded into `{label}`, draw: {i}");
+            }
+
+            item.record(i, label, &mut state, push_constants.get(i), &mut encoder)?;
+            item.record_resources(&mut resources);
+        }
+        resources.dedup();
+
+        let inner_rb = encoder.finish(&wgpu::RenderBundleDescriptor { label: Some(label) });
+
+        debug!(
+            "
+Build `{label}`:
+    Color formats: {color_formats:?},
+    Depth stencil: {depth_stencil:?},
+    Sample count: {sample_count},
+    Draws: {},
+    Resources: {},
+    State Changes: {},
+    Skipped State Changes: {},",
+            state.draws,
+            resources.len(),
+            state.state_changes,
+            state.skipped_changes
+        );
+
+        Ok(RenderBundle {
+            id,
+            label: label.to_string(),
+            resources,
+            inner_rb,
+        })
+    }
+}
+
+impl<'a> RenderBundleBuilder<'a> {
+    pub fn label(mut self, label: &'a str) -> Self {
+        self.label = Some(label);
+        self
+    }
+
+    // Formats must match the color attachments of the render stage, which replays the bundle
+    pub fn color_format(mut self, format: wgpu::TextureFormat) -> Self {
+        self.color_formats.push(Some(format));
+        self
+    }
+
+    pub fn color_formats(mut self, formats: Vec<Option<wgpu::TextureFormat>>) -> Self {
+        self.color_formats.extend(formats);
+        self
+    }
+
+    pub fn depth_format(mut self, format: wgpu::TextureFormat) -> Self {
+        self.depth_stencil = Some(wgpu::RenderBundleDepthStencil {
+            format,
+            depth_read_only: false,
+            stencil_read_only: false,
+        });
+        self
+    }
+
+    pub fn depth_stencil(mut self, depth_stencil: wgpu::RenderBundleDepthStencil) -> Self {
+        self.depth_stencil = Some(depth_stencil);
+        self
+    }
+
+    pub fn sample_count(mut self, sample_count: u32) -> Self {
+        self.sample_count = sample_count;
+        self
+    }
+
+    pub fn draw(mut self, draw: DrawItem<'a>) -> Self {
+        self.draws.push(draw);
+        self
+    }
+
+    pub fn draws(mut self, draws: Vec<DrawItem<'a>>) -> Self {
+        self.draws.extend(draws);
+        self
+    }
+
+    // Only the draw content of the stage is recorded, attachments are set by the replaying stage
+    pub fn render_stage(mut self, stage: RenderStage<'a>) -> Self {
+        self.stages.push(stage);
+        self
+    }
+
+    // Draws of the bundle and its stages are grouped by the pipeline and the material
+    pub fn sort_draws(mut self, sort_draws: bool) -> Self {
+        self.sort_draws = sort_draws;
+        self
+    }
+}
@@ -1,12 +1,43 @@
 use derive_more::{Deref, DerefMut};
 use log::debug;
 
-use crate::errors::CoreError;
+use crate::{errors::CoreError, pipeline::Pipeline};
 
 #[derive(Debug, Deref, DerefMut)]
 pub struct ColorAttachments<'a>(Vec<ColorAttachment<'a>>);
 
 impl<'a> ColorAttachments<'a> {
+    pub fn new(color_attachments: Vec<ColorAttachment<'a>>) -> Self {
+        Self(color_attachments)
+    }
+
+    // Attachments go in the order of the fragment targets, formats are checked when they are set
+    pub fn validate(&self, pipeline: &Pipeline) -> Result<(), CoreError> {
+        let targets = &pipeline.color_targets;
+        if targets.len() != self.0.len() {
+            return Err(CoreError::ColorAttachmentsCount(
+                pipeline.label.clone(),
+                targets.len(),
+                self.0.len(),
+            ));
+        }
+
+        for (i, (target, attachment)) in targets.iter().zip(self.0.iter()).enumerate() {
+            if let (Some(target), Some(format)) = (target, attachment.format) {
+                if target.format != format {
+                    return Err(CoreError::ColorAttachmentFormat(
+                        pipeline.label.clone(),
+                        i,
+                        target.format,
+                        format,
+                    ));
+                }
+            }
+        }
+
+        Ok(())
+    }
+
     pub fn into_render_pass(self) -> Vec<Option<wgpu::RenderPassColorAttachment<'a>>> {
         self.0
             .into_iter()
@@ -18,6 +49,7 @@ impl<'a> ColorAttachments<'a> {
 #[derive(Debug, Deref, DerefMut)]
 pub struct ColorAttachment<'a> {
     pub id: usize,
+    pub format: Option<wgpu::TextureFormat>,
 
     #[deref]
     #[deref_mut]
@@ -35,6 +67,8 @@ pub struct ColorAttachmentBuilder<'a> {
     id: Option<usize>,
     label: Option<&'a str>,
     view: Option<&'a wgpu::TextureView>,
+    resolve_target: Option<&'a wgpu::TextureView>,
+    format: Option<wgpu::TextureFormat>,
     ops: Option<wgpu::Operations<wgpu::Color>>,
 }
 
@@ -45,6 +79,8 @@ impl<'a> ColorAttachmentBuilder<'a> {
     {
         Self {
             view: None,
+            resolve_target: None,
+            format: None,
             id: None,
             label: None,
             ops: None,
@@ -57,6 +93,8 @@ impl<'a> ColorAttachmentBuilder<'a> {
     {
         Self {
             view: None,
+            resolve_target: None,
+            format: None,
             id: Some(id),
             label: None,
             ops: None,
@@ -74,6 +112,8 @@ impl<'a> ColorAttachmentBuilder<'a> {
         let view = self
             .view
             .ok_or(CoreError::EmptyTextureView(label.to_string()))?;
+        let resolve_target = self.resolve_target;
+        let format = self.format;
         let ops = self.ops.unwrap_or(wgpu::Operations {
             load: wgpu::LoadOp::Load,
             store: wgpu::StoreOp::Store,
@@ -83,16 +123,22 @@ impl<'a> ColorAttachmentBuilder<'a> {
             "
 Build `{label}`:
     View: {view:#?},
+    Resolve target: {resolve_target:#?},
+    Format: {format:#?},
     Ops: {ops:#?},"
         );
 
         let inner_ca = wgpu::RenderPassColorAttachment {
             view,
             ops,
-            resolve_target: None,
+            resolve_target,
         };
 
-        Ok(ColorAttachment { id, inner_ca })
+        Ok(ColorAttachment {
+            id,
+            format,
+            inner_ca,
+        })
     }
 }
 
@@ -107,6 +153,18 @@ impl<'a> ColorAttachmentBuilder<'a> {
         self
     }
 
+    // Multisampled `view` is resolved into the target at the end of the pass
+    pub fn resolve_target(mut self, resolve_target: &'a wgpu::TextureView) -> Self {
+        self.resolve_target = Some(resolve_target);
+        self
+    }
+
+    // Format of the view, which is validated against the fragment target of the pipeline
+    pub fn format(mut self, format: wgpu::TextureFormat) -> Self {
+        self.format = Some(format);
+        self
+    }
+
     pub fn ops(mut self, ops: wgpu::Operations<wgpu::Color>) -> Self {
         self.ops = Some(ops);
         self
@@ -0,0 +1,413 @@
+use std::{collections::BTreeMap, ops::Range, ptr};
+
+use wgpu::util::RenderEncoder;
+
+use crate::{
+    bind_group::BindGroup,
+    buffer::Buffer,
+    context::RecordedResources,
+    errors::CoreError,
+    model::Model,
+    pipeline::{push_constants::PushConstantValue, Pipeline},
+    render_pass::query_set::QuerySet,
+};
+
+#[derive(Debug)]
+pub struct DrawItem<'a> {
+    label: Option<&'a str>,
+    pipeline: &'a Pipeline,
+
+    vertex_buffers: Vec<&'a Buffer>,
+    index_buffer: Option<&'a Buffer>,
+    index_format: wgpu::IndexFormat,
+    bind_groups: Vec<&'a BindGroup>,
+    dynamic_offsets: BTreeMap<u32, Vec<u32>>,
+    model: Option<&'a Model>,
+
+    instances: Range<u32>,
+    base_vertex: i32,
+    entities: Option<Range<u32>>,
+
+    material: usize,
+    occlusion_query: Option<u32>,
+    push_constants: Vec<PushConstantValue>,
+}
+
+impl<'a> DrawItem<'a> {
+    pub fn new(pipeline: &'a Pipeline) -> Self {
+        Self {
+            label: None,
+            pipeline,
+
+            vertex_buffers: vec![],
+            index_buffer: None,
+            index_format: wgpu::IndexFormat::Uint16,
+            bind_groups: vec![],
+            dynamic_offsets: BTreeMap::default(),
+            model: None,
+
+            instances: 0..1,
+            base_vertex: 0,
+            entities: None,
+
+            material: 0,
+            occlusion_query: None,
+            push_constants: vec![],
+        }
+    }
+
+    pub fn label(mut self, label: &'a str) -> Self {
+        self.label = Some(label);
+        self
+    }
+
+    pub fn vertex_buffer(mut self, vertex_buffer: &'a Buffer) -> Self {
+        self.vertex_buffers.push(vertex_buffer);
+        self
+    }
+
+    pub fn index_buffer(mut self, index_buffer: &'a Buffer) -> Self {
+        self.index_buffer = Some(index_buffer);
+        self
+    }
+
+    pub fn index_format(mut self, index_format: wgpu::IndexFormat) -> Self {
+        self.index_format = index_format;
+        self
+    }
+
+    pub fn bind_group(mut self, bind_group: &'a BindGroup) -> Self {
+        self.bind_groups.push(bind_group);
+        self
+    }
+
+    pub fn bind_groups(mut self, bind_groups: Vec<&'a BindGroup>) -> Self {
+        self.bind_groups.extend(bind_groups);
+        self
+    }
+
+    // Offsets of the dynamic entries of the bind group, in the order of their bindings
+    pub fn dynamic_offsets(mut self, bind_group_binding: u32, offsets: Vec<u32>) -> Self {
+        self.dynamic_offsets.insert(bind_group_binding, offsets);
+        self
+    }
+
+    pub fn model(mut self, model: &'a Model) -> Self {
+        self.model = Some(model);
+        self
+    }
+
+    pub fn instances(mut self, instances: Range<u32>) -> Self {
+        self.instances = instances;
+        self
+    }
+
+    pub fn entities(mut self, entities: Range<u32>) -> Self {
+        self.entities = Some(entities);
+        self
+    }
+
+    pub fn base_vertex(mut self, base_vertex: i32) -> Self {
+        self.base_vertex = base_vertex;
+        self
+    }
+
+    // Draws with the same pipeline are sorted by the material key
+    pub fn material(mut self, material: usize) -> Self {
+        self.material = material;
+        self
+    }
+
+    // Index of the query in the occlusion query set of the stage
+    pub fn occlusion_query(mut self, query: u32) -> Self {
+        self.occlusion_query = Some(query);
+        self
+    }
+
+    // The range of the pipeline layout is found by `stages`
+    pub fn push_constants<T: bytemuck::Pod>(
+        mut self,
+        stages: wgpu::ShaderStages,
+        value: &T,
+    ) -> Self {
+        self.push_constants
+            .push(PushConstantValue::new(stages, value));
+        self
+    }
+
+    pub(crate) fn push_constant_values(
+        mut self,
+        values: impl IntoIterator<Item = PushConstantValue>,
+    ) -> Self {
+        self.push_constants.extend(values);
+        self
+    }
+
+    pub(crate) fn pipeline_push_constants(&self) -> (&Pipeline, &[PushConstantValue]) {
+        (self.pipeline, &self.push_constants)
+    }
+
+    pub fn pipeline(&self) -> &Pipeline {
+        self.pipeline
+    }
+
+    // Handle indices are stable between frames unlike addresses, equal keys keep the order
+    pub(crate) fn sort_key(&self) -> (usize, usize) {
+        (self.pipeline.id, self.material)
+    }
+
+    pub(crate) fn has_occlusion_query(&self) -> bool {
+        self.occlusion_query.is_some()
+    }
+
+    // Versions of the referenced resources, render bundles are invalidated by them
+    pub(crate) fn record_resources(&self, resources: &mut RecordedResources) {
+        resources.record(self.pipeline);
+        self.vertex_buffers
+            .iter()
+            .for_each(|b| resources.record(*b));
+        if let Some(b) = self.index_buffer {
+            resources.record(b);
+        }
+        self.bind_groups.iter().for_each(|b| resources.record(*b));
+        if let Some(m) = self.model {
+            resources.record(m);
+        }
+    }
+
+    pub(crate) fn draw<'p>(
+        &self,
+        index: usize,
+        label: &str,
+        state: &mut DrawState<'a>,
+        push_constants: Option<(&'p wgpu::BindGroup, u32)>,
+        render_pass: &mut wgpu::RenderPass<'p>,
+    ) -> Result<(), CoreError>
+    where
+        'a: 'p,
+    {
+        let Some(query) = self.occlusion_query else {
+            return self.record(index, label, state, push_constants, render_pass);
+        };
+
+        let query_set = state
+            .occlusion_query_set
+            .ok_or(CoreError::EmptyOcclusionQuerySet(index))?;
+        query_set.issue(
+            query,
+            self.label
+                .map(str::to_string)
+                .unwrap_or_else(|| format!("{label}: render stage {index}, query {query}")),
+        )?;
+
+        render_pass.begin_occlusion_query(query);
+        let result = self.record(index, label, state, push_constants, render_pass);
+        render_pass.end_occlusion_query();
+
+        result
+    }
+
+    // Records the draw into a render pass or a render bundle
+    pub(crate) fn record<'p, E: RenderEncoder<'p>>(
+        &self,
+        index: usize,
+        label: &str,
+        state: &mut DrawState<'a>,
+        push_constants: Option<(&'p wgpu::BindGroup, u32)>,
+        render_pass: &mut E,
+    ) -> Result<(), CoreError>
+    where
+        'a: 'p,
+    {
+        let pipeline = self
+            .pipeline
+            .render()
+            .ok_or(CoreError::NotRenderPipeline(label.to_string()))?;
+        state.set_pipeline(render_pass, pipeline);
+
+        for vb in &self.vertex_buffers {
+            state.set_vertex_buffer(render_pass, vb);
+        }
+        for bg in &self.bind_groups {
+            let offsets = self
+                .dynamic_offsets
+                .get(&bg.binding)
+                .map(Vec::as_slice)
+                .unwrap_or_default();
+            state.set_bind_group(render_pass, bg, offsets);
+        }
+        if let Some(p_c) = self.pipeline.push_constants() {
+            match (p_c.fallback_group(), push_constants) {
+                (Some(group), Some((bg, offset))) => {
+                    state.set_fallback_bind_group(render_pass, group, bg, offset)
+                }
+                (None, _) => {
+                    for v in &self.push_constants {
+                        let offset = p_c.offset(&self.pipeline.label, v)?;
+                        render_pass.set_push_constants(v.stages, offset, &v.data);
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        if let Some(m) = self.model {
+            let (materials, meshes) = (m.materials(), m.meshes());
+
+            // Every node draws its meshes with the own transform
+            let mut draws = m
+                .nodes()
+                .iter()
+                .flat_map(|n| n.meshes.iter().zip(n.bind_groups()))
+                .map(|(mesh, bg)| (&meshes[*mesh], bg))
+                .collect::<Vec<_>>();
+            draws.sort_by_key(|(mesh, _)| mesh.material);
+
+            for (mesh, node_bg) in draws {
+                let material = materials
+                    .get(mesh.material)
+                    .ok_or(CoreError::MaterialNotFound(m.id, mesh.material))?;
+
+                state.set_vertex_buffer(render_pass, mesh.vertex_buffer());
+                state.set_index_buffer(render_pass, mesh.index_buffer(), wgpu::IndexFormat::Uint32);
+                state.set_bind_group(render_pass, material.bind_group(), &[]);
+                state.set_bind_group(render_pass, node_bg, &[]);
+
+                render_pass.draw_indexed(0..mesh.num_elements, 0, self.instances.clone());
+                state.draws += 1;
+            }
+
+            return Ok(());
+        }
+
+        let entities = self
+            .entities
+            .clone()
+            .ok_or(CoreError::EmptyEntities(index))?;
+
+        if let Some(ib) = self.index_buffer {
+            state.set_index_buffer(render_pass, ib, self.index_format);
+            render_pass.draw_indexed(entities, self.base_vertex, self.instances.clone());
+        } else {
+            render_pass.draw(entities, self.instances.clone());
+        }
+        state.draws += 1;
+
+        Ok(())
+    }
+}
+
+// Tracks the bound state of a render pass to skip redundant calls
+#[derive(Debug, Default)]
+pub(crate) struct DrawState<'a> {
+    occlusion_query_set: Option<&'a QuerySet>,
+
+    pipeline: Option<&'a wgpu::RenderPipeline>,
+    vertex_buffers: BTreeMap<u32, &'a Buffer>,
+    index_buffer: Option<(&'a Buffer, wgpu::IndexFormat)>,
+    bind_groups: BTreeMap<u32, (&'a BindGroup, Vec<u32>)>,
+
+    pub draws: usize,
+    pub state_changes: usize,
+    pub skipped_changes: usize,
+}
+
+impl<'a> DrawState<'a> {
+    pub fn new(occlusion_query_set: Option<&'a QuerySet>) -> Self {
+        Self {
+            occlusion_query_set,
+            ..Default::default()
+        }
+    }
+
+    fn set_pipeline<'p, E: RenderEncoder<'p>>(
+        &mut self,
+        render_pass: &mut E,
+        pipeline: &'a wgpu::RenderPipeline,
+    ) where
+        'a: 'p,
+    {
+        if self.pipeline.is_some_and(|p| ptr::eq(p, pipeline)) {
+            self.skipped_changes += 1;
+            return;
+        }
+
+        render_pass.set_pipeline(pipeline);
+        self.pipeline = Some(pipeline);
+        self.state_changes += 1;
+    }
+
+    fn set_vertex_buffer<'p, E: RenderEncoder<'p>>(&mut self, render_pass: &mut E, vb: &'a Buffer)
+    where
+        'a: 'p,
+    {
+        if self
+            .vertex_buffers
+            .get(&vb.binding)
+            .is_some_and(|b| ptr::eq(*b, vb))
+        {
+            self.skipped_changes += 1;
+            return;
+        }
+
+        render_pass.set_vertex_buffer(vb.binding, vb.slice(..));
+        self.vertex_buffers.insert(vb.binding, vb);
+        self.state_changes += 1;
+    }
+
+    fn set_index_buffer<'p, E: RenderEncoder<'p>>(
+        &mut self,
+        render_pass: &mut E,
+        ib: &'a Buffer,
+        format: wgpu::IndexFormat,
+    ) where
+        'a: 'p,
+    {
+        if self
+            .index_buffer
+            .is_some_and(|(b, f)| ptr::eq(b, ib) && f == format)
+        {
+            self.skipped_changes += 1;
+            return;
+        }
+
+        render_pass.set_index_buffer(ib.slice(..), format);
+        self.index_buffer = Some((ib, format));
+        self.state_changes += 1;
+    }
+
+    // Fallback push constants are bound for every draw, the group isn't tracked
+    fn set_fallback_bind_group<'p, E: RenderEncoder<'p>>(
+        &mut self,
+        render_pass: &mut E,
+        group: u32,
+        bind_group: &'p wgpu::BindGroup,
+        offset: u32,
+    ) {
+        render_pass.set_bind_group(group, bind_group, &[offset]);
+        self.bind_groups.remove(&group);
+        self.state_changes += 1;
+    }
+
+    fn set_bind_group<'p, E: RenderEncoder<'p>>(
+        &mut self,
+        render_pass: &mut E,
+        bg: &'a BindGroup,
+        offsets: &[u32],
+    ) where
+        'a: 'p,
+    {
+        if self
+            .bind_groups
+            .get(&bg.binding)
+            .is_some_and(|(b, o)| ptr::eq(*b, bg) && o == offsets)
+        {
+            self.skipped_changes += 1;
+            return;
+        }
+
+        render_pass.set_bind_group(bg.binding, bg, offsets);
+        self.bind_groups.insert(bg.binding, (bg, offsets.to_vec()));
+        self.state_changes += 1;
+    }
+}
@@ -1,17 +1,100 @@
+use std::{cell::RefCell, collections::BTreeMap, mem::size_of};
+
 use derive_more::{Deref, DerefMut};
 use log::debug;
 
-use crate::{errors::CoreError, traits::Builder};
+use crate::{
+    buffer::{Buffer, BufferBuilder},
+    errors::CoreError,
+    traits::Builder,
+};
+
+#[derive(Debug, Clone)]
+pub struct OcclusionQuery {
+    pub index: u32,
+    pub draw: Option<String>,
+    pub samples: u64,
+}
+
+impl OcclusionQuery {
+    #[inline]
+    pub fn is_visible(&self) -> bool {
+        self.samples > 0
+    }
+}
 
 #[derive(Debug, Deref, DerefMut)]
 pub struct QuerySet {
     pub id: usize,
+    pub count: u32,
+    pub query_type: wgpu::QueryType,
+
+    label: String,
+    // Only occlusion queries are read back, timestamps are handled by the profiler
+    resolve_buffer: Option<Buffer>,
+    read_buffer: Option<Buffer>,
+    // Draws which issued the queries since the last read
+    issued: RefCell<BTreeMap<u32, String>>,
 
     #[deref]
     #[deref_mut]
     inner_qs: wgpu::QuerySet,
 }
 
+impl QuerySet {
+    #[inline]
+    pub fn is_occlusion(&self) -> bool {
+        matches!(self.query_type, wgpu::QueryType::Occlusion)
+    }
+
+    pub(crate) fn issue(&self, query: u32, draw: String) -> Result<(), CoreError> {
+        if query >= self.count {
+            return Err(CoreError::QueryOutOfRange(self.label.clone(), query));
+        }
+
+        self.issued.borrow_mut().insert(query, draw);
+
+        Ok(())
+    }
+
+    pub(crate) fn resolve(&self, encoder: &mut wgpu::CommandEncoder) -> Result<(), CoreError> {
+        let (Some(resolve_buffer), Some(read_buffer)) =
+            (self.resolve_buffer.as_ref(), self.read_buffer.as_ref())
+        else {
+            return Err(CoreError::NotOcclusionQuerySet(self.label.clone()));
+        };
+
+        encoder.resolve_query_set(&self.inner_qs, 0..self.count, resolve_buffer, 0);
+        encoder.copy_buffer_to_buffer(resolve_buffer, 0, read_buffer, 0, read_buffer.size());
+
+        Ok(())
+    }
+
+    pub(crate) async fn read_async(
+        &self,
+        device: &wgpu::Device,
+    ) -> Result<Vec<OcclusionQuery>, CoreError> {
+        let read_buffer = self
+            .read_buffer
+            .as_ref()
+            .ok_or(CoreError::NotOcclusionQuerySet(self.label.clone()))?;
+
+        let data = read_buffer.read_buffer_async(device).await?;
+        let samples: &[u64] = bytemuck::cast_slice(&data);
+        let mut issued = self.issued.take();
+
+        Ok(samples
+            .iter()
+            .enumerate()
+            .map(|(i, s)| OcclusionQuery {
+                index: i as u32,
+                draw: issued.remove(&(i as u32)),
+                samples: *s,
+            })
+            .collect())
+    }
+}
+
 #[derive(Debug)]
 pub struct QuerySetBuilder<'a> {
     id: Option<usize>,
@@ -56,7 +139,7 @@ impl<'a> Builder<'a> for QuerySetBuilder<'a> {
         Self: Sized,
     {
         let id = self.id.unwrap_or_default();
-        let query_set_name = format!("Depth stencil attachment: {id}");
+        let query_set_name = format!("Query set: {id}");
 
         let label = self.label.unwrap_or(&query_set_name);
         let ty = self
@@ -77,7 +160,35 @@ Build `{label}`:
             ty,
         });
 
-        Ok(QuerySet { id, inner_qs })
+        let (resolve_buffer, read_buffer) = if matches!(ty, wgpu::QueryType::Occlusion) {
+            let size = count as u64 * size_of::<u64>() as u64;
+
+            let resolve_buffer = BufferBuilder::new_indexed(self.device, id)
+                .label(&format!("Resolve buffer of `{label}`"))
+                .usage(wgpu::BufferUsages::QUERY_RESOLVE | wgpu::BufferUsages::COPY_SRC)
+                .size(size)
+                .build()?;
+            let read_buffer = BufferBuilder::new_indexed(self.device, id)
+                .label(&format!("Read buffer of `{label}`"))
+                .usage(wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ)
+                .size(size)
+                .build()?;
+
+            (Some(resolve_buffer), Some(read_buffer))
+        } else {
+            (None, None)
+        };
+
+        Ok(QuerySet {
+            id,
+            count,
+            query_type: ty,
+            label: label.to_string(),
+            resolve_buffer,
+            read_buffer,
+            issued: Default::default(),
+            inner_qs,
+        })
     }
 }
 
@@ -1,8 +1,15 @@
-use std::time::Duration;
+mod adapter;
+mod headless;
+mod options;
+
+pub use adapter::*;
+pub use headless::*;
+pub use options::*;
+
+use std::{sync::Arc, time::Duration};
 
 use derive_more::Display;
-use log::{debug, error};
-use pollster::block_on;
+use log::{error, warn};
 use winit::{
     application::ApplicationHandler,
     dpi::PhysicalSize,
@@ -34,10 +41,10 @@ pub(crate) struct SurfaceProperties<'a> {
 
 pub struct Runtime<'a, R: RenderWorker + 'a, H: EventHandler<R>> {
     pub(crate) size: (u32, u32),
-    pub(crate) limits: wgpu::Limits,
     pub(crate) instance: wgpu::Instance,
-    pub(crate) power_preference: wgpu::PowerPreference,
+    pub(crate) options: RuntimeOptions,
 
+    window: Option<Arc<Window>>,
     worker: Option<Worker<'a>>,
     render: R,
     handler: H,
@@ -58,21 +65,24 @@ impl<'a, E: OnEvent + 'static, R: RenderWorker + 'a, H: EventHandler<R>> Applica
     ) {
         let w = self.worker.as_mut().unwrap();
 
-        match self
-            .render
-            .update(w, &event, Duration::from_secs(1))
-            .and(self.render.render(w))
-        {
-            Err(CoreError::SurfaceError(wgpu::SurfaceError::Lost)) => w.resize(),
-            Err(CoreError::SurfaceError(wgpu::SurfaceError::Timeout)) => w.resize(),
-            Err(CoreError::SurfaceError(wgpu::SurfaceError::OutOfMemory)) => {
-                event_loop.exit();
-            }
-            Err(e) => error!("{e}"),
-            _ => {}
+        if let Err(e) = self.render.input(w, &event) {
+            error!("{e}");
         }
 
         match event {
+            WindowEvent::RedrawRequested => {
+                let dt = w.clock_mut().tick();
+
+                match run_frame(&mut self.render, w, dt) {
+                    Err(CoreError::SurfaceError(wgpu::SurfaceError::Lost)) => w.resize(),
+                    Err(CoreError::SurfaceError(wgpu::SurfaceError::Timeout)) => w.resize(),
+                    Err(CoreError::SurfaceError(wgpu::SurfaceError::OutOfMemory)) => {
+                        event_loop.exit();
+                    }
+                    Err(e) => error!("{e}"),
+                    _ => {}
+                }
+            }
             WindowEvent::Resized(PhysicalSize { width, height }) => {
                 w.resize_by_size((width, height));
 
@@ -287,18 +297,27 @@ impl<'a, E: OnEvent + 'static, R: RenderWorker + 'a, H: EventHandler<R>> Applica
                 }
             }
 
-            WindowEvent::ActivationTokenDone { .. } | WindowEvent::RedrawRequested => (),
+            WindowEvent::ActivationTokenDone { .. } => (),
+        }
+    }
+
+    fn about_to_wait(&mut self, _event_loop: &ActiveEventLoop) {
+        if let Some(window) = self.window.as_ref() {
+            window.request_redraw();
         }
     }
 
     fn resumed(&mut self, event_loop: &ActiveEventLoop) {
         // Panic if window is not init
-        let w = event_loop
-            .create_window(
-                Window::default_attributes()
-                    .with_inner_size(PhysicalSize::new(self.size.0, self.size.1)),
-            )
-            .unwrap();
+        let w = Arc::new(
+            event_loop
+                .create_window(
+                    Window::default_attributes()
+                        .with_inner_size(PhysicalSize::new(self.size.0, self.size.1)),
+                )
+                .unwrap(),
+        );
+        self.window = Some(w.clone());
 
         if let Err(e) = self.worker_init(w) {
             error!("{e}");
@@ -315,34 +334,31 @@ impl<'a, E: OnEvent + 'static, R: RenderWorker + 'a, H: EventHandler<R>> Applica
 
 impl<'a, R: RenderWorker + 'a, H: EventHandler<R>> Runtime<'a, R, H> {
     pub fn new(size: (u32, u32)) -> Self {
+        Self::with_options(size, RuntimeOptions::default())
+    }
+
+    pub fn with_options(size: (u32, u32), options: RuntimeOptions) -> Self {
         let instance = wgpu::Instance::new(wgpu::InstanceDescriptor {
-            backends: wgpu::Backends::all(),
+            backends: options.backends,
             ..Default::default()
         });
-        let power_preference = wgpu::PowerPreference::default();
-        let limits = if cfg!(target_arch = "wasm32") {
-            wgpu::Limits::downlevel_webgl2_defaults()
-        } else {
-            wgpu::Limits::default()
-        };
 
         Self {
             instance,
-            power_preference,
-            limits,
+            options,
             size,
             render: R::new(),
             handler: H::default(),
+            window: None,
             worker: None,
         }
     }
 
     // Create only in winit context
-    fn worker_init(&mut self, window: Window) -> Result<(), CoreError> {
+    fn worker_init(&mut self, window: Arc<Window>) -> Result<(), CoreError> {
         let Self {
-            limits,
+            options,
             instance,
-            power_preference,
             worker,
             ..
         } = self;
@@ -366,79 +382,62 @@ impl<'a, R: RenderWorker + 'a, H: EventHandler<R>> Runtime<'a, R, H> {
             }
         }
 
+        let i_s = window.inner_size();
+        let surface = instance.create_surface(window)?;
+        let adapter = request_adapter(instance, options, Some(&surface))?;
+        let (device, queue, capabilities) = request_device(&adapter, options, Some(&surface))?;
+
         let size = if cfg!(target_arch = "wasm32") {
             (
-                limits.max_texture_dimension_2d,
-                limits.max_texture_dimension_2d,
+                capabilities.limits.max_texture_dimension_2d,
+                capabilities.limits.max_texture_dimension_2d,
             )
+        } else if i_s.height == 0 || i_s.width == 0 {
+            self.size
         } else {
-            let i_s = window.inner_size();
-
-            if i_s.height == 0 || i_s.width == 0 {
-                self.size
-            } else {
-                (i_s.width, i_s.height)
-            }
+            (i_s.width, i_s.height)
         };
 
-        let surface = instance.create_surface(window)?;
-        let adapter = block_on(instance.request_adapter(&wgpu::RequestAdapterOptions {
-            power_preference: *power_preference,
-            compatible_surface: Some(&surface),
-            force_fallback_adapter: false,
-        }))
-        .ok_or(CoreError::RequestAdapter)?;
-        let adapter_info = adapter.get_info();
-        let adapter_features = adapter.features();
-
-        debug!(
-            "
-Adapter: 
-    Info: {adapter_info:#?},
-    Features: {adapter_features:#?},
-    Limits: {limits:#?}"
-        );
-
-        let (device, queue) = block_on(adapter.request_device(
-            &wgpu::DeviceDescriptor {
-                required_features: adapter_features,
-                required_limits: limits.clone(),
-                label: None,
-            },
-            None,
-        ))?;
-
-        let surface_caps = surface.get_capabilities(&adapter);
-        let surface_format = surface_caps
-            .formats
-            .iter()
-            .find(|f| f.is_srgb())
-            .cloned()
-            .unwrap_or(surface_caps.formats[0]);
-
-        let config = wgpu::SurfaceConfiguration {
-            usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
-            format: surface_format,
-            width: size.0,
-            height: size.1,
-            present_mode: surface_caps.present_modes[0],
-            alpha_mode: surface_caps.alpha_modes[0],
-            view_formats: vec![],
-            desired_maximum_frame_latency: 0,
-        };
+        let surface_caps = capabilities
+            .surface
+            .as_ref()
+            .ok_or(CoreError::SurfaceNotConfigured)?;
+        let config = options.surface_config(surface_caps, size);
         surface.configure(&device, &config);
 
-        *worker = Some(Worker::new(
+        let mut w = Worker::new(
             size,
             1.,
-            SurfaceProperties { config, surface },
+            Some(SurfaceProperties { config, surface }),
+            adapter,
             device,
             queue,
-            limits.clone(),
+            capabilities,
             None,
             Context::new(),
-        )?);
+        )?;
+        if options.gpu_profiling {
+            w.enable_profiler();
+        }
+        if let Err(e) = w.set_sample_count(options.sample_count) {
+            warn!("{e}. MSAA is disabled");
+        }
+        *worker = Some(w);
 
         Ok(())
     }
 }
+
+pub(crate) fn run_frame<R: RenderWorker>(
+    render: &mut R,
+    w: &mut Worker<'_>,
+    dt: Duration,
+) -> Result<(), CoreError> {
+    let fixed_step = w.clock().fixed_step();
+    while w.clock_mut().consume_fixed_step() {
+        render.fixed_update(w, fixed_step)?;
+    }
+
+    render.update(w, dt)?;
+    render.render(w)
+}
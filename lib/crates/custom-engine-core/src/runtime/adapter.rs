@@ -0,0 +1,164 @@
+use log::{debug, info, warn};
+use pollster::block_on;
+
+use crate::{errors::CoreError, runtime::RuntimeOptions};
+
+#[derive(Debug)]
+pub struct AdapterCapabilities {
+    pub info: wgpu::AdapterInfo,
+    pub adapter_features: wgpu::Features,
+    pub adapter_limits: wgpu::Limits,
+    pub downlevel: wgpu::DownlevelCapabilities,
+    pub features: wgpu::Features,
+    pub limits: wgpu::Limits,
+    pub surface: Option<wgpu::SurfaceCapabilities>,
+}
+
+pub(crate) fn request_adapter(
+    instance: &wgpu::Instance,
+    options: &RuntimeOptions,
+    surface: Option<&wgpu::Surface<'_>>,
+) -> Result<wgpu::Adapter, CoreError> {
+    #[cfg(not(target_arch = "wasm32"))]
+    if options.adapter_name.is_some() || options.adapter_type.is_some() {
+        if let Some(adapter) = select_adapter(instance, options, surface) {
+            return Ok(adapter);
+        }
+
+        warn!(
+            "Adapter with name `{:?}` and type `{:?}` is not found. Request the default adapter",
+            options.adapter_name, options.adapter_type
+        );
+    }
+
+    let request = |force_fallback_adapter| {
+        block_on(instance.request_adapter(&wgpu::RequestAdapterOptions {
+            power_preference: options.power_preference,
+            compatible_surface: surface,
+            force_fallback_adapter,
+        }))
+    };
+
+    request(options.force_fallback_adapter)
+        .or_else(|| {
+            if options.force_fallback_adapter {
+                None
+            } else {
+                warn!("Hardware adapter is not found, request the fallback adapter");
+                request(true)
+            }
+        })
+        .ok_or(CoreError::RequestAdapter)
+}
+
+pub(crate) fn request_device(
+    adapter: &wgpu::Adapter,
+    options: &RuntimeOptions,
+    surface: Option<&wgpu::Surface<'_>>,
+) -> Result<(wgpu::Device, wgpu::Queue, AdapterCapabilities), CoreError> {
+    let adapter_features = adapter.features();
+    let missing_features = options.required_features - adapter_features;
+    if !missing_features.is_empty() {
+        return Err(CoreError::UnsupportedFeatures(missing_features));
+    }
+
+    let mut optional_features = options.optional_features;
+    if options.gpu_profiling {
+        optional_features |= wgpu::Features::TIMESTAMP_QUERY;
+    }
+    if options.push_constant_size > 0 {
+        optional_features |= wgpu::Features::PUSH_CONSTANTS;
+    }
+    if options.sample_count > 1 {
+        optional_features |= wgpu::Features::TEXTURE_ADAPTER_SPECIFIC_FORMAT_FEATURES;
+    }
+
+    let dropped_features = optional_features - adapter_features;
+    if !dropped_features.is_empty() {
+        warn!("Optional features `{dropped_features:?}` are not supported by the adapter");
+    }
+    let mut features = options.required_features | (optional_features & adapter_features);
+    if options.texture_compression {
+        features |= adapter_features
+            & (wgpu::Features::TEXTURE_COMPRESSION_BC
+                | wgpu::Features::TEXTURE_COMPRESSION_ETC2
+                | wgpu::Features::TEXTURE_COMPRESSION_ASTC);
+    }
+
+    let adapter_limits = adapter.limits();
+    let mut limits = options.limits.resolve(adapter);
+    if features.contains(wgpu::Features::PUSH_CONSTANTS) {
+        limits.max_push_constant_size = limits.max_push_constant_size.max(
+            options
+                .push_constant_size
+                .min(adapter_limits.max_push_constant_size),
+        );
+    }
+    let mut failed_limits = vec![];
+    limits.check_limits_with_fail_fn(&adapter_limits, false, |name, requested, allowed| {
+        failed_limits.push(format!("{name} (requested {requested}, allowed {allowed})"))
+    });
+    if !failed_limits.is_empty() {
+        return Err(CoreError::UnsupportedLimits(failed_limits.join(", ")));
+    }
+
+    let (device, queue) = block_on(adapter.request_device(
+        &wgpu::DeviceDescriptor {
+            required_features: features,
+            required_limits: limits.clone(),
+            memory_hints: Default::default(),
+            label: None,
+        },
+        None,
+    ))?;
+
+    let capabilities = AdapterCapabilities {
+        info: adapter.get_info(),
+        adapter_features,
+        adapter_limits,
+        downlevel: adapter.get_downlevel_capabilities(),
+        features,
+        limits,
+        surface: surface.map(|s| s.get_capabilities(adapter)),
+    };
+    let AdapterCapabilities {
+        info,
+        features,
+        limits,
+        ..
+    } = &capabilities;
+
+    info!("Adapter: {} ({:?})", info.name, info.backend);
+    debug!(
+        "
+Adapter:
+    Info: {info:#?},
+    Adapter features: {adapter_features:#?},
+    Features: {features:#?},
+    Limits: {limits:#?}"
+    );
+
+    Ok((device, queue, capabilities))
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn select_adapter(
+    instance: &wgpu::Instance,
+    options: &RuntimeOptions,
+    surface: Option<&wgpu::Surface<'_>>,
+) -> Option<wgpu::Adapter> {
+    let adapter_name = options.adapter_name.as_ref().map(|n| n.to_lowercase());
+
+    instance
+        .enumerate_adapters(options.backends)
+        .into_iter()
+        .find(|adapter| {
+            let info = adapter.get_info();
+
+            adapter_name
+                .as_ref()
+                .is_none_or(|n| info.name.to_lowercase().contains(n))
+                && options.adapter_type.is_none_or(|t| info.device_type == t)
+                && surface.is_none_or(|s| adapter.is_surface_supported(s))
+        })
+}
@@ -0,0 +1,127 @@
+use std::time::Duration;
+
+use log::{debug, warn};
+
+use crate::{
+    context::Context,
+    errors::CoreError,
+    runtime::{request_adapter, request_device, run_frame, RuntimeOptions},
+    traits::RenderWorker,
+    worker::Worker,
+};
+
+pub struct HeadlessRuntime<'a, R: RenderWorker + 'a> {
+    pub(crate) size: (u32, u32),
+    pub(crate) instance: wgpu::Instance,
+    pub(crate) options: RuntimeOptions,
+    pub(crate) frame_time: Duration,
+
+    worker: Option<Worker<'a>>,
+    render: R,
+}
+
+impl<'a, R: RenderWorker + 'a> HeadlessRuntime<'a, R> {
+    pub fn new(size: (u32, u32)) -> Self {
+        Self::with_options(size, RuntimeOptions::default())
+    }
+
+    pub fn with_options(size: (u32, u32), options: RuntimeOptions) -> Self {
+        let instance = wgpu::Instance::new(wgpu::InstanceDescriptor {
+            backends: options.backends,
+            ..Default::default()
+        });
+
+        Self {
+            instance,
+            size,
+            options,
+            frame_time: Duration::from_secs_f64(1. / 60.),
+            render: R::new(),
+            worker: None,
+        }
+    }
+
+    pub fn frame_time(mut self, frame_time: Duration) -> Self {
+        self.frame_time = frame_time;
+        self
+    }
+
+    pub fn init(&mut self) -> Result<(), CoreError> {
+        let adapter = request_adapter(&self.instance, &self.options, None)?;
+        let (device, queue, capabilities) = request_device(&adapter, &self.options, None)?;
+
+        let mut worker = Worker::new(
+            self.size,
+            1.,
+            None,
+            adapter,
+            device,
+            queue,
+            capabilities,
+            None,
+            Context::new(),
+        )?;
+        if self.options.gpu_profiling {
+            worker.enable_profiler();
+        }
+        if let Err(e) = worker.set_sample_count(self.options.sample_count) {
+            warn!("{e}. MSAA is disabled");
+        }
+        self.render.init(&mut worker)?;
+        self.worker = Some(worker);
+
+        Ok(())
+    }
+
+    pub fn run(&mut self, frames: usize) -> Result<(), CoreError> {
+        if self.worker.is_none() {
+            self.init()?;
+        }
+
+        let Self {
+            worker,
+            render,
+            frame_time,
+            ..
+        } = self;
+
+        // Worker is presented always at this step
+        let w = worker.as_mut().unwrap();
+        for frame in 0..frames {
+            debug!("Headless frame: {frame}");
+
+            let dt = w.clock_mut().advance(*frame_time);
+            run_frame(render, w, dt)?;
+        }
+        w.device.poll(wgpu::Maintain::Wait);
+
+        Ok(())
+    }
+
+    pub fn resize(&mut self, size: (u32, u32)) -> Result<(), CoreError> {
+        self.size = size;
+
+        if let Some(w) = self.worker.as_mut() {
+            w.resize_by_size(size);
+            self.render.resize(w)?;
+        }
+
+        Ok(())
+    }
+
+    pub fn worker(&self) -> Option<&Worker<'a>> {
+        self.worker.as_ref()
+    }
+
+    pub fn worker_mut(&mut self) -> Option<&mut Worker<'a>> {
+        self.worker.as_mut()
+    }
+
+    pub fn render(&self) -> &R {
+        &self.render
+    }
+
+    pub fn render_mut(&mut self) -> &mut R {
+        &mut self.render
+    }
+}
@@ -0,0 +1,212 @@
+use log::warn;
+
+#[derive(Debug, Clone, Default)]
+pub enum LimitsKind {
+    #[default]
+    Default,
+    Downlevel,
+    WebGl2,
+    Adapter,
+    Custom(wgpu::Limits),
+}
+
+impl LimitsKind {
+    pub fn resolve(&self, adapter: &wgpu::Adapter) -> wgpu::Limits {
+        match self {
+            LimitsKind::Default => {
+                if cfg!(target_arch = "wasm32") {
+                    wgpu::Limits::downlevel_webgl2_defaults()
+                } else {
+                    wgpu::Limits::default()
+                }
+            }
+            LimitsKind::Downlevel => wgpu::Limits::downlevel_defaults(),
+            LimitsKind::WebGl2 => wgpu::Limits::downlevel_webgl2_defaults(),
+            LimitsKind::Adapter => adapter.limits(),
+            LimitsKind::Custom(limits) => limits.clone(),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct RuntimeOptions {
+    pub(crate) backends: wgpu::Backends,
+    pub(crate) power_preference: wgpu::PowerPreference,
+    pub(crate) adapter_name: Option<String>,
+    pub(crate) adapter_type: Option<wgpu::DeviceType>,
+    pub(crate) force_fallback_adapter: bool,
+
+    pub(crate) required_features: wgpu::Features,
+    pub(crate) optional_features: wgpu::Features,
+    pub(crate) limits: LimitsKind,
+    pub(crate) gpu_profiling: bool,
+    pub(crate) push_constant_size: u32,
+    pub(crate) sample_count: u32,
+    pub(crate) texture_compression: bool,
+
+    pub(crate) present_mode: wgpu::PresentMode,
+    pub(crate) alpha_mode: Option<wgpu::CompositeAlphaMode>,
+    pub(crate) frame_latency: u32,
+}
+
+impl Default for RuntimeOptions {
+    fn default() -> Self {
+        Self {
+            backends: wgpu::Backends::all(),
+            power_preference: wgpu::PowerPreference::default(),
+            adapter_name: None,
+            adapter_type: None,
+            force_fallback_adapter: false,
+            required_features: wgpu::Features::empty(),
+            optional_features: wgpu::Features::empty(),
+            limits: LimitsKind::Default,
+            gpu_profiling: false,
+            push_constant_size: 0,
+            sample_count: 1,
+            texture_compression: true,
+            present_mode: wgpu::PresentMode::AutoVsync,
+            alpha_mode: None,
+            frame_latency: 2,
+        }
+    }
+}
+
+impl RuntimeOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn backends(mut self, backends: wgpu::Backends) -> Self {
+        self.backends = backends;
+        self
+    }
+
+    pub fn power_preference(mut self, power_preference: wgpu::PowerPreference) -> Self {
+        self.power_preference = power_preference;
+        self
+    }
+
+    pub fn adapter_name<S: Into<String>>(mut self, adapter_name: S) -> Self {
+        self.adapter_name = Some(adapter_name.into());
+        self
+    }
+
+    pub fn adapter_type(mut self, adapter_type: wgpu::DeviceType) -> Self {
+        self.adapter_type = Some(adapter_type);
+        self
+    }
+
+    pub fn force_fallback_adapter(mut self, force_fallback_adapter: bool) -> Self {
+        self.force_fallback_adapter = force_fallback_adapter;
+        self
+    }
+
+    pub fn required_features(mut self, required_features: wgpu::Features) -> Self {
+        self.required_features = required_features;
+        self
+    }
+
+    pub fn optional_features(mut self, optional_features: wgpu::Features) -> Self {
+        self.optional_features = optional_features;
+        self
+    }
+
+    pub fn limits(mut self, limits: LimitsKind) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    // Request `TIMESTAMP_QUERY` and profile every stage, if the adapter supports it
+    pub fn gpu_profiling(mut self, gpu_profiling: bool) -> Self {
+        self.gpu_profiling = gpu_profiling;
+        self
+    }
+
+    // Request `PUSH_CONSTANTS`, pipeline layouts fall back to uniforms without them
+    pub fn push_constant_size(mut self, push_constant_size: u32) -> Self {
+        self.push_constant_size = push_constant_size;
+        self
+    }
+
+    // MSAA sample count of the worker, it falls back to `1` when the formats don't support it
+    pub fn sample_count(mut self, sample_count: u32) -> Self {
+        self.sample_count = sample_count;
+        self
+    }
+
+    // Request BC, ETC2 and ASTC formats, which the adapter supports. Others are decoded on load
+    pub fn texture_compression(mut self, texture_compression: bool) -> Self {
+        self.texture_compression = texture_compression;
+        self
+    }
+
+    pub fn vsync(mut self, vsync: bool) -> Self {
+        self.present_mode = if vsync {
+            wgpu::PresentMode::AutoVsync
+        } else {
+            wgpu::PresentMode::AutoNoVsync
+        };
+        self
+    }
+
+    pub fn present_mode(mut self, present_mode: wgpu::PresentMode) -> Self {
+        self.present_mode = present_mode;
+        self
+    }
+
+    pub fn alpha_mode(mut self, alpha_mode: wgpu::CompositeAlphaMode) -> Self {
+        self.alpha_mode = Some(alpha_mode);
+        self
+    }
+
+    pub fn frame_latency(mut self, frame_latency: u32) -> Self {
+        self.frame_latency = frame_latency;
+        self
+    }
+
+    pub(crate) fn surface_config(
+        &self,
+        caps: &wgpu::SurfaceCapabilities,
+        size: (u32, u32),
+    ) -> wgpu::SurfaceConfiguration {
+        let format = caps
+            .formats
+            .iter()
+            .find(|f| f.is_srgb())
+            .cloned()
+            .unwrap_or(caps.formats[0]);
+
+        let present_mode = match self.present_mode {
+            p_m @ (wgpu::PresentMode::AutoVsync | wgpu::PresentMode::AutoNoVsync) => p_m,
+            p_m if caps.present_modes.contains(&p_m) => p_m,
+            p_m => {
+                warn!("Present mode `{p_m:?}` is not supported by the surface. Set `Fifo`");
+                wgpu::PresentMode::Fifo
+            }
+        };
+
+        let alpha_mode = match self.alpha_mode {
+            Some(a_m) if a_m == wgpu::CompositeAlphaMode::Auto => a_m,
+            Some(a_m) if caps.alpha_modes.contains(&a_m) => a_m,
+            Some(a_m) => {
+                warn!(
+                    "Alpha mode `{a_m:?}` is not supported by the surface. Set `{:?}`",
+                    caps.alpha_modes[0]
+                );
+                caps.alpha_modes[0]
+            }
+            None => caps.alpha_modes[0],
+        };
+
+        wgpu::SurfaceConfiguration {
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+            format,
+            width: size.0,
+            height: size.1,
+            present_mode,
+            alpha_mode,
+            view_formats: vec![],
+            desired_maximum_frame_latency: self.frame_latency,
+        }
+    }
+}
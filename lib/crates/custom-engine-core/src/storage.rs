@@ -34,6 +34,14 @@ impl Storages {
         &self.bind_group_layout
     }
 
+    pub(crate) fn buffers(&self) -> impl Iterator<Item = &Buffer> {
+        self.buffers.values()
+    }
+
+    pub(crate) fn textures(&self) -> impl Iterator<Item = &RenderTexture> {
+        self.textures.values()
+    }
+
     pub fn get_buffer(&self, name: &str) -> Option<&Buffer> {
         self.buffers.get(name)
     }
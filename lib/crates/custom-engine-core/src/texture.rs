@@ -1,12 +1,20 @@
+mod compressed;
 mod depth;
+mod mipmaps;
+mod msaa;
+mod readback;
 mod render;
 
+pub use compressed::*;
 pub use depth::*;
+pub use mipmaps::*;
+pub use msaa::*;
+pub use readback::*;
 pub use render::*;
 
 use derive_more::Constructor;
 
-use crate::buffer::Buffer;
+use crate::{buffer::Buffer, errors::CoreError};
 
 #[derive(Debug)]
 pub enum TextureKind {
@@ -44,9 +52,9 @@ pub struct CopyTextureParams<'a> {
 }
 
 impl<'a> CopyTextureParams<'a> {
-    pub fn process(&self, encoder: &mut wgpu::CommandEncoder) {
+    pub fn process(&self, encoder: &mut wgpu::CommandEncoder) -> Result<(), CoreError> {
         let CopyTextureParams { buffer, texture } = self;
 
-        texture.load_to_buffer(encoder, buffer);
+        texture.load_to_buffer(encoder, buffer)
     }
 }
@@ -0,0 +1,862 @@
+mod astc;
+#[cfg(all(feature = "basis-universal", not(target_arch = "wasm32")))]
+mod basis;
+mod bc;
+mod etc;
+
+use std::io::Read;
+
+use image::DynamicImage;
+use log::debug;
+
+use crate::errors::CoreError;
+
+const KTX2_IDENTIFIER: [u8; 12] = [
+    0xAB, 0x4B, 0x54, 0x58, 0x20, 0x32, 0x30, 0xBB, 0x0D, 0x0A, 0x1A, 0x0A,
+];
+const DDS_MAGIC: &[u8; 4] = b"DDS ";
+
+const KTX2_HEADER_SIZE: usize = 80;
+const KTX2_LEVEL_INDEX_SIZE: usize = 24;
+const KTX2_DFD_BLOCK_SIZE: usize = 24;
+const KTX2_DFD_SAMPLE_SIZE: usize = 16;
+const DDS_HEADER_SIZE: usize = 128;
+const DDS_DX10_HEADER_SIZE: usize = 20;
+
+const DDS_PIXEL_FORMAT_FOURCC: u32 = 0x4;
+const DDS_PIXEL_FORMAT_RGB: u32 = 0x40;
+const DDS_CAPS2_CUBEMAP: u32 = 0x200;
+const DDS_CAPS2_VOLUME: u32 = 0x200000;
+const DDS_RESOURCE_MISC_TEXTURECUBE: u32 = 0x4;
+
+const KTX2_MODEL_ETC1S: u8 = 163;
+const KTX2_MODEL_UASTC: u8 = 166;
+const KTX2_TRANSFER_SRGB: u8 = 2;
+
+// Channels of the UASTC or ETC1S data, they pick the format of the transcoded blocks
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BasisChannels {
+    Rgb,
+    Rgba,
+    R,
+    Rg,
+}
+
+// Data of the basis universal formats, `into_supported` transcodes it
+#[derive(derivative::Derivative, Clone, PartialEq, Eq)]
+#[derivative(Debug)]
+pub enum BasisData {
+    // UASTC blocks have the size of ASTC 4x4 blocks
+    Uastc(BasisChannels),
+    // ETC1S levels are BasisLZ slices, they're decoded with the codebooks of the global data
+    Etc1s {
+        channels: BasisChannels,
+        #[derivative(Debug = "ignore")]
+        global_data: Vec<u8>,
+    },
+}
+
+// Texture of the KTX2 or DDS container, it's uploaded without decoding
+#[derive(derivative::Derivative)]
+#[derivative(Debug)]
+pub struct CompressedImage {
+    pub format: wgpu::TextureFormat,
+    pub width: u32,
+    pub height: u32,
+    // Cube maps have six layers per array element
+    pub layers: u32,
+    pub cube: bool,
+    pub basis: Option<BasisData>,
+    // Every mip level contains the images of all layers
+    #[derivative(Debug = "ignore")]
+    pub levels: Vec<Vec<u8>>,
+}
+
+impl CompressedImage {
+    // Returns `None`, when the data isn't a KTX2 or DDS container
+    pub fn parse(data: &[u8]) -> Result<Option<Self>, CoreError> {
+        let image = if data.starts_with(&KTX2_IDENTIFIER) {
+            Self::parse_ktx2(data)?
+        } else if data.starts_with(DDS_MAGIC) {
+            Self::parse_dds(data)?
+        } else {
+            return Ok(None);
+        };
+
+        debug!(
+            "
+Parse compressed image:
+    Format: {:?},
+    Size: {}x{},
+    Layers: {},
+    Levels: {},",
+            image.format,
+            image.width,
+            image.height,
+            image.layers,
+            image.levels.len()
+        );
+
+        Ok(Some(image))
+    }
+
+    // Texels of the decoded image are stored as `Rgba16Float`, `Rgba32Float` or `Rgba8`
+    pub fn from_image(image: &DynamicImage, format: wgpu::TextureFormat) -> Self {
+        let data = match format {
+            wgpu::TextureFormat::Rgba32Float => {
+                bytemuck::cast_slice(&image.to_rgba32f().into_raw()).to_vec()
+            }
+            wgpu::TextureFormat::Rgba16Float => image
+                .to_rgba32f()
+                .into_raw()
+                .into_iter()
+                .flat_map(|v| f32_to_f16(v).to_le_bytes())
+                .collect(),
+            _ => image.to_rgba8().into_raw(),
+        };
+
+        Self {
+            format,
+            width: image.width(),
+            height: image.height(),
+            layers: 1,
+            cube: false,
+            basis: None,
+            levels: vec![data],
+        }
+    }
+
+    // Images with the same format, size and mip levels are joined into layers of one image
+    pub fn join(images: Vec<Self>) -> Result<Self, CoreError> {
+        let mut images = images.into_iter();
+        let mut joined = images.next().ok_or(CoreError::TextureLayersMismatch)?;
+
+        for image in images {
+            if image.format != joined.format
+                || image.basis != joined.basis
+                || (image.width, image.height) != (joined.width, joined.height)
+                || image.levels.len() != joined.levels.len()
+            {
+                return Err(CoreError::TextureLayersMismatch);
+            }
+
+            joined.layers += image.layers;
+            for (level, data) in joined.levels.iter_mut().zip(image.levels) {
+                level.extend(data);
+            }
+        }
+
+        Ok(joined)
+    }
+
+    // Parses the container and converts it to the format, which the device supports
+    pub fn load(data: &[u8], features: wgpu::Features) -> Result<Option<Self>, CoreError> {
+        Self::parse(data)?
+            .map(|image| image.into_supported(features))
+            .transpose()
+    }
+
+    // Formats without the device features and unaligned sizes are decoded to `Rgba8`
+    pub fn into_supported(self, features: wgpu::Features) -> Result<Self, CoreError> {
+        if self.basis.is_some() {
+            return self.transcode(features);
+        }
+
+        let (block_width, block_height) = self.format.block_dimensions();
+        let is_aligned =
+            self.width.is_multiple_of(block_width) && self.height.is_multiple_of(block_height);
+
+        if features.contains(self.format.required_features()) && is_aligned {
+            Ok(self)
+        } else {
+            self.decompress()
+        }
+    }
+
+    // Blocks are decoded in software, BC6H to `Rgba16Float` and others to `Rgba8`, ASTC HDR needs the device support
+    pub fn decompress(self) -> Result<Self, CoreError> {
+        use wgpu::TextureFormat::*;
+
+        if self.basis.is_some() {
+            return self.transcode(wgpu::Features::empty());
+        }
+
+        let format = match self.format {
+            Bc1RgbaUnormSrgb | Bc2RgbaUnormSrgb | Bc3RgbaUnormSrgb | Bc7RgbaUnormSrgb
+            | Etc2Rgb8UnormSrgb | Etc2Rgb8A1UnormSrgb | Etc2Rgba8UnormSrgb => Rgba8UnormSrgb,
+            Bc1RgbaUnorm | Bc2RgbaUnorm | Bc3RgbaUnorm | Bc4RUnorm | Bc5RgUnorm | Bc7RgbaUnorm
+            | Etc2Rgb8Unorm | Etc2Rgb8A1Unorm | Etc2Rgba8Unorm | EacR11Unorm | EacRg11Unorm => {
+                Rgba8Unorm
+            }
+            Bc4RSnorm | Bc5RgSnorm | EacR11Snorm | EacRg11Snorm => Rgba8Snorm,
+            Bc6hRgbUfloat | Bc6hRgbFloat => Rgba16Float,
+            Astc {
+                channel: wgpu::AstcChannel::UnormSrgb,
+                ..
+            } => Rgba8UnormSrgb,
+            Astc {
+                channel: wgpu::AstcChannel::Unorm,
+                ..
+            } => Rgba8Unorm,
+            f if !f.is_compressed() => return Ok(self),
+            f => return Err(CoreError::UnsupportedCompressedFormat(f)),
+        };
+
+        let levels = self
+            .levels
+            .iter()
+            .enumerate()
+            .map(|(level, data)| {
+                let (width, height) = self.level_dimensions(level as u32);
+                let image_size = level_image_size(self.format, width, height);
+
+                data.chunks_exact(image_size)
+                    .flat_map(|image| decode_blocks(self.format, width, height, image))
+                    .collect()
+            })
+            .collect();
+
+        Ok(Self {
+            format,
+            levels,
+            ..self
+        })
+    }
+
+    pub fn level_dimensions(&self, level: u32) -> (u32, u32) {
+        ((self.width >> level).max(1), (self.height >> level).max(1))
+    }
+
+    #[cfg(all(feature = "basis-universal", not(target_arch = "wasm32")))]
+    fn transcode(self, features: wgpu::Features) -> Result<Self, CoreError> {
+        basis::transcode(self, features)
+    }
+
+    // Textures with the fallback data, like the glTF `source` image, load it instead
+    #[cfg(not(all(feature = "basis-universal", not(target_arch = "wasm32"))))]
+    fn transcode(self, _: wgpu::Features) -> Result<Self, CoreError> {
+        Err(CoreError::UnsupportedTextureContainer(
+            "KTX2 UASTC and ETC1S data need the `basis-universal` feature on native targets"
+                .to_string(),
+        ))
+    }
+
+    fn parse_ktx2(data: &[u8]) -> Result<Self, CoreError> {
+        let vk_format = read_u32(data, 12)?;
+        let width = read_u32(data, 20)?;
+        let height = read_u32(data, 24)?;
+        let depth = read_u32(data, 28)?;
+        let faces = read_u32(data, 36)?.max(1);
+        let layers = read_u32(data, 32)?.max(1) * faces;
+        let level_count = read_u32(data, 40)?.max(1);
+        let supercompression = read_u32(data, 44)?;
+
+        if depth > 1 {
+            return Err(CoreError::UnsupportedTextureContainer(
+                "KTX2 volume textures".to_string(),
+            ));
+        }
+        // Zstandard is decoded here, BasisLZ is decoded by the ETC1S transcoder with the global codebooks
+        if supercompression > 2 {
+            return Err(CoreError::UnsupportedTextureContainer(format!(
+                "KTX2 supercompression scheme {supercompression}"
+            )));
+        }
+        let (format, basis) = match vk_format {
+            0 => {
+                let (format, basis) = Self::parse_ktx2_dfd(data)?;
+                (format, Some(basis))
+            }
+            f => (
+                ktx2_format(f).ok_or(CoreError::UnsupportedTextureContainer(format!(
+                    "KTX2 Vulkan format {f}"
+                )))?,
+                None,
+            ),
+        };
+        if (supercompression == 1) != matches!(basis, Some(BasisData::Etc1s { .. })) {
+            return Err(CoreError::InvalidTextureContainer(
+                "KTX2 BasisLZ supercompression is used only by ETC1S data".to_string(),
+            ));
+        }
+
+        let mut levels = Vec::with_capacity(level_count as usize);
+        for level in 0..level_count as usize {
+            let index = KTX2_HEADER_SIZE + level * KTX2_LEVEL_INDEX_SIZE;
+            let offset = read_u64(data, index)? as usize;
+            let length = read_u64(data, index + 8)? as usize;
+            let level_data = read_bytes(data, offset, length)?;
+
+            levels.push(if supercompression == 2 {
+                zstd_decode(level_data, level)?
+            } else {
+                level_data.to_vec()
+            });
+        }
+
+        let image = Self {
+            format,
+            width,
+            height,
+            layers,
+            cube: faces == 6,
+            basis,
+            levels,
+        };
+        image.validate()?;
+
+        Ok(image)
+    }
+
+    // Data without the Vulkan format is described by the color model of the data format descriptor
+    fn parse_ktx2_dfd(data: &[u8]) -> Result<(wgpu::TextureFormat, BasisData), CoreError> {
+        let dfd = read_u32(data, 48)? as usize;
+        let block_size = read_bytes(data, dfd + 10, 2)?;
+        let sample_count = (u16::from_le_bytes([block_size[0], block_size[1]]) as usize)
+            .saturating_sub(KTX2_DFD_BLOCK_SIZE)
+            / KTX2_DFD_SAMPLE_SIZE;
+        let model = read_bytes(data, dfd + 12, 1)?[0];
+        let transfer = read_bytes(data, dfd + 14, 1)?[0];
+        let channel = read_bytes(data, dfd + 31, 1)?[0] & 0xf;
+        let srgb = transfer == KTX2_TRANSFER_SRGB;
+
+        match model {
+            KTX2_MODEL_UASTC => {
+                let channels = match channel {
+                    0 => BasisChannels::Rgb,
+                    3 | 5 => BasisChannels::Rgba,
+                    4 => BasisChannels::R,
+                    6 => BasisChannels::Rg,
+                    c => {
+                        return Err(CoreError::InvalidTextureContainer(format!(
+                            "KTX2 UASTC channels {c}"
+                        )))
+                    }
+                };
+                let channel = if srgb {
+                    wgpu::AstcChannel::UnormSrgb
+                } else {
+                    wgpu::AstcChannel::Unorm
+                };
+                let format = wgpu::TextureFormat::Astc {
+                    block: wgpu::AstcBlock::B4x4,
+                    channel,
+                };
+
+                Ok((format, BasisData::Uastc(channels)))
+            }
+            // ETC1S stores the alpha or the green channel in the second slice
+            KTX2_MODEL_ETC1S => {
+                let second_channel = if sample_count > 1 {
+                    Some(read_bytes(data, dfd + 31 + KTX2_DFD_SAMPLE_SIZE, 1)?[0] & 0xf)
+                } else {
+                    None
+                };
+                let channels = match (channel, second_channel) {
+                    (0, None) => BasisChannels::Rgb,
+                    (0, Some(15)) => BasisChannels::Rgba,
+                    (3, None) => BasisChannels::R,
+                    (3, Some(4)) => BasisChannels::Rg,
+                    (c, s) => {
+                        return Err(CoreError::InvalidTextureContainer(format!(
+                            "KTX2 ETC1S channels {c} and {s:?}"
+                        )))
+                    }
+                };
+                let offset = read_u64(data, 64)? as usize;
+                let length = read_u64(data, 72)? as usize;
+                let global_data = read_bytes(data, offset, length)?.to_vec();
+                let format = if srgb {
+                    wgpu::TextureFormat::Etc2Rgb8UnormSrgb
+                } else {
+                    wgpu::TextureFormat::Etc2Rgb8Unorm
+                };
+
+                Ok((
+                    format,
+                    BasisData::Etc1s {
+                        channels,
+                        global_data,
+                    },
+                ))
+            }
+            m => Err(CoreError::UnsupportedTextureContainer(format!(
+                "KTX2 color model {m}"
+            ))),
+        }
+    }
+
+    fn parse_dds(data: &[u8]) -> Result<Self, CoreError> {
+        let height = read_u32(data, 12)?;
+        let width = read_u32(data, 16)?;
+        let level_count = read_u32(data, 28)?.max(1);
+        let pixel_format_flags = read_u32(data, 80)?;
+        let four_cc = read_bytes(data, 84, 4)?;
+        let caps2 = read_u32(data, 112)?;
+
+        if caps2 & DDS_CAPS2_VOLUME != 0 {
+            return Err(CoreError::UnsupportedTextureContainer(
+                "DDS volume textures".to_string(),
+            ));
+        }
+
+        let mut offset = DDS_HEADER_SIZE;
+        let mut cube = caps2 & DDS_CAPS2_CUBEMAP != 0;
+        let mut layers = if cube { 6 } else { 1 };
+        let format = if pixel_format_flags & DDS_PIXEL_FORMAT_FOURCC != 0 {
+            if four_cc == b"DX10" {
+                let dxgi_format = read_u32(data, offset)?;
+                let misc_flags = read_u32(data, offset + 8)?;
+                cube = misc_flags & DDS_RESOURCE_MISC_TEXTURECUBE != 0;
+                layers = read_u32(data, offset + 12)?.max(1) * if cube { 6 } else { 1 };
+                offset += DDS_DX10_HEADER_SIZE;
+
+                dxgi_format_to_wgpu(dxgi_format).ok_or(CoreError::UnsupportedTextureContainer(
+                    format!("DXGI format {dxgi_format}"),
+                ))?
+            } else {
+                four_cc_format(four_cc).ok_or(CoreError::UnsupportedTextureContainer(format!(
+                    "DDS FourCC `{}`",
+                    String::from_utf8_lossy(four_cc)
+                )))?
+            }
+        } else if pixel_format_flags & DDS_PIXEL_FORMAT_RGB != 0 && read_u32(data, 88)? == 32 {
+            match (read_u32(data, 92)?, read_u32(data, 100)?) {
+                (0xff, 0xff0000) => wgpu::TextureFormat::Rgba8Unorm,
+                (0xff0000, 0xff) => wgpu::TextureFormat::Bgra8Unorm,
+                _ => {
+                    return Err(CoreError::UnsupportedTextureContainer(
+                        "DDS pixel masks".to_string(),
+                    ))
+                }
+            }
+        } else {
+            return Err(CoreError::UnsupportedTextureContainer(
+                "DDS pixel format".to_string(),
+            ));
+        };
+
+        // DDS stores all mip levels of every layer in turn, levels are regrouped by the mip
+        let mut levels = vec![vec![]; level_count as usize];
+        for _ in 0..layers {
+            for (level, level_data) in levels.iter_mut().enumerate() {
+                let (w, h) = ((width >> level).max(1), (height >> level).max(1));
+                let image_size = level_image_size(format, w, h);
+
+                level_data.extend_from_slice(read_bytes(data, offset, image_size)?);
+                offset += image_size;
+            }
+        }
+
+        Ok(Self {
+            format,
+            width,
+            height,
+            layers,
+            cube,
+            basis: None,
+            levels,
+        })
+    }
+
+    fn validate(&self) -> Result<(), CoreError> {
+        // BasisLZ slices have variable sizes, the transcoder checks them
+        if matches!(self.basis, Some(BasisData::Etc1s { .. })) {
+            return Ok(());
+        }
+
+        for (level, data) in self.levels.iter().enumerate() {
+            let (width, height) = self.level_dimensions(level as u32);
+            let size = level_image_size(self.format, width, height) * self.layers as usize;
+
+            if data.len() != size {
+                return Err(CoreError::InvalidTextureContainer(format!(
+                    "level {level} has {} bytes, but {size} are expected",
+                    data.len()
+                )));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+fn f32_to_f16(value: f32) -> u16 {
+    let bits = value.to_bits();
+    let sign = ((bits >> 16) & 0x8000) as u16;
+    let exponent = ((bits >> 23) & 0xff) as i32;
+    let mantissa = bits & 0x7fffff;
+
+    match exponent - 127 + 15 {
+        _ if exponent == 0xff && mantissa != 0 => sign | 0x7e00,
+        e if e >= 0x1f => sign | 0x7c00,
+        e if e <= 0 => {
+            if e < -10 {
+                return sign;
+            }
+            sign | ((mantissa | 0x800000) >> (14 - e)) as u16
+        }
+        e => sign | (e as u16) << 10 | (mantissa >> 13) as u16,
+    }
+}
+
+fn zstd_decode(data: &[u8], level: usize) -> Result<Vec<u8>, CoreError> {
+    let error = |e: &dyn std::fmt::Display| {
+        CoreError::InvalidTextureContainer(format!("level {level}: {e}"))
+    };
+
+    let mut decoder = ruzstd::StreamingDecoder::new(data).map_err(|e| error(&e))?;
+    let mut decoded = vec![];
+    decoder.read_to_end(&mut decoded).map_err(|e| error(&e))?;
+
+    Ok(decoded)
+}
+
+fn level_image_size(format: wgpu::TextureFormat, width: u32, height: u32) -> usize {
+    let (block_width, block_height) = format.block_dimensions();
+    let block_size = format.block_copy_size(None).unwrap_or_default();
+
+    (width.div_ceil(block_width) * height.div_ceil(block_height) * block_size) as usize
+}
+
+fn read_bytes(data: &[u8], offset: usize, len: usize) -> Result<&[u8], CoreError> {
+    data.get(offset..offset + len)
+        .ok_or(CoreError::InvalidTextureContainer(format!(
+            "{len} bytes at {offset} are out of the data"
+        )))
+}
+
+fn read_u32(data: &[u8], offset: usize) -> Result<u32, CoreError> {
+    let bytes = read_bytes(data, offset, 4)?;
+
+    Ok(u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+}
+
+fn read_u64(data: &[u8], offset: usize) -> Result<u64, CoreError> {
+    Ok(read_u32(data, offset)? as u64 | (read_u32(data, offset + 4)? as u64) << 32)
+}
+
+fn ktx2_format(vk_format: u32) -> Option<wgpu::TextureFormat> {
+    use wgpu::{AstcBlock::*, AstcChannel, TextureFormat::*};
+
+    let format = match vk_format {
+        37 => Rgba8Unorm,
+        43 => Rgba8UnormSrgb,
+        44 => Bgra8Unorm,
+        50 => Bgra8UnormSrgb,
+        97 => Rgba16Float,
+        109 => Rgba32Float,
+        131 | 133 => Bc1RgbaUnorm,
+        132 | 134 => Bc1RgbaUnormSrgb,
+        135 => Bc2RgbaUnorm,
+        136 => Bc2RgbaUnormSrgb,
+        137 => Bc3RgbaUnorm,
+        138 => Bc3RgbaUnormSrgb,
+        139 => Bc4RUnorm,
+        140 => Bc4RSnorm,
+        141 => Bc5RgUnorm,
+        142 => Bc5RgSnorm,
+        143 => Bc6hRgbUfloat,
+        144 => Bc6hRgbFloat,
+        145 => Bc7RgbaUnorm,
+        146 => Bc7RgbaUnormSrgb,
+        147 => Etc2Rgb8Unorm,
+        148 => Etc2Rgb8UnormSrgb,
+        149 => Etc2Rgb8A1Unorm,
+        150 => Etc2Rgb8A1UnormSrgb,
+        151 => Etc2Rgba8Unorm,
+        152 => Etc2Rgba8UnormSrgb,
+        153 => EacR11Unorm,
+        154 => EacR11Snorm,
+        155 => EacRg11Unorm,
+        156 => EacRg11Snorm,
+        157..=184 => {
+            let blocks = [
+                B4x4, B5x4, B5x5, B6x5, B6x6, B8x5, B8x6, B8x8, B10x5, B10x6, B10x8, B10x10,
+                B12x10, B12x12,
+            ];
+            let index = vk_format - 157;
+            let channel = if index.is_multiple_of(2) {
+                AstcChannel::Unorm
+            } else {
+                AstcChannel::UnormSrgb
+            };
+
+            Astc {
+                block: blocks[index as usize / 2],
+                channel,
+            }
+        }
+        _ => return None,
+    };
+
+    Some(format)
+}
+
+fn dxgi_format_to_wgpu(dxgi_format: u32) -> Option<wgpu::TextureFormat> {
+    use wgpu::TextureFormat::*;
+
+    let format = match dxgi_format {
+        2 => Rgba32Float,
+        10 => Rgba16Float,
+        28 => Rgba8Unorm,
+        29 => Rgba8UnormSrgb,
+        71 => Bc1RgbaUnorm,
+        72 => Bc1RgbaUnormSrgb,
+        74 => Bc2RgbaUnorm,
+        75 => Bc2RgbaUnormSrgb,
+        77 => Bc3RgbaUnorm,
+        78 => Bc3RgbaUnormSrgb,
+        80 => Bc4RUnorm,
+        81 => Bc4RSnorm,
+        83 => Bc5RgUnorm,
+        84 => Bc5RgSnorm,
+        87 => Bgra8Unorm,
+        91 => Bgra8UnormSrgb,
+        95 => Bc6hRgbUfloat,
+        96 => Bc6hRgbFloat,
+        98 => Bc7RgbaUnorm,
+        99 => Bc7RgbaUnormSrgb,
+        _ => return None,
+    };
+
+    Some(format)
+}
+
+fn four_cc_format(four_cc: &[u8]) -> Option<wgpu::TextureFormat> {
+    use wgpu::TextureFormat::*;
+
+    let format = match four_cc {
+        b"DXT1" => Bc1RgbaUnorm,
+        b"DXT2" | b"DXT3" => Bc2RgbaUnorm,
+        b"DXT4" | b"DXT5" => Bc3RgbaUnorm,
+        b"ATI1" | b"BC4U" => Bc4RUnorm,
+        b"BC4S" => Bc4RSnorm,
+        b"ATI2" | b"BC5U" => Bc5RgUnorm,
+        b"BC5S" => Bc5RgSnorm,
+        _ => return None,
+    };
+
+    Some(format)
+}
+
+// Decodes the image of the blocks to `Rgba8` texels or `Rgba16Float` ones of BC6H
+fn decode_blocks(format: wgpu::TextureFormat, width: u32, height: u32, data: &[u8]) -> Vec<u8> {
+    let (width, height) = (width as usize, height as usize);
+    let (block_width, block_height) = format.block_dimensions();
+    let (block_width, block_height) = (block_width as usize, block_height as usize);
+    let block_size = format.block_copy_size(None).unwrap_or_default() as usize;
+    let texel_size = match format {
+        wgpu::TextureFormat::Bc6hRgbUfloat | wgpu::TextureFormat::Bc6hRgbFloat => 8,
+        _ => 4,
+    };
+    let blocks_per_row = width.div_ceil(block_width);
+
+    let mut texels = vec![0; width * height * texel_size];
+    for (index, block) in data.chunks_exact(block_size).enumerate() {
+        let block_texels = if let Some(texels) =
+            bc::decode(format, block).or_else(|| etc::decode(format, block))
+        {
+            texels.concat()
+        } else if let Some(texels) = bc::decode_bc6h(format, block) {
+            texels
+                .iter()
+                .flatten()
+                .flat_map(|c| c.to_le_bytes())
+                .collect()
+        } else if let Some(texels) = astc::decode(format, block) {
+            texels.concat()
+        } else {
+            return texels;
+        };
+
+        let (block_x, block_y) = (
+            index % blocks_per_row * block_width,
+            index / blocks_per_row * block_height,
+        );
+        for (i, texel) in block_texels.chunks_exact(texel_size).enumerate() {
+            let (x, y) = (block_x + i % block_width, block_y + i / block_width);
+            if x < width && y < height {
+                let offset = (y * width + x) * texel_size;
+                texels[offset..offset + texel_size].copy_from_slice(texel);
+            }
+        }
+    }
+
+    texels
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn compressed_texture() {
+        // 4x4 DXT1 texture: red and blue endpoints, the first row is blue
+        let mut dds = vec![0; DDS_HEADER_SIZE];
+        dds[..4].copy_from_slice(DDS_MAGIC);
+        dds[12..16].copy_from_slice(&4u32.to_le_bytes());
+        dds[16..20].copy_from_slice(&4u32.to_le_bytes());
+        dds[80..84].copy_from_slice(&DDS_PIXEL_FORMAT_FOURCC.to_le_bytes());
+        dds[84..88].copy_from_slice(b"DXT1");
+        dds.extend_from_slice(&[0x00, 0xf8, 0x1f, 0x00, 0x55, 0x00, 0x00, 0x00]);
+
+        let image = CompressedImage::parse(&dds).unwrap().unwrap();
+        assert_eq!(image.format, wgpu::TextureFormat::Bc1RgbaUnorm);
+        assert_eq!((image.width, image.height, image.layers), (4, 4, 1));
+        assert_eq!(image.levels.len(), 1);
+
+        let image = image.into_supported(wgpu::Features::empty()).unwrap();
+        assert_eq!(image.format, wgpu::TextureFormat::Rgba8Unorm);
+        assert_eq!(&image.levels[0][..4], &[0, 0, 255, 255]);
+        assert_eq!(&image.levels[0][16..20], &[255, 0, 0, 255]);
+
+        let face = CompressedImage::parse(&dds).unwrap().unwrap();
+        let faces = (0..6).map(|_| CompressedImage::parse(&dds).unwrap().unwrap());
+        let cube = CompressedImage::join(faces.collect()).unwrap();
+        assert_eq!(cube.layers, 6);
+        assert_eq!(cube.levels[0].len(), 6 * 8);
+        assert!(CompressedImage::join(vec![cube, face.decompress().unwrap()]).is_err());
+
+        assert_eq!(f32_to_f16(1.), 0x3c00);
+        assert_eq!(f32_to_f16(-2.), 0xc000);
+        assert_eq!(f32_to_f16(0.5), 0x3800);
+        assert_eq!(f32_to_f16(2f32.powi(-24)), 0x0001);
+        assert_eq!(f32_to_f16(1e6), 0x7c00);
+
+        let mut ktx2 = vec![0; KTX2_HEADER_SIZE + KTX2_LEVEL_INDEX_SIZE];
+        ktx2[..12].copy_from_slice(&KTX2_IDENTIFIER);
+        ktx2[12..16].copy_from_slice(&157u32.to_le_bytes());
+        ktx2[20..24].copy_from_slice(&4u32.to_le_bytes());
+        ktx2[24..28].copy_from_slice(&4u32.to_le_bytes());
+        let offset = ktx2.len() as u64;
+        ktx2[80..88].copy_from_slice(&offset.to_le_bytes());
+        ktx2[88..96].copy_from_slice(&16u64.to_le_bytes());
+        ktx2.extend_from_slice(&[0; 16]);
+
+        let image = CompressedImage::parse(&ktx2).unwrap().unwrap();
+        assert!(matches!(image.format, wgpu::TextureFormat::Astc { .. }));
+
+        // The reserved block mode gives the error color
+        let image = image.into_supported(wgpu::Features::empty()).unwrap();
+        assert_eq!(image.format, wgpu::TextureFormat::Rgba8Unorm);
+        assert_eq!(&image.levels[0][..4], &[255, 0, 255, 255]);
+
+        assert!(CompressedImage::parse(&[0x89, b'P', b'N', b'G'])
+            .unwrap()
+            .is_none());
+    }
+
+    fn ktx2_image(vk_format: u32, supercompression: u32, level: &[u8]) -> Vec<u8> {
+        let mut ktx2 = vec![0; KTX2_HEADER_SIZE + KTX2_LEVEL_INDEX_SIZE];
+        ktx2[..12].copy_from_slice(&KTX2_IDENTIFIER);
+        ktx2[12..16].copy_from_slice(&vk_format.to_le_bytes());
+        ktx2[20..24].copy_from_slice(&4u32.to_le_bytes());
+        ktx2[24..28].copy_from_slice(&4u32.to_le_bytes());
+        ktx2[44..48].copy_from_slice(&supercompression.to_le_bytes());
+        let offset = ktx2.len() as u64;
+        ktx2[80..88].copy_from_slice(&offset.to_le_bytes());
+        ktx2[88..96].copy_from_slice(&(level.len() as u64).to_le_bytes());
+        ktx2.extend_from_slice(level);
+
+        ktx2
+    }
+
+    #[test]
+    fn software_decoding() {
+        let decode = |vk_format: u32, block: &[u8]| {
+            let ktx2 = ktx2_image(vk_format, 0, block);
+            let image = CompressedImage::parse(&ktx2).unwrap().unwrap();
+            image.into_supported(wgpu::Features::empty()).unwrap()
+        };
+
+        // ETC2 individual mode: gray sub-blocks 136 and 68 with the large positive modifiers
+        let etc2 = [0x84, 0x84, 0x84, 0x1c, 0x00, 0x00, 0xff, 0xff];
+        let image = decode(147, &etc2);
+        assert_eq!(image.format, wgpu::TextureFormat::Rgba8Unorm);
+        assert_eq!(&image.levels[0][..4], &[144, 144, 144, 255]);
+        assert_eq!(&image.levels[0][12..16], &[251, 251, 251, 255]);
+
+        // EAC alpha: base 128, multiplier 1, the last modifier of the table 13
+        let mut etc2_eac = vec![128, 0x1d, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff];
+        etc2_eac.extend_from_slice(&etc2);
+        let image = decode(152, &etc2_eac);
+        assert_eq!(image.format, wgpu::TextureFormat::Rgba8UnormSrgb);
+        assert_eq!(&image.levels[0][..4], &[144, 144, 144, 137]);
+
+        // BC7 mode 6: the first endpoint is white red with the p-bit, the second one is zero
+        let mut bits = 1u128 << 6;
+        bits |= 0x7f << 7 | 0x7f << 49 | 1 << 63;
+        bits |= 0xf << 68;
+        let image = decode(145, &bits.to_le_bytes());
+        assert_eq!(&image.levels[0][..4], &[255, 1, 1, 255]);
+        assert_eq!(&image.levels[0][4..8], &[0, 0, 0, 0]);
+
+        // The same block in the Zstandard frame with one raw block
+        let mut zstd = vec![0x28, 0xb5, 0x2f, 0xfd, 0x20, 16, 16 << 3 | 1, 0, 0];
+        zstd.extend_from_slice(&bits.to_le_bytes());
+        let ktx2 = ktx2_image(145, 2, &zstd);
+        let image = CompressedImage::parse(&ktx2).unwrap().unwrap();
+        assert_eq!(image.levels[0], bits.to_le_bytes());
+
+        // ASTC void extent: the constant color is stored as `u16` values
+        let mut bits = 0xdfc | ((1u128 << 52) - 1) << 12;
+        bits |= 0xff00 << 64 | 0x8000 << 80 | 0xffff << 112;
+        let image = decode(157, &bits.to_le_bytes());
+        assert_eq!(image.format, wgpu::TextureFormat::Rgba8Unorm);
+        assert_eq!(&image.levels[0][60..64], &[255, 128, 0, 255]);
+
+        // BC6H mode 3: the first endpoint is black and the second one is the max value
+        let mut bits = 0x3u128 | 0x3ff << 35 | 0x3ff << 45 | 0x3ff << 55;
+        bits |= 0xf << 68;
+        let image = decode(143, &bits.to_le_bytes());
+        assert_eq!(image.format, wgpu::TextureFormat::Rgba16Float);
+        assert_eq!(&image.levels[0][..8], &[0, 0, 0, 0, 0, 0, 0x00, 0x3c]);
+        assert_eq!(
+            &image.levels[0][8..16],
+            &[0xff, 0x7b, 0xff, 0x7b, 0xff, 0x7b, 0x00, 0x3c]
+        );
+
+        // UASTC RGBA with the sRGB transfer is described by the data format descriptor
+        let mut ktx2 = ktx2_image(0, 0, &[0; 16]);
+        let dfd = ktx2.len();
+        ktx2[48..52].copy_from_slice(&(dfd as u32).to_le_bytes());
+        ktx2.extend_from_slice(&[0; 44]);
+        ktx2[dfd + 12] = KTX2_MODEL_UASTC;
+        ktx2[dfd + 14] = KTX2_TRANSFER_SRGB;
+        ktx2[dfd + 31] = 3;
+        let image = CompressedImage::parse(&ktx2).unwrap().unwrap();
+        assert_eq!(image.basis, Some(BasisData::Uastc(BasisChannels::Rgba)));
+        assert_eq!(
+            image.format,
+            wgpu::TextureFormat::Astc {
+                block: wgpu::AstcBlock::B4x4,
+                channel: wgpu::AstcChannel::UnormSrgb
+            }
+        );
+
+        // ETC1S RGB with the alpha slice needs BasisLZ with the global data
+        ktx2[dfd + 12] = KTX2_MODEL_ETC1S;
+        assert!(CompressedImage::parse(&ktx2).is_err());
+        ktx2[44..48].copy_from_slice(&1u32.to_le_bytes());
+        ktx2[dfd + 10] = (KTX2_DFD_BLOCK_SIZE + 2 * KTX2_DFD_SAMPLE_SIZE) as u8;
+        ktx2[dfd + 31] = 0;
+        ktx2.extend_from_slice(&[0; KTX2_DFD_SAMPLE_SIZE]);
+        ktx2[dfd + 47] = 15;
+        let sgd = ktx2.len() as u64;
+        ktx2[64..72].copy_from_slice(&sgd.to_le_bytes());
+        ktx2[72..80].copy_from_slice(&4u64.to_le_bytes());
+        ktx2.extend_from_slice(&[1, 2, 3, 4]);
+
+        let image = CompressedImage::parse(&ktx2).unwrap().unwrap();
+        assert_eq!(image.format, wgpu::TextureFormat::Etc2Rgb8UnormSrgb);
+        assert_eq!(
+            image.basis,
+            Some(BasisData::Etc1s {
+                channels: BasisChannels::Rgba,
+                global_data: vec![1, 2, 3, 4]
+            })
+        );
+        assert!(image.into_supported(wgpu::Features::empty()).is_err());
+    }
+}
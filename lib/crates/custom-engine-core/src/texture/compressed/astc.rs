@@ -0,0 +1,587 @@
+// Blocks are decoded with the LDR profile, HDR and invalid blocks get the error color
+const ERROR_COLOR: [u8; 4] = [255, 0, 255, 255];
+
+// Quantization ranges of the integer sequences: trits, quints and bits of every value
+const RANGES: [(u32, u32, u32); 21] = [
+    (0, 0, 1),
+    (1, 0, 0),
+    (0, 0, 2),
+    (0, 1, 0),
+    (1, 0, 1),
+    (0, 0, 3),
+    (0, 1, 1),
+    (1, 0, 2),
+    (0, 0, 4),
+    (0, 1, 2),
+    (1, 0, 3),
+    (0, 0, 5),
+    (0, 1, 3),
+    (1, 0, 4),
+    (0, 0, 6),
+    (0, 1, 4),
+    (1, 0, 5),
+    (0, 0, 7),
+    (0, 1, 5),
+    (1, 0, 6),
+    (0, 0, 8),
+];
+
+// Color endpoints use the ranges from 0..5
+const MIN_COLOR_RANGE: usize = 4;
+
+struct BlockMode {
+    width: usize,
+    height: usize,
+    dual_plane: bool,
+    range: (u32, u32, u32),
+}
+
+// Decodes the block to `Rgba8` texels by rows
+pub fn decode(format: wgpu::TextureFormat, block: &[u8]) -> Option<Vec<[u8; 4]>> {
+    let wgpu::TextureFormat::Astc { channel, .. } = format else {
+        return None;
+    };
+    let srgb = match channel {
+        wgpu::AstcChannel::Unorm => false,
+        wgpu::AstcChannel::UnormSrgb => true,
+        wgpu::AstcChannel::Hdr => return None,
+    };
+
+    let mut bytes = [0; 16];
+    bytes.copy_from_slice(&block[..16]);
+    let bits = u128::from_le_bytes(bytes);
+    let (width, height) = format.block_dimensions();
+    let (width, height) = (width as usize, height as usize);
+
+    Some(
+        decode_block(bits, width, height, srgb)
+            .unwrap_or_else(|| vec![ERROR_COLOR; width * height]),
+    )
+}
+
+fn decode_block(bits: u128, width: usize, height: usize, srgb: bool) -> Option<Vec<[u8; 4]>> {
+    if bits & 0x1ff == 0x1fc {
+        return decode_void_extent(bits, width * height);
+    }
+
+    let mode = block_mode(bits as u32 & 0x7ff)?;
+    let planes = 1 + mode.dual_plane as usize;
+    let weight_count = mode.width * mode.height * planes;
+    let partitions = (bits >> 11 & 0x3) as usize + 1;
+    if mode.width > width
+        || mode.height > height
+        || weight_count > 64
+        || (mode.dual_plane && partitions == 4)
+    {
+        return None;
+    }
+    let weight_bits = ise_bits(weight_count, mode.range);
+    if !(24..=96).contains(&weight_bits) {
+        return None;
+    }
+
+    // Endpoint modes of several partitions are the same or have the base class and the extra bits below the weights
+    let (modes, color_start, extra_bits) = if partitions == 1 {
+        (vec![(bits >> 13 & 0xf) as u32], 17, 0)
+    } else {
+        let selector = (bits >> 23 & 0x3) as u32;
+        let field = (bits >> 25 & 0xf) as u32;
+        if selector == 0 {
+            (vec![field; partitions], 29, 0)
+        } else {
+            let extra_bits = 3 * partitions - 4;
+            let extra = (bits >> (128 - weight_bits - extra_bits)) as u32 & ((1 << extra_bits) - 1);
+            let field = field | extra << 4;
+            let modes = (0..partitions)
+                .map(|i| {
+                    (selector - 1 + (field >> i & 0x1)) << 2 | (field >> (partitions + 2 * i) & 0x3)
+                })
+                .collect();
+
+            (modes, 29, extra_bits)
+        }
+    };
+    let color_end = 128 - weight_bits - extra_bits - 2 * mode.dual_plane as usize;
+    let plane_channel = (bits >> color_end & 0x3) as usize;
+
+    let value_count = modes.iter().map(|m| (*m as usize >> 2) + 1).sum::<usize>() * 2;
+    let available = color_end.checked_sub(color_start)?;
+    if value_count > 18 {
+        return None;
+    }
+    let color_range = RANGES[MIN_COLOR_RANGE..]
+        .iter()
+        .rev()
+        .find(|r| ise_bits(value_count, **r) <= available)
+        .copied()?;
+    let color_data = bits >> color_start & mask(ise_bits(value_count, color_range));
+    let values = decode_ise(color_data, value_count, color_range)
+        .into_iter()
+        .map(|v| unquantize_color(v, color_range))
+        .collect::<Vec<_>>();
+
+    let mut endpoints = Vec::with_capacity(partitions);
+    let mut offset = 0;
+    for endpoint_mode in modes {
+        let count = ((endpoint_mode as usize >> 2) + 1) * 2;
+        endpoints.push(decode_endpoints(
+            endpoint_mode,
+            &values[offset..offset + count],
+        )?);
+        offset += count;
+    }
+
+    // Weights are stored from the highest bit
+    let weight_data = bits.reverse_bits() & mask(weight_bits);
+    let weights = decode_ise(weight_data, weight_count, mode.range)
+        .into_iter()
+        .map(|v| unquantize_weight(v, mode.range))
+        .collect::<Vec<_>>();
+
+    let seed = (bits >> 13 & 0x3ff) as u32;
+    let small_block = width * height < 31;
+    let (ds, dt) = (
+        (1024 + width / 2) / (width - 1),
+        (1024 + height / 2) / (height - 1),
+    );
+
+    let texels = (0..width * height)
+        .map(|i| {
+            let (x, y) = (i % width, i / width);
+            let partition = if partitions > 1 {
+                select_partition(seed, x as u32, y as u32, partitions, small_block)
+            } else {
+                0
+            };
+            let [e0, e1] = endpoints[partition];
+
+            // Weights of the grid are interpolated bilinearly to the texel
+            let gs = (ds * x * (mode.width - 1) + 32) >> 6;
+            let gt = (dt * y * (mode.height - 1) + 32) >> 6;
+            let (js, fs, jt, ft) = (gs >> 4, gs & 0xf, gt >> 4, gt & 0xf);
+            let w11 = (fs * ft + 8) >> 4;
+            let factors = [16 - fs - ft + w11, fs - w11, ft - w11, w11];
+            let origin = js + jt * mode.width;
+            let plane_weight = |plane: usize| {
+                let sum = [
+                    origin,
+                    origin + 1,
+                    origin + mode.width,
+                    origin + mode.width + 1,
+                ]
+                .iter()
+                .zip(factors)
+                .map(|(&g, f)| weights.get(g * planes + plane).copied().unwrap_or(0) * f as u32)
+                .sum::<u32>();
+
+                (sum + 8) >> 4
+            };
+            let (w0, w1) = (plane_weight(0), plane_weight(planes - 1));
+
+            std::array::from_fn(|c| {
+                let weight = if mode.dual_plane && c == plane_channel {
+                    w1
+                } else {
+                    w0
+                };
+                interpolate(e0[c], e1[c], weight, srgb && c < 3)
+            })
+        })
+        .collect();
+
+    Some(texels)
+}
+
+// Constant color of the block, it's stored as `u16` values
+fn decode_void_extent(bits: u128, count: usize) -> Option<Vec<[u8; 4]>> {
+    if bits >> 9 & 0x1 != 0 || bits >> 10 & 0x3 != 0x3 {
+        return None;
+    }
+    let color = std::array::from_fn(|c| (bits >> (64 + 16 * c + 8)) as u8);
+
+    Some(vec![color; count])
+}
+
+fn block_mode(bits: u32) -> Option<BlockMode> {
+    let bit = |i: u32| bits >> i & 0x1;
+    let (a, b) = (bits >> 5 & 0x3, bits >> 7 & 0x3);
+    let (mut high, mut dual_plane) = (bit(9), bit(10) != 0);
+
+    let (range, width, height) = if bits & 0x3 != 0 {
+        let range = bit(4) | (bits & 0x3) << 1;
+        let (width, height) = match bits >> 2 & 0x3 {
+            0 => (b + 4, a + 2),
+            1 => (b + 8, a + 2),
+            2 => (a + 2, b + 8),
+            _ if bit(8) == 0 => (a + 2, (b & 0x1) + 6),
+            _ => ((b & 0x1) + 2, a + 2),
+        };
+
+        (range, width, height)
+    } else {
+        let range = bit(4) | (bits >> 2 & 0x3) << 1;
+        let (width, height) = match (b, a) {
+            (0, _) => (12, a + 2),
+            (1, _) => (a + 2, 12),
+            (3, 0) => (6, 10),
+            (3, 1) => (10, 6),
+            (2, _) => {
+                (high, dual_plane) = (0, false);
+                (a + 6, (bits >> 9 & 0x3) + 6)
+            }
+            _ => return None,
+        };
+
+        (range, width, height)
+    };
+    if range < 2 {
+        return None;
+    }
+
+    Some(BlockMode {
+        width: width as usize,
+        height: height as usize,
+        dual_plane,
+        range: RANGES[(range - 2 + 6 * high) as usize],
+    })
+}
+
+fn ise_bits(count: usize, (trits, quints, bits): (u32, u32, u32)) -> usize {
+    count * bits as usize
+        + trits as usize * (8 * count).div_ceil(5)
+        + quints as usize * (7 * count).div_ceil(3)
+}
+
+fn mask(bits: usize) -> u128 {
+    if bits >= 128 {
+        u128::MAX
+    } else {
+        (1 << bits) - 1
+    }
+}
+
+// Values of the integer sequence are trits or quints with the low bits, missing bits are zeros
+fn decode_ise(data: u128, count: usize, (trits, quints, bits): (u32, u32, u32)) -> Vec<(u32, u32)> {
+    let mut offset = 0;
+    let mut read = |count: u32| {
+        let value = if offset < 128 {
+            (data >> offset) as u32 & ((1 << count) - 1)
+        } else {
+            0
+        };
+        offset += count;
+
+        value
+    };
+
+    let mut values = Vec::with_capacity(count + 4);
+    while values.len() < count {
+        if trits == 1 {
+            let (mut low, mut packed, mut shift) = ([0; 5], 0, 0);
+            for (value, packed_bits) in low.iter_mut().zip([2, 2, 1, 2, 1]) {
+                *value = read(bits);
+                packed |= read(packed_bits) << shift;
+                shift += packed_bits;
+            }
+            values.extend(decode_trits(packed).into_iter().zip(low));
+        } else if quints == 1 {
+            let (mut low, mut packed, mut shift) = ([0; 3], 0, 0);
+            for (value, packed_bits) in low.iter_mut().zip([3, 2, 2]) {
+                *value = read(bits);
+                packed |= read(packed_bits) << shift;
+                shift += packed_bits;
+            }
+            values.extend(decode_quints(packed).into_iter().zip(low));
+        } else {
+            values.push((0, read(bits)));
+        }
+    }
+    values.truncate(count);
+
+    values
+}
+
+fn decode_trits(packed: u32) -> [u32; 5] {
+    let bit = |v: u32, i: u32| v >> i & 0x1;
+
+    let (c, t4, t3) = if packed >> 2 & 0x7 == 0x7 {
+        ((packed >> 5 & 0x7) << 2 | (packed & 0x3), 2, 2)
+    } else if packed >> 5 & 0x3 == 0x3 {
+        (packed & 0x1f, 2, bit(packed, 7))
+    } else {
+        (packed & 0x1f, bit(packed, 7), packed >> 5 & 0x3)
+    };
+    let (t2, t1, t0) = if c & 0x3 == 0x3 {
+        (
+            2,
+            bit(c, 4),
+            bit(c, 3) << 1 | (bit(c, 2) & !bit(c, 3) & 0x1),
+        )
+    } else if c >> 2 & 0x3 == 0x3 {
+        (2, 2, c & 0x3)
+    } else {
+        (
+            bit(c, 4),
+            c >> 2 & 0x3,
+            bit(c, 1) << 1 | (bit(c, 0) & !bit(c, 1) & 0x1),
+        )
+    };
+
+    [t0, t1, t2, t3, t4]
+}
+
+fn decode_quints(packed: u32) -> [u32; 3] {
+    let bit = |v: u32, i: u32| v >> i & 0x1;
+
+    if packed >> 1 & 0x3 == 0x3 && packed >> 5 & 0x3 == 0 {
+        let low = !bit(packed, 0) & 0x1;
+        let q2 = bit(packed, 0) << 2 | (bit(packed, 4) & low) << 1 | (bit(packed, 3) & low);
+        return [4, 4, q2];
+    }
+
+    let (q2, c) = if packed >> 1 & 0x3 == 0x3 {
+        (
+            4,
+            (packed >> 3 & 0x3) << 3 | (!(packed >> 5) & 0x3) << 1 | bit(packed, 0),
+        )
+    } else {
+        (packed >> 5 & 0x3, packed & 0x1f)
+    };
+    let (q1, q0) = if c & 0x7 == 0x5 {
+        (4, c >> 3 & 0x3)
+    } else {
+        (c >> 3 & 0x3, c & 0x7)
+    };
+
+    [q0, q1, q2]
+}
+
+// Values are scaled to 0..255, the bits of trits and quints are mixed with the shuffled low bits
+fn unquantize_color((d, m): (u32, u32), (trits, quints, bits): (u32, u32, u32)) -> i32 {
+    if trits == 0 && quints == 0 {
+        return replicate(m, bits, 8) as i32;
+    }
+
+    let bit = |i: u32| m >> i & 0x1;
+    let (b, c, d_, e, f) = (bit(1), bit(2), bit(3), bit(4), bit(5));
+    let (shuffled, scale) = match (trits, bits) {
+        (1, 1) => (0, 204),
+        (1, 2) => (b << 8 | b << 4 | b << 2 | b << 1, 93),
+        (1, 3) => (c << 8 | b << 7 | c << 3 | b << 2 | c << 1 | b, 44),
+        (1, 4) => (d_ << 8 | c << 7 | b << 6 | d_ << 2 | c << 1 | b, 22),
+        (1, 5) => (e << 8 | d_ << 7 | c << 6 | b << 5 | e << 1 | d_, 11),
+        (1, _) => (f << 8 | e << 7 | d_ << 6 | c << 5 | b << 4 | f, 5),
+        (_, 1) => (0, 113),
+        (_, 2) => (b << 8 | b << 3 | b << 2, 54),
+        (_, 3) => (c << 8 | b << 7 | c << 2 | b << 1 | c, 26),
+        (_, 4) => (d_ << 8 | c << 7 | b << 6 | d_ << 1 | c, 13),
+        (_, _) => (e << 8 | d_ << 7 | c << 6 | b << 5 | e, 6),
+    };
+    let a = if m & 0x1 != 0 { 0x1ff } else { 0 };
+    let t = (d * scale + shuffled) ^ a;
+
+    ((a & 0x80) | t >> 2) as i32
+}
+
+// Weights are scaled to 0..64
+fn unquantize_weight((d, m): (u32, u32), (trits, quints, bits): (u32, u32, u32)) -> u32 {
+    let bit = |i: u32| m >> i & 0x1;
+    let (b, c) = (bit(1), bit(2));
+
+    let value = match (trits, quints, bits) {
+        (0, 0, _) => replicate(m, bits, 6),
+        (1, _, 0) => [0, 32, 63][d as usize],
+        (_, 1, 0) => [0, 16, 32, 47, 63][d as usize],
+        _ => {
+            let (shuffled, scale) = match (trits, bits) {
+                (1, 1) => (0, 50),
+                (1, 2) => (b << 6 | b << 2 | b, 23),
+                (1, _) => (c << 6 | b << 5 | c << 1 | b, 11),
+                (_, 1) => (0, 28),
+                (_, _) => (b << 6 | b << 1, 13),
+            };
+            let a = if m & 0x1 != 0 { 0x7f } else { 0 };
+            let t = (d * scale + shuffled) ^ a;
+
+            (a & 0x20) | t >> 2
+        }
+    };
+
+    if value > 32 {
+        value + 1
+    } else {
+        value
+    }
+}
+
+fn replicate(value: u32, bits: u32, to: u32) -> u32 {
+    if bits == 0 {
+        return 0;
+    }
+
+    let (mut result, mut filled) = (0, 0);
+    while filled < to {
+        result = result << bits | value;
+        filled += bits;
+    }
+
+    result >> (filled - to)
+}
+
+// LDR endpoint modes, the HDR ones are errors
+fn decode_endpoints(mode: u32, v: &[i32]) -> Option<[[u8; 4]; 2]> {
+    let endpoints = match mode {
+        0 => [[v[0], v[0], v[0], 255], [v[1], v[1], v[1], 255]],
+        1 => {
+            let l0 = v[0] >> 2 | (v[1] & 0xc0);
+            let l1 = (l0 + (v[1] & 0x3f)).min(255);
+            [[l0, l0, l0, 255], [l1, l1, l1, 255]]
+        }
+        4 => [[v[0], v[0], v[0], v[2]], [v[1], v[1], v[1], v[3]]],
+        5 => {
+            let (d0, l0) = bit_transfer_signed(v[1], v[0]);
+            let (d1, a0) = bit_transfer_signed(v[3], v[2]);
+            let l1 = l0 + d0;
+            [[l0, l0, l0, a0], [l1, l1, l1, a0 + d1]]
+        }
+        6 => [
+            [
+                (v[0] * v[3]) >> 8,
+                (v[1] * v[3]) >> 8,
+                (v[2] * v[3]) >> 8,
+                255,
+            ],
+            [v[0], v[1], v[2], 255],
+        ],
+        8 | 12 => {
+            let (a0, a1) = if mode == 12 { (v[6], v[7]) } else { (255, 255) };
+            if v[1] + v[3] + v[5] >= v[0] + v[2] + v[4] {
+                [[v[0], v[2], v[4], a0], [v[1], v[3], v[5], a1]]
+            } else {
+                [
+                    blue_contract([v[1], v[3], v[5], a1]),
+                    blue_contract([v[0], v[2], v[4], a0]),
+                ]
+            }
+        }
+        9 | 13 => {
+            let (dr, r) = bit_transfer_signed(v[1], v[0]);
+            let (dg, g) = bit_transfer_signed(v[3], v[2]);
+            let (db, b) = bit_transfer_signed(v[5], v[4]);
+            let (da, a) = if mode == 13 {
+                bit_transfer_signed(v[7], v[6])
+            } else {
+                (0, 255)
+            };
+            if dr + dg + db >= 0 {
+                [[r, g, b, a], [r + dr, g + dg, b + db, a + da]]
+            } else {
+                [
+                    blue_contract([r + dr, g + dg, b + db, a + da]),
+                    blue_contract([r, g, b, a]),
+                ]
+            }
+        }
+        10 => [
+            [
+                (v[0] * v[3]) >> 8,
+                (v[1] * v[3]) >> 8,
+                (v[2] * v[3]) >> 8,
+                v[4],
+            ],
+            [v[0], v[1], v[2], v[5]],
+        ],
+        _ => return None,
+    };
+
+    Some(endpoints.map(|e| e.map(|c| c.clamp(0, 255) as u8)))
+}
+
+fn bit_transfer_signed(a: i32, b: i32) -> (i32, i32) {
+    let b = b >> 1 | (a & 0x80);
+    let a = a >> 1 & 0x3f;
+
+    (if a & 0x20 != 0 { a - 0x40 } else { a }, b)
+}
+
+fn blue_contract([r, g, b, a]: [i32; 4]) -> [i32; 4] {
+    [(r + b) >> 1, (g + b) >> 1, b, a]
+}
+
+fn select_partition(seed: u32, x: u32, y: u32, partitions: usize, small_block: bool) -> usize {
+    let (x, y) = if small_block {
+        (x << 1, y << 1)
+    } else {
+        (x, y)
+    };
+    let seed = seed + (partitions as u32 - 1) * 1024;
+    let rnum = hash52(seed);
+
+    let mut seeds = [
+        rnum,
+        rnum >> 4,
+        rnum >> 8,
+        rnum >> 12,
+        rnum >> 16,
+        rnum >> 20,
+        rnum >> 24,
+        rnum >> 28,
+    ]
+    .map(|s| (s & 0xf) * (s & 0xf));
+
+    let (sh1, sh2) = match (seed & 0x1 != 0, seed & 0x2 != 0, partitions == 3) {
+        (true, high, three) => (if high { 4 } else { 5 }, if three { 6 } else { 5 }),
+        (false, high, three) => (if three { 6 } else { 5 }, if high { 4 } else { 5 }),
+    };
+    for (i, s) in seeds.iter_mut().enumerate() {
+        *s >>= if i % 2 == 0 { sh1 } else { sh2 };
+    }
+
+    let mut parts = [
+        (seeds[0] * x + seeds[1] * y + (rnum >> 14)) & 0x3f,
+        (seeds[2] * x + seeds[3] * y + (rnum >> 10)) & 0x3f,
+        (seeds[4] * x + seeds[5] * y + (rnum >> 6)) & 0x3f,
+        (seeds[6] * x + seeds[7] * y + (rnum >> 2)) & 0x3f,
+    ];
+    for part in parts.iter_mut().skip(partitions) {
+        *part = 0;
+    }
+
+    let [a, b, c, d] = parts;
+    if a >= b && a >= c && a >= d {
+        0
+    } else if b >= c && b >= d {
+        1
+    } else if c >= d {
+        2
+    } else {
+        3
+    }
+}
+
+fn hash52(mut value: u32) -> u32 {
+    value ^= value >> 15;
+    value = value.wrapping_mul(0xeede0891);
+    value ^= value >> 5;
+    value = value.wrapping_add(value << 16);
+    value ^= value >> 7;
+    value ^= value >> 3;
+    value ^= value << 6;
+    value ^= value >> 17;
+
+    value
+}
+
+fn interpolate(e0: u8, e1: u8, weight: u32, srgb: bool) -> u8 {
+    // sRGB endpoints are expanded to the middle of the lower byte
+    let expand = |c: u8| {
+        if srgb {
+            (c as u32) << 8 | 0x80
+        } else {
+            c as u32 * 257
+        }
+    };
+    let c = (expand(e0) * (64 - weight) + expand(e1) * weight + 32) >> 6;
+
+    (c >> 8) as u8
+}
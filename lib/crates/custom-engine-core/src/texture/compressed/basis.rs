@@ -0,0 +1,310 @@
+use basis_universal::{
+    DecodeFlags, LowLevelUastcTranscoder, SliceParametersUastc, TranscodeParameters, Transcoder,
+    TranscoderBlockFormat, TranscoderTextureFormat,
+};
+
+use crate::{
+    errors::CoreError,
+    texture::compressed::{
+        level_image_size, read_bytes, read_u32, BasisChannels, BasisData, CompressedImage,
+    },
+};
+
+const BASIS_HEADER_SIZE: usize = 77;
+const BASIS_SLICE_DESC_SIZE: usize = 23;
+const BASIS_SIGNATURE: u16 = 0x4273;
+const BASIS_VERSION: u16 = 0x13;
+const BASIS_FLAG_ETC1S: u16 = 0x1;
+const BASIS_FLAG_ALPHA_SLICES: u16 = 0x4;
+const BASIS_FLAG_SRGB: u16 = 0x10;
+const BASIS_SLICE_FLAG_ALPHA: u8 = 0x1;
+
+const BASISLZ_GLOBAL_HEADER_SIZE: usize = 20;
+const BASISLZ_IMAGE_DESC_SIZE: usize = 20;
+
+// Blocks are transcoded to the supported compressed format with the same channels or `Rgba8`
+pub fn transcode(
+    mut image: CompressedImage,
+    features: wgpu::Features,
+) -> Result<CompressedImage, CoreError> {
+    let Some(basis) = image.basis.take() else {
+        return Ok(image);
+    };
+
+    let is_aligned = image.width.is_multiple_of(4) && image.height.is_multiple_of(4);
+    let features = if is_aligned {
+        features
+    } else {
+        wgpu::Features::empty()
+    };
+    let channels = match &basis {
+        BasisData::Uastc(channels) | BasisData::Etc1s { channels, .. } => *channels,
+    };
+    let (block_format, texture_format, format) = target_format(channels, features);
+    let format = if image.format.is_srgb() {
+        format.add_srgb_suffix()
+    } else {
+        format
+    };
+
+    let levels = match basis {
+        BasisData::Uastc(channels) => transcode_uastc(&image, channels, block_format)?,
+        BasisData::Etc1s {
+            channels,
+            global_data,
+        } => transcode_etc1s(&image, channels, &global_data, texture_format)?,
+    };
+
+    let image = CompressedImage {
+        format,
+        levels,
+        ..image
+    };
+    image.validate()?;
+
+    Ok(image)
+}
+
+fn transcode_uastc(
+    image: &CompressedImage,
+    channels: BasisChannels,
+    block_format: TranscoderBlockFormat,
+) -> Result<Vec<Vec<u8>>, CoreError> {
+    let transcoder = LowLevelUastcTranscoder::new();
+    let mut levels = Vec::with_capacity(image.levels.len());
+    for (level, data) in image.levels.iter().enumerate() {
+        let (width, height) = image.level_dimensions(level as u32);
+        let layer_size = level_image_size(image.format, width, height);
+
+        let mut transcoded = vec![];
+        for layer in data.chunks_exact(layer_size) {
+            let parameters = SliceParametersUastc {
+                num_blocks_x: width.div_ceil(4),
+                num_blocks_y: height.div_ceil(4),
+                has_alpha: channels == BasisChannels::Rgba,
+                original_width: width,
+                original_height: height,
+            };
+            let layer = transcoder
+                .transcode_slice(layer, parameters, DecodeFlags::HIGH_QUALITY, block_format)
+                .map_err(|e| {
+                    CoreError::InvalidTextureContainer(format!(
+                        "UASTC level {level} can't be transcoded to `{block_format:?}`: {e:?}"
+                    ))
+                })?;
+            transcoded.extend(layer);
+        }
+        levels.push(transcoded);
+    }
+
+    Ok(levels)
+}
+
+// The transcoder reads ETC1S only from `.basis` files, they're assembled from the KTX2 levels
+fn transcode_etc1s(
+    image: &CompressedImage,
+    channels: BasisChannels,
+    global_data: &[u8],
+    texture_format: TranscoderTextureFormat,
+) -> Result<Vec<Vec<u8>>, CoreError> {
+    let file = basis_file(image, channels, global_data)?;
+
+    let mut transcoder = Transcoder::new();
+    transcoder.prepare_transcoding(&file).map_err(|_| {
+        CoreError::InvalidTextureContainer("ETC1S codebooks can't be decoded".to_string())
+    })?;
+
+    let mut levels = Vec::with_capacity(image.levels.len());
+    for level in 0..image.levels.len() as u32 {
+        let mut transcoded = vec![];
+        for layer in 0..image.layers {
+            let parameters = TranscodeParameters {
+                image_index: layer,
+                level_index: level,
+                decode_flags: Some(DecodeFlags::HIGH_QUALITY),
+                ..Default::default()
+            };
+            let mut layer = transcoder
+                .transcode_image_level(&file, texture_format, parameters)
+                .map_err(|e| {
+                    CoreError::InvalidTextureContainer(format!(
+                        "ETC1S level {level} can't be transcoded to `{texture_format:?}`: {e:?}"
+                    ))
+                })?;
+
+            // Green of the RG data is stored in the alpha slice
+            if channels == BasisChannels::Rg && texture_format == TranscoderTextureFormat::RGBA32 {
+                for texel in layer.chunks_exact_mut(4) {
+                    texel[1] = texel[3];
+                    texel[3] = 255;
+                }
+            }
+            transcoded.extend(layer);
+        }
+        levels.push(transcoded);
+    }
+    transcoder.end_transcoding();
+
+    Ok(levels)
+}
+
+// Slices of the images are sorted by the mip levels, the alpha slices follow the color ones
+fn basis_file(
+    image: &CompressedImage,
+    channels: BasisChannels,
+    global_data: &[u8],
+) -> Result<Vec<u8>, CoreError> {
+    let read_u16 =
+        |offset| read_bytes(global_data, offset, 2).map(|b| u16::from_le_bytes([b[0], b[1]]));
+    let endpoint_count = read_u16(0)?;
+    let selector_count = read_u16(2)?;
+    let endpoints_size = read_u32(global_data, 4)? as usize;
+    let selectors_size = read_u32(global_data, 8)? as usize;
+    let tables_size = read_u32(global_data, 12)? as usize;
+
+    let image_count = image.levels.len() * image.layers as usize;
+    let codebooks_offset = BASISLZ_GLOBAL_HEADER_SIZE + image_count * BASISLZ_IMAGE_DESC_SIZE;
+    let codebooks_size = endpoints_size + selectors_size + tables_size;
+    let codebooks = read_bytes(global_data, codebooks_offset, codebooks_size)?;
+
+    let has_alpha = matches!(channels, BasisChannels::Rgba | BasisChannels::Rg);
+    let slice_count = image_count * (1 + has_alpha as usize);
+    let file_codebooks_offset = BASIS_HEADER_SIZE + slice_count * BASIS_SLICE_DESC_SIZE;
+    let mut level_offset = file_codebooks_offset + codebooks_size;
+    let level_offsets = image
+        .levels
+        .iter()
+        .map(|level| {
+            let offset = level_offset;
+            level_offset += level.len();
+
+            offset
+        })
+        .collect::<Vec<_>>();
+    let file_size = level_offset;
+
+    let mut slices = Vec::with_capacity(slice_count * BASIS_SLICE_DESC_SIZE);
+    for layer in 0..image.layers as usize {
+        for (level, data) in image.levels.iter().enumerate() {
+            // Images of the global data are sorted by the levels
+            let desc = BASISLZ_GLOBAL_HEADER_SIZE
+                + (level * image.layers as usize + layer) * BASISLZ_IMAGE_DESC_SIZE;
+            let mut images = vec![(
+                read_u32(global_data, desc + 4)?,
+                read_u32(global_data, desc + 8)?,
+                0,
+            )];
+            if has_alpha {
+                images.push((
+                    read_u32(global_data, desc + 12)?,
+                    read_u32(global_data, desc + 16)?,
+                    BASIS_SLICE_FLAG_ALPHA,
+                ));
+            }
+
+            let (width, height) = image.level_dimensions(level as u32);
+            for (offset, length, flags) in images {
+                if offset as usize + length as usize > data.len() {
+                    return Err(CoreError::InvalidTextureContainer(format!(
+                        "ETC1S slice of the level {level} is out of the data"
+                    )));
+                }
+
+                slices.extend_from_slice(&(layer as u32).to_le_bytes()[..3]);
+                slices.extend_from_slice(&[level as u8, flags]);
+                slices.extend_from_slice(&(width as u16).to_le_bytes());
+                slices.extend_from_slice(&(height as u16).to_le_bytes());
+                slices.extend_from_slice(&(width.div_ceil(4) as u16).to_le_bytes());
+                slices.extend_from_slice(&(height.div_ceil(4) as u16).to_le_bytes());
+                slices.extend_from_slice(&(level_offsets[level] as u32 + offset).to_le_bytes());
+                slices.extend_from_slice(&length.to_le_bytes());
+                slices.extend_from_slice(&0u16.to_le_bytes());
+            }
+        }
+    }
+
+    let mut flags = BASIS_FLAG_ETC1S;
+    if has_alpha {
+        flags |= BASIS_FLAG_ALPHA_SLICES;
+    }
+    if image.format.is_srgb() {
+        flags |= BASIS_FLAG_SRGB;
+    }
+    let texture_type: u8 = if image.layers > 1 { 1 } else { 0 };
+    let endpoints_offset = file_codebooks_offset as u32;
+    let selectors_offset = endpoints_offset + endpoints_size as u32;
+    let tables_offset = selectors_offset + selectors_size as u32;
+
+    // Header has the packed little endian fields, CRCs aren't checked by the transcoder
+    let mut file = Vec::with_capacity(file_size);
+    file.extend_from_slice(&BASIS_SIGNATURE.to_le_bytes());
+    file.extend_from_slice(&BASIS_VERSION.to_le_bytes());
+    file.extend_from_slice(&(BASIS_HEADER_SIZE as u16).to_le_bytes());
+    file.extend_from_slice(&0u16.to_le_bytes());
+    file.extend_from_slice(&((file_size - BASIS_HEADER_SIZE) as u32).to_le_bytes());
+    file.extend_from_slice(&0u16.to_le_bytes());
+    file.extend_from_slice(&(slice_count as u32).to_le_bytes()[..3]);
+    file.extend_from_slice(&image.layers.to_le_bytes()[..3]);
+    file.extend_from_slice(&[0]);
+    file.extend_from_slice(&flags.to_le_bytes());
+    file.extend_from_slice(&[texture_type]);
+    file.extend_from_slice(&[0; 3 + 3 * 4]);
+    file.extend_from_slice(&endpoint_count.to_le_bytes());
+    file.extend_from_slice(&endpoints_offset.to_le_bytes());
+    file.extend_from_slice(&(endpoints_size as u32).to_le_bytes()[..3]);
+    file.extend_from_slice(&selector_count.to_le_bytes());
+    file.extend_from_slice(&selectors_offset.to_le_bytes());
+    file.extend_from_slice(&(selectors_size as u32).to_le_bytes()[..3]);
+    file.extend_from_slice(&tables_offset.to_le_bytes());
+    file.extend_from_slice(&(tables_size as u32).to_le_bytes());
+    file.extend_from_slice(&(BASIS_HEADER_SIZE as u32).to_le_bytes());
+    file.extend_from_slice(&[0; 2 * 4]);
+
+    file.extend(slices);
+    file.extend_from_slice(codebooks);
+    for level in &image.levels {
+        file.extend_from_slice(level);
+    }
+
+    Ok(file)
+}
+
+// ASTC keeps the quality of UASTC, BC and ETC2 are preferred to `Rgba8` for the memory
+fn target_format(
+    channels: BasisChannels,
+    features: wgpu::Features,
+) -> (
+    TranscoderBlockFormat,
+    TranscoderTextureFormat,
+    wgpu::TextureFormat,
+) {
+    use wgpu::{AstcBlock, AstcChannel, Features, TextureFormat::*};
+    use TranscoderBlockFormat as Block;
+    use TranscoderTextureFormat as Texture;
+
+    let bc = features.contains(Features::TEXTURE_COMPRESSION_BC);
+    let etc2 = features.contains(Features::TEXTURE_COMPRESSION_ETC2);
+    let astc = features.contains(Features::TEXTURE_COMPRESSION_ASTC);
+
+    match channels {
+        BasisChannels::R if bc => (Block::BC4, Texture::BC4_R, Bc4RUnorm),
+        BasisChannels::R if etc2 => (Block::ETC2_EAC_R11, Texture::ETC2_EAC_R11, EacR11Unorm),
+        BasisChannels::Rg if bc => (Block::BC5, Texture::BC5_RG, Bc5RgUnorm),
+        BasisChannels::Rg if etc2 => (Block::ETC2_EAC_RG11, Texture::ETC2_EAC_RG11, EacRg11Unorm),
+        BasisChannels::Rgb | BasisChannels::Rgba if astc => (
+            Block::ASTC_4x4,
+            Texture::ASTC_4x4_RGBA,
+            Astc {
+                block: AstcBlock::B4x4,
+                channel: AstcChannel::Unorm,
+            },
+        ),
+        BasisChannels::Rgb | BasisChannels::Rgba if bc => {
+            (Block::BC7, Texture::BC7_RGBA, Bc7RgbaUnorm)
+        }
+        BasisChannels::Rgb | BasisChannels::Rgba if etc2 => {
+            (Block::ETC2_RGBA, Texture::ETC2_RGBA, Etc2Rgba8Unorm)
+        }
+        _ => (Block::RGBA32, Texture::RGBA32, Rgba8Unorm),
+    }
+}
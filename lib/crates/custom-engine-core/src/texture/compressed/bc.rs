@@ -0,0 +1,779 @@
+// Subsets of the texels in the 2-subset partitions, one bit per texel
+const PARTITIONS_2: [u16; 64] = [
+    0xcccc, 0x8888, 0xeeee, 0xecc8, 0xc880, 0xfeec, 0xfec8, 0xec80, 0xc800, 0xffec, 0xfe80, 0xe800,
+    0xffe8, 0xff00, 0xfff0, 0xf000, 0xf710, 0x008e, 0x7100, 0x08ce, 0x008c, 0x7310, 0x3100, 0x8cce,
+    0x088c, 0x3110, 0x6666, 0x366c, 0x17e8, 0x0ff0, 0x718e, 0x399c, 0xaaaa, 0xf0f0, 0x5a5a, 0x33cc,
+    0x3c3c, 0x55aa, 0x9696, 0xa55a, 0x73ce, 0x13c8, 0x324c, 0x3bdc, 0x6996, 0xc33c, 0x9966, 0x0660,
+    0x0272, 0x04e4, 0x4e40, 0x2720, 0xc936, 0x936c, 0x39c6, 0x639c, 0x9336, 0x9cc6, 0x817e, 0xe718,
+    0xccf0, 0x0fcc, 0x7744, 0xee22,
+];
+
+// Subsets of the texels in the 3-subset partitions, two bits per texel
+const PARTITIONS_3: [u32; 64] = [
+    0xaa685050, 0x6a5a5040, 0x5a5a4200, 0x5450a0a8, 0xa5a50000, 0xa0a05050, 0x5555a0a0, 0x5a5a5050,
+    0xaa550000, 0xaa555500, 0xaaaa5500, 0x90909090, 0x94949494, 0xa4a4a4a4, 0xa9a59450, 0x2a0a4250,
+    0xa5945040, 0x0a425054, 0xa5a5a500, 0x55a0a0a0, 0xa8a85454, 0x6a6a4040, 0xa4a45000, 0x1a1a0500,
+    0x0050a4a4, 0xaaa59090, 0x14696914, 0x69691400, 0xa08585a0, 0xaa821414, 0x50a4a450, 0x6a5a0200,
+    0xa9a58000, 0x5090a0a8, 0xa8a09050, 0x24242424, 0x00aa5500, 0x24924924, 0x24499224, 0x50a50a50,
+    0x500aa550, 0xaaaa4444, 0x66660000, 0xa5a0a5a0, 0x50a050a0, 0x69286928, 0x44aaaa44, 0x66666600,
+    0xaa444444, 0x54a854a8, 0x95809580, 0x96969600, 0xa85454a8, 0x80959580, 0xaa141414, 0x96960000,
+    0xaaaa1414, 0xa05050a0, 0xa0a5a5a0, 0x96000000, 0x40804080, 0xa9a8a9a8, 0xaaaaaa44, 0x2a4a5254,
+];
+
+// Anchor texels of the second subset, their indices lose the high bit
+const ANCHORS_2: [u8; 64] = [
+    15, 15, 15, 15, 15, 15, 15, 15, 15, 15, 15, 15, 15, 15, 15, 15, 15, 2, 8, 2, 2, 8, 8, 15, 2, 8,
+    2, 2, 8, 8, 2, 2, 15, 15, 6, 8, 2, 8, 15, 15, 2, 8, 2, 2, 2, 15, 15, 6, 6, 2, 6, 8, 15, 15, 2,
+    2, 15, 15, 15, 15, 15, 2, 2, 15,
+];
+
+const ANCHORS_3_SECOND: [u8; 64] = [
+    3, 3, 15, 15, 8, 3, 15, 15, 8, 8, 6, 6, 6, 5, 3, 3, 3, 3, 8, 15, 3, 3, 6, 10, 5, 8, 8, 6, 8, 5,
+    15, 15, 8, 15, 3, 5, 6, 10, 8, 15, 15, 3, 15, 5, 15, 15, 15, 15, 3, 15, 5, 5, 5, 8, 5, 10, 5,
+    10, 8, 13, 15, 12, 3, 3,
+];
+
+const ANCHORS_3_THIRD: [u8; 64] = [
+    15, 8, 8, 3, 15, 15, 3, 8, 15, 15, 15, 15, 15, 15, 15, 8, 15, 8, 15, 3, 15, 8, 15, 8, 3, 15, 6,
+    10, 15, 15, 10, 8, 15, 3, 15, 10, 10, 8, 9, 10, 6, 15, 8, 15, 3, 6, 6, 8, 15, 3, 15, 15, 15,
+    15, 15, 15, 15, 15, 15, 15, 3, 15, 15, 8,
+];
+
+const WEIGHTS_2: [u16; 4] = [0, 21, 43, 64];
+const WEIGHTS_3: [u16; 8] = [0, 9, 18, 27, 37, 46, 55, 64];
+const WEIGHTS_4: [u16; 16] = [0, 4, 9, 13, 17, 21, 26, 30, 34, 38, 43, 47, 51, 55, 60, 64];
+
+struct Bc7Mode {
+    subsets: usize,
+    partition_bits: u32,
+    rotation_bits: u32,
+    index_selection_bits: u32,
+    color_bits: u32,
+    alpha_bits: u32,
+    endpoint_p_bits: bool,
+    shared_p_bits: bool,
+    index_bits: u32,
+    // Modes 4 and 5 store the color and the alpha with separate indices
+    second_index_bits: u32,
+}
+
+const BC7_MODES: [Bc7Mode; 8] = [
+    Bc7Mode {
+        subsets: 3,
+        partition_bits: 4,
+        rotation_bits: 0,
+        index_selection_bits: 0,
+        color_bits: 4,
+        alpha_bits: 0,
+        endpoint_p_bits: true,
+        shared_p_bits: false,
+        index_bits: 3,
+        second_index_bits: 0,
+    },
+    Bc7Mode {
+        subsets: 2,
+        partition_bits: 6,
+        rotation_bits: 0,
+        index_selection_bits: 0,
+        color_bits: 6,
+        alpha_bits: 0,
+        endpoint_p_bits: false,
+        shared_p_bits: true,
+        index_bits: 3,
+        second_index_bits: 0,
+    },
+    Bc7Mode {
+        subsets: 3,
+        partition_bits: 6,
+        rotation_bits: 0,
+        index_selection_bits: 0,
+        color_bits: 5,
+        alpha_bits: 0,
+        endpoint_p_bits: false,
+        shared_p_bits: false,
+        index_bits: 2,
+        second_index_bits: 0,
+    },
+    Bc7Mode {
+        subsets: 2,
+        partition_bits: 6,
+        rotation_bits: 0,
+        index_selection_bits: 0,
+        color_bits: 7,
+        alpha_bits: 0,
+        endpoint_p_bits: true,
+        shared_p_bits: false,
+        index_bits: 2,
+        second_index_bits: 0,
+    },
+    Bc7Mode {
+        subsets: 1,
+        partition_bits: 0,
+        rotation_bits: 2,
+        index_selection_bits: 1,
+        color_bits: 5,
+        alpha_bits: 6,
+        endpoint_p_bits: false,
+        shared_p_bits: false,
+        index_bits: 2,
+        second_index_bits: 3,
+    },
+    Bc7Mode {
+        subsets: 1,
+        partition_bits: 0,
+        rotation_bits: 2,
+        index_selection_bits: 0,
+        color_bits: 7,
+        alpha_bits: 8,
+        endpoint_p_bits: false,
+        shared_p_bits: false,
+        index_bits: 2,
+        second_index_bits: 2,
+    },
+    Bc7Mode {
+        subsets: 1,
+        partition_bits: 0,
+        rotation_bits: 0,
+        index_selection_bits: 0,
+        color_bits: 7,
+        alpha_bits: 7,
+        endpoint_p_bits: true,
+        shared_p_bits: false,
+        index_bits: 4,
+        second_index_bits: 0,
+    },
+    Bc7Mode {
+        subsets: 2,
+        partition_bits: 6,
+        rotation_bits: 0,
+        index_selection_bits: 0,
+        color_bits: 5,
+        alpha_bits: 5,
+        endpoint_p_bits: true,
+        shared_p_bits: false,
+        index_bits: 2,
+        second_index_bits: 0,
+    },
+];
+
+const HALF_ONE: u16 = 0x3c00;
+
+struct Bc6hMode {
+    transformed: bool,
+    endpoint_bits: u32,
+    delta_bits: [u32; 3],
+    subsets: usize,
+    // Bits of the endpoints after the mode by triples: channel of the endpoint, lowest bit and count
+    fields: &'static [u8],
+}
+
+// Channels of the endpoints in `Bc6hMode::fields`
+const R0: u8 = 0;
+const G0: u8 = 1;
+const B0: u8 = 2;
+const R1: u8 = 3;
+const G1: u8 = 4;
+const B1: u8 = 5;
+const R2: u8 = 6;
+const G2: u8 = 7;
+const B2: u8 = 8;
+const R3: u8 = 9;
+const G3: u8 = 10;
+const B3: u8 = 11;
+
+fn bc6h_mode(mode: u8) -> Option<Bc6hMode> {
+    let (transformed, endpoint_bits, delta_bits, subsets, fields): (_, _, _, _, &[_]) = match mode {
+        0 => (
+            true,
+            10,
+            [5, 5, 5],
+            2,
+            &[
+                G2, 4, 1, B2, 4, 1, B3, 4, 1, R0, 0, 10, G0, 0, 10, B0, 0, 10, R1, 0, 5, G3, 4, 1,
+                G2, 0, 4, G1, 0, 5, B3, 0, 1, G3, 0, 4, B1, 0, 5, B3, 1, 1, B2, 0, 4, R2, 0, 5, B3,
+                2, 1, R3, 0, 5, B3, 3, 1,
+            ],
+        ),
+        1 => (
+            true,
+            7,
+            [6, 6, 6],
+            2,
+            &[
+                G2, 5, 1, G3, 4, 1, G3, 5, 1, R0, 0, 7, B3, 0, 1, B3, 1, 1, B2, 4, 1, G0, 0, 7, B2,
+                5, 1, B3, 2, 1, G2, 4, 1, B0, 0, 7, B3, 3, 1, B3, 5, 1, B3, 4, 1, R1, 0, 6, G2, 0,
+                4, G1, 0, 6, G3, 0, 4, B1, 0, 6, B2, 0, 4, R2, 0, 6, R3, 0, 6,
+            ],
+        ),
+        2 => (
+            true,
+            11,
+            [5, 4, 4],
+            2,
+            &[
+                R0, 0, 10, G0, 0, 10, B0, 0, 10, R1, 0, 5, R0, 10, 1, G2, 0, 4, G1, 0, 4, G0, 10,
+                1, B3, 0, 1, G3, 0, 4, B1, 0, 4, B0, 10, 1, B3, 1, 1, B2, 0, 4, R2, 0, 5, B3, 2, 1,
+                R3, 0, 5, B3, 3, 1,
+            ],
+        ),
+        6 => (
+            true,
+            11,
+            [4, 5, 4],
+            2,
+            &[
+                R0, 0, 10, G0, 0, 10, B0, 0, 10, R1, 0, 4, R0, 10, 1, G3, 4, 1, G2, 0, 4, G1, 0, 5,
+                G0, 10, 1, G3, 0, 4, B1, 0, 4, B0, 10, 1, B3, 1, 1, B2, 0, 4, R2, 0, 4, B3, 0, 1,
+                B3, 2, 1, R3, 0, 4, G2, 4, 1, B3, 3, 1,
+            ],
+        ),
+        10 => (
+            true,
+            11,
+            [4, 4, 5],
+            2,
+            &[
+                R0, 0, 10, G0, 0, 10, B0, 0, 10, R1, 0, 4, R0, 10, 1, B2, 4, 1, G2, 0, 4, G1, 0, 4,
+                G0, 10, 1, B3, 0, 1, G3, 0, 4, B1, 0, 5, B0, 10, 1, B2, 0, 4, R2, 0, 4, B3, 1, 1,
+                B3, 2, 1, R3, 0, 4, B3, 4, 1, B3, 3, 1,
+            ],
+        ),
+        14 => (
+            true,
+            9,
+            [5, 5, 5],
+            2,
+            &[
+                R0, 0, 9, B2, 4, 1, G0, 0, 9, G2, 4, 1, B0, 0, 9, B3, 4, 1, R1, 0, 5, G3, 4, 1, G2,
+                0, 4, G1, 0, 5, B3, 0, 1, G3, 0, 4, B1, 0, 5, B3, 1, 1, B2, 0, 4, R2, 0, 5, B3, 2,
+                1, R3, 0, 5, B3, 3, 1,
+            ],
+        ),
+        18 => (
+            true,
+            8,
+            [6, 5, 5],
+            2,
+            &[
+                R0, 0, 8, G3, 4, 1, B2, 4, 1, G0, 0, 8, B3, 2, 1, G2, 4, 1, B0, 0, 8, B3, 3, 1, B3,
+                4, 1, R1, 0, 6, G2, 0, 4, G1, 0, 5, B3, 0, 1, G3, 0, 4, B1, 0, 5, B3, 1, 1, B2, 0,
+                4, R2, 0, 6, R3, 0, 6,
+            ],
+        ),
+        22 => (
+            true,
+            8,
+            [5, 6, 5],
+            2,
+            &[
+                R0, 0, 8, B3, 0, 1, B2, 4, 1, G0, 0, 8, G2, 5, 1, G2, 4, 1, B0, 0, 8, G3, 5, 1, B3,
+                4, 1, R1, 0, 5, G3, 4, 1, G2, 0, 4, G1, 0, 6, G3, 0, 4, B1, 0, 5, B3, 1, 1, B2, 0,
+                4, R2, 0, 5, B3, 2, 1, R3, 0, 5, B3, 3, 1,
+            ],
+        ),
+        26 => (
+            true,
+            8,
+            [5, 5, 6],
+            2,
+            &[
+                R0, 0, 8, B3, 1, 1, B2, 4, 1, G0, 0, 8, B2, 5, 1, G2, 4, 1, B0, 0, 8, B3, 5, 1, B3,
+                4, 1, R1, 0, 5, G3, 4, 1, G2, 0, 4, G1, 0, 5, B3, 0, 1, G3, 0, 4, B1, 0, 6, B2, 0,
+                4, R2, 0, 5, B3, 2, 1, R3, 0, 5, B3, 3, 1,
+            ],
+        ),
+        30 => (
+            false,
+            6,
+            [6, 6, 6],
+            2,
+            &[
+                R0, 0, 6, G3, 4, 1, B3, 0, 1, B3, 1, 1, B2, 4, 1, G0, 0, 6, G2, 5, 1, B2, 5, 1, B3,
+                2, 1, G2, 4, 1, B0, 0, 6, G3, 5, 1, B3, 3, 1, B3, 5, 1, B3, 4, 1, R1, 0, 6, G2, 0,
+                4, G1, 0, 6, G3, 0, 4, B1, 0, 6, B2, 0, 4, R2, 0, 6, R3, 0, 6,
+            ],
+        ),
+        3 => (
+            false,
+            10,
+            [10, 10, 10],
+            1,
+            &[
+                R0, 0, 10, G0, 0, 10, B0, 0, 10, R1, 0, 10, G1, 0, 10, B1, 0, 10,
+            ],
+        ),
+        7 => (
+            true,
+            11,
+            [9, 9, 9],
+            1,
+            &[
+                R0, 0, 10, G0, 0, 10, B0, 0, 10, R1, 0, 9, R0, 10, 1, G1, 0, 9, G0, 10, 1, B1, 0,
+                9, B0, 10, 1,
+            ],
+        ),
+        // High bits of the first endpoint are stored reversed
+        11 => (
+            true,
+            12,
+            [8, 8, 8],
+            1,
+            &[
+                R0, 0, 10, G0, 0, 10, B0, 0, 10, R1, 0, 8, R0, 11, 1, R0, 10, 1, G1, 0, 8, G0, 11,
+                1, G0, 10, 1, B1, 0, 8, B0, 11, 1, B0, 10, 1,
+            ],
+        ),
+        15 => (
+            true,
+            16,
+            [4, 4, 4],
+            1,
+            &[
+                R0, 0, 10, G0, 0, 10, B0, 0, 10, R1, 0, 4, R0, 15, 1, R0, 14, 1, R0, 13, 1, R0, 12,
+                1, R0, 11, 1, R0, 10, 1, G1, 0, 4, G0, 15, 1, G0, 14, 1, G0, 13, 1, G0, 12, 1, G0,
+                11, 1, G0, 10, 1, B1, 0, 4, B0, 15, 1, B0, 14, 1, B0, 13, 1, B0, 12, 1, B0, 11, 1,
+                B0, 10, 1,
+            ],
+        ),
+        _ => return None,
+    };
+
+    Some(Bc6hMode {
+        transformed,
+        endpoint_bits,
+        delta_bits,
+        subsets,
+        fields,
+    })
+}
+
+// Decodes the block to `Rgba8` texels, snorm formats store `i8` values
+pub fn decode(format: wgpu::TextureFormat, block: &[u8]) -> Option<[[u8; 4]; 16]> {
+    use wgpu::TextureFormat::*;
+
+    let texels = match format {
+        Bc1RgbaUnorm | Bc1RgbaUnormSrgb => decode_bc1(block, true),
+        Bc2RgbaUnorm | Bc2RgbaUnormSrgb => {
+            let mut texels = decode_bc1(&block[8..], false);
+            for (i, texel) in texels.iter_mut().enumerate() {
+                let alpha = (block[i / 2] >> (4 * (i % 2))) & 0xf;
+                texel[3] = alpha * 17;
+            }
+
+            texels
+        }
+        Bc3RgbaUnorm | Bc3RgbaUnormSrgb => {
+            let mut texels = decode_bc1(&block[8..], false);
+            for (texel, alpha) in texels.iter_mut().zip(decode_bc4(block)) {
+                texel[3] = alpha;
+            }
+
+            texels
+        }
+        Bc4RUnorm => decode_bc4(block).map(|r| [r, 0, 0, 255]),
+        Bc4RSnorm => decode_bc4_snorm(block).map(|r| [r as u8, 0, 0, 127]),
+        Bc5RgUnorm => {
+            let green = decode_bc4(&block[8..]);
+            let mut texels = decode_bc4(block).map(|r| [r, 0, 0, 255]);
+            for (texel, g) in texels.iter_mut().zip(green) {
+                texel[1] = g;
+            }
+
+            texels
+        }
+        Bc5RgSnorm => {
+            let green = decode_bc4_snorm(&block[8..]);
+            let mut texels = decode_bc4_snorm(block).map(|r| [r as u8, 0, 0, 127]);
+            for (texel, g) in texels.iter_mut().zip(green) {
+                texel[1] = g as u8;
+            }
+
+            texels
+        }
+        Bc7RgbaUnorm | Bc7RgbaUnormSrgb => decode_bc7(block),
+        _ => return None,
+    };
+
+    Some(texels)
+}
+
+fn decode_bc1(block: &[u8], punch_through: bool) -> [[u8; 4]; 16] {
+    let c0 = u16::from_le_bytes([block[0], block[1]]);
+    let c1 = u16::from_le_bytes([block[2], block[3]]);
+    let indices = u32::from_le_bytes([block[4], block[5], block[6], block[7]]);
+
+    let (rgb0, rgb1) = (rgb565(c0), rgb565(c1));
+    let mix = |w0: u16, w1: u16, d: u16| {
+        let mut rgb = [0; 4];
+        for i in 0..3 {
+            rgb[i] = ((rgb0[i] as u16 * w0 + rgb1[i] as u16 * w1) / d) as u8;
+        }
+        rgb[3] = 255;
+
+        rgb
+    };
+    let palette = if c0 > c1 || !punch_through {
+        [rgb0, rgb1, mix(2, 1, 3), mix(1, 2, 3)]
+    } else {
+        [rgb0, rgb1, mix(1, 1, 2), [0; 4]]
+    };
+
+    std::array::from_fn(|i| palette[(indices >> (2 * i)) as usize & 0x3])
+}
+
+fn decode_bc4(block: &[u8]) -> [u8; 16] {
+    let (a0, a1) = (block[0] as u16, block[1] as u16);
+    let indices = bc4_indices(block);
+
+    let mut palette = [a0, a1, 0, 0, 0, 0, 0, 0];
+    if a0 > a1 {
+        for (i, p) in palette.iter_mut().enumerate().skip(2) {
+            *p = ((8 - i as u16) * a0 + (i as u16 - 1) * a1) / 7;
+        }
+    } else {
+        for (i, p) in palette.iter_mut().enumerate().take(6).skip(2) {
+            *p = ((6 - i as u16) * a0 + (i as u16 - 1) * a1) / 5;
+        }
+        palette[7] = 255;
+    }
+
+    std::array::from_fn(|i| palette[(indices >> (3 * i)) as usize & 0x7] as u8)
+}
+
+// Endpoints are signed, -128 is clamped to -127 like -1.0
+fn decode_bc4_snorm(block: &[u8]) -> [i8; 16] {
+    let (a0, a1) = (
+        (block[0] as i8).max(-127) as i16,
+        (block[1] as i8).max(-127) as i16,
+    );
+    let indices = bc4_indices(block);
+
+    let mut palette = [a0, a1, 0, 0, 0, 0, 0, 0];
+    if a0 > a1 {
+        for (i, p) in palette.iter_mut().enumerate().skip(2) {
+            *p = ((8 - i as i16) * a0 + (i as i16 - 1) * a1) / 7;
+        }
+    } else {
+        for (i, p) in palette.iter_mut().enumerate().take(6).skip(2) {
+            *p = ((6 - i as i16) * a0 + (i as i16 - 1) * a1) / 5;
+        }
+        palette[6] = -127;
+        palette[7] = 127;
+    }
+
+    std::array::from_fn(|i| palette[(indices >> (3 * i)) as usize & 0x7] as i8)
+}
+
+fn bc4_indices(block: &[u8]) -> u64 {
+    block[2..8]
+        .iter()
+        .rev()
+        .fold(0u64, |bits, &b| bits << 8 | b as u64)
+}
+
+fn rgb565(color: u16) -> [u8; 4] {
+    let r = (color >> 11) & 0x1f;
+    let g = (color >> 5) & 0x3f;
+    let b = color & 0x1f;
+
+    [
+        (r << 3 | r >> 2) as u8,
+        (g << 2 | g >> 4) as u8,
+        (b << 3 | b >> 2) as u8,
+        255,
+    ]
+}
+
+// Reads the fields of the block from the lowest bit
+struct BlockBits {
+    bits: u128,
+    offset: u32,
+}
+
+impl BlockBits {
+    fn read(&mut self, count: u32) -> u8 {
+        self.read_wide(count) as u8
+    }
+
+    fn read_wide(&mut self, count: u32) -> u16 {
+        let value = (self.bits >> self.offset) & ((1 << count) - 1);
+        self.offset += count;
+
+        value as u16
+    }
+}
+
+fn decode_bc7(block: &[u8]) -> [[u8; 4]; 16] {
+    let mut bytes = [0; 16];
+    bytes.copy_from_slice(&block[..16]);
+    let bits = u128::from_le_bytes(bytes);
+
+    // The mode is the count of the low zero bits, blocks without the mode are transparent black
+    let mode_index = bits.trailing_zeros();
+    let Some(mode) = BC7_MODES.get(mode_index as usize) else {
+        return [[0; 4]; 16];
+    };
+    let mut reader = BlockBits {
+        bits,
+        offset: mode_index + 1,
+    };
+
+    let partition = reader.read(mode.partition_bits) as usize;
+    let rotation = reader.read(mode.rotation_bits);
+    let index_selection = reader.read(mode.index_selection_bits);
+
+    // Channels of all endpoints are stored in turn: reds, greens, blues and alphas
+    let mut channel_bits = [
+        mode.color_bits,
+        mode.color_bits,
+        mode.color_bits,
+        mode.alpha_bits,
+    ];
+    let mut endpoints = [[[0u8; 4]; 2]; 3];
+    for (channel, &bits) in channel_bits.iter().enumerate() {
+        for subset in endpoints.iter_mut().take(mode.subsets) {
+            for endpoint in subset.iter_mut() {
+                endpoint[channel] = reader.read(bits);
+            }
+        }
+    }
+
+    // P-bits are the lowest bits of the endpoints, they're unique or shared by the subset
+    if mode.endpoint_p_bits || mode.shared_p_bits {
+        for subset in endpoints.iter_mut().take(mode.subsets) {
+            let shared = if mode.shared_p_bits {
+                reader.read(1)
+            } else {
+                0
+            };
+            for endpoint in subset.iter_mut() {
+                let p_bit = if mode.endpoint_p_bits {
+                    reader.read(1)
+                } else {
+                    shared
+                };
+                for (value, &bits) in endpoint.iter_mut().zip(&channel_bits) {
+                    if bits > 0 {
+                        *value = *value << 1 | p_bit;
+                    }
+                }
+            }
+        }
+        for bits in channel_bits.iter_mut().filter(|b| **b > 0) {
+            *bits += 1;
+        }
+    }
+    for endpoint in endpoints.iter_mut().flatten() {
+        for (value, &bits) in endpoint.iter_mut().zip(&channel_bits) {
+            *value = expand_bits(*value, bits);
+        }
+    }
+
+    let subset = |i: usize| match mode.subsets {
+        2 => (PARTITIONS_2[partition] >> i) as usize & 0x1,
+        3 => (PARTITIONS_3[partition] >> (2 * i)) as usize & 0x3,
+        _ => 0,
+    };
+    let anchors = match mode.subsets {
+        2 => [0, ANCHORS_2[partition] as usize, 0],
+        3 => [
+            0,
+            ANCHORS_3_SECOND[partition] as usize,
+            ANCHORS_3_THIRD[partition] as usize,
+        ],
+        _ => [0; 3],
+    };
+
+    let indices: [u8; 16] = std::array::from_fn(|i| {
+        let is_anchor = anchors[..mode.subsets].contains(&i);
+        reader.read(mode.index_bits - is_anchor as u32)
+    });
+    let second_indices: [u8; 16] = std::array::from_fn(|i| {
+        if mode.second_index_bits == 0 {
+            return 0;
+        }
+        reader.read(mode.second_index_bits - (i == 0) as u32)
+    });
+
+    std::array::from_fn(|i| {
+        let [e0, e1] = endpoints[subset(i)];
+        let (color, alpha) = match (mode.second_index_bits, index_selection) {
+            (0, _) => ((indices[i], mode.index_bits), (indices[i], mode.index_bits)),
+            (bits, 0) => ((indices[i], mode.index_bits), (second_indices[i], bits)),
+            (bits, _) => ((second_indices[i], bits), (indices[i], mode.index_bits)),
+        };
+
+        let mut texel = [255; 4];
+        for c in 0..3 {
+            texel[c] = interpolate(e0[c], e1[c], color);
+        }
+        if mode.alpha_bits > 0 {
+            texel[3] = interpolate(e0[3], e1[3], alpha);
+        }
+        match rotation {
+            1 => texel.swap(0, 3),
+            2 => texel.swap(1, 3),
+            3 => texel.swap(2, 3),
+            _ => {}
+        }
+
+        texel
+    })
+}
+
+// Decodes the BC6H block to `Rgba16Float` texels
+pub fn decode_bc6h(format: wgpu::TextureFormat, block: &[u8]) -> Option<[[u16; 4]; 16]> {
+    let signed = match format {
+        wgpu::TextureFormat::Bc6hRgbUfloat => false,
+        wgpu::TextureFormat::Bc6hRgbFloat => true,
+        _ => return None,
+    };
+
+    let mut bytes = [0; 16];
+    bytes.copy_from_slice(&block[..16]);
+    let mut reader = BlockBits {
+        bits: u128::from_le_bytes(bytes),
+        offset: 0,
+    };
+
+    // Modes have 2 bits or 5 bits when the second bit is set, reserved modes are black
+    let mode = match reader.read(2) {
+        mode @ (0 | 1) => mode,
+        mode => mode | reader.read(3) << 2,
+    };
+    let Some(mode) = bc6h_mode(mode) else {
+        return Some([[0, 0, 0, HALF_ONE]; 16]);
+    };
+
+    let mut values = [0i32; 12];
+    for field in mode.fields.chunks(3) {
+        values[field[0] as usize] |= (reader.read_wide(field[2] as u32) as i32) << field[1];
+    }
+    let partition = if mode.subsets == 2 {
+        reader.read(5) as usize
+    } else {
+        0
+    };
+
+    // Other endpoints are the deltas of the first one in the transformed modes
+    let endpoint_count = 2 * mode.subsets;
+    let mut endpoints = [[0i32; 3]; 4];
+    for (e, endpoint) in endpoints.iter_mut().enumerate().take(endpoint_count) {
+        for (c, value) in endpoint.iter_mut().enumerate() {
+            *value = values[3 * e + c];
+            if e == 0 && signed {
+                *value = sign_extend(*value, mode.endpoint_bits);
+            } else if e > 0 && (signed || mode.transformed) {
+                *value = sign_extend(*value, mode.delta_bits[c]);
+            }
+        }
+    }
+    if mode.transformed {
+        let mask = (1 << mode.endpoint_bits) - 1;
+        let [first, others @ ..] = &mut endpoints;
+        for endpoint in others.iter_mut().take(endpoint_count - 1) {
+            for (value, base) in endpoint.iter_mut().zip(*first) {
+                *value = (base + *value) & mask;
+                if signed {
+                    *value = sign_extend(*value, mode.endpoint_bits);
+                }
+            }
+        }
+    }
+    for value in endpoints.iter_mut().flatten() {
+        *value = bc6h_unquantize(*value, mode.endpoint_bits, signed);
+    }
+
+    let (index_bits, anchor) = if mode.subsets == 2 {
+        (3, ANCHORS_2[partition] as usize)
+    } else {
+        (4, 0)
+    };
+    let indices: [u8; 16] = std::array::from_fn(|i| {
+        let is_anchor = i == 0 || i == anchor;
+        reader.read(index_bits - is_anchor as u32)
+    });
+
+    Some(std::array::from_fn(|i| {
+        let subset = if mode.subsets == 2 {
+            (PARTITIONS_2[partition] >> i) as usize & 0x1
+        } else {
+            0
+        };
+        let weight = match index_bits {
+            3 => WEIGHTS_3[indices[i] as usize],
+            _ => WEIGHTS_4[indices[i] as usize],
+        } as i32;
+        let (e0, e1) = (endpoints[2 * subset], endpoints[2 * subset + 1]);
+
+        let mut texel = [0, 0, 0, HALF_ONE];
+        for c in 0..3 {
+            let value = (e0[c] * (64 - weight) + e1[c] * weight + 32) >> 6;
+            // Values are scaled to the finite half floats
+            texel[c] = if !signed {
+                ((value * 31) >> 6) as u16
+            } else if value < 0 {
+                ((-value * 31) >> 5) as u16 | 0x8000
+            } else {
+                ((value * 31) >> 5) as u16
+            };
+        }
+
+        texel
+    }))
+}
+
+fn sign_extend(value: i32, bits: u32) -> i32 {
+    value << (32 - bits) >> (32 - bits)
+}
+
+// Endpoints are scaled to 16 bits, the signed ones keep the sign
+fn bc6h_unquantize(value: i32, bits: u32, signed: bool) -> i32 {
+    if !signed {
+        return if bits >= 15 || value == 0 {
+            value
+        } else if value == (1 << bits) - 1 {
+            0xffff
+        } else {
+            ((value << 16) + 0x8000) >> bits
+        };
+    }
+    if bits >= 16 {
+        return value;
+    }
+
+    let magnitude = value.abs();
+    let unquantized = if magnitude == 0 {
+        0
+    } else if magnitude >= (1 << (bits - 1)) - 1 {
+        0x7fff
+    } else {
+        ((magnitude << 15) + 0x4000) >> (bits - 1)
+    };
+
+    if value < 0 {
+        -unquantized
+    } else {
+        unquantized
+    }
+}
+
+// Replicates the high bits of the value to the low bits of the byte
+fn expand_bits(value: u8, bits: u32) -> u8 {
+    if bits == 0 {
+        return 255;
+    }
+    let value = value as u16;
+
+    (value << (8 - bits) | value >> (2 * bits - 8)) as u8
+}
+
+fn interpolate(e0: u8, e1: u8, (index, bits): (u8, u32)) -> u8 {
+    let weight = match bits {
+        2 => WEIGHTS_2[index as usize],
+        3 => WEIGHTS_3[index as usize],
+        _ => WEIGHTS_4[index as usize],
+    };
+
+    (((64 - weight) * e0 as u16 + weight * e1 as u16 + 32) >> 6) as u8
+}
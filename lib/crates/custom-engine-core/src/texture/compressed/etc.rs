@@ -0,0 +1,308 @@
+const ETC1_MODIFIERS: [[i32; 2]; 8] = [
+    [2, 8],
+    [5, 17],
+    [9, 29],
+    [13, 42],
+    [18, 60],
+    [24, 80],
+    [33, 106],
+    [47, 183],
+];
+
+// Distances of the paint colors in the T and H modes
+const ETC2_DISTANCES: [i32; 8] = [3, 6, 11, 16, 23, 32, 41, 64];
+
+const EAC_MODIFIERS: [[i32; 8]; 16] = [
+    [-3, -6, -9, -15, 2, 5, 8, 14],
+    [-3, -7, -10, -13, 2, 6, 9, 12],
+    [-2, -5, -8, -13, 1, 4, 7, 12],
+    [-2, -4, -6, -13, 1, 3, 5, 12],
+    [-3, -6, -8, -12, 2, 5, 7, 11],
+    [-3, -7, -9, -11, 2, 6, 8, 10],
+    [-4, -7, -8, -11, 3, 6, 7, 10],
+    [-3, -5, -8, -11, 2, 4, 7, 10],
+    [-2, -6, -8, -10, 1, 5, 7, 9],
+    [-2, -5, -8, -10, 1, 4, 7, 9],
+    [-2, -4, -8, -10, 1, 3, 7, 9],
+    [-2, -5, -7, -10, 1, 4, 6, 9],
+    [-3, -4, -7, -10, 2, 3, 6, 9],
+    [-1, -2, -3, -10, 0, 1, 2, 9],
+    [-4, -6, -8, -9, 3, 5, 7, 8],
+    [-3, -5, -7, -9, 2, 4, 6, 8],
+];
+
+// Decodes the block to `Rgba8` texels, snorm formats store `i8` values
+pub fn decode(format: wgpu::TextureFormat, block: &[u8]) -> Option<[[u8; 4]; 16]> {
+    use wgpu::TextureFormat::*;
+
+    let texels = match format {
+        Etc2Rgb8Unorm | Etc2Rgb8UnormSrgb => decode_etc2(block, false),
+        Etc2Rgb8A1Unorm | Etc2Rgb8A1UnormSrgb => decode_etc2(block, true),
+        Etc2Rgba8Unorm | Etc2Rgba8UnormSrgb => {
+            let mut texels = decode_etc2(&block[8..], false);
+            for (texel, alpha) in texels.iter_mut().zip(decode_eac(block)) {
+                texel[3] = alpha;
+            }
+
+            texels
+        }
+        EacR11Unorm => decode_eac11(block, false).map(|r| [unorm11(r), 0, 0, 255]),
+        EacR11Snorm => decode_eac11(block, true).map(|r| [snorm11(r), 0, 0, 127]),
+        EacRg11Unorm => {
+            let green = decode_eac11(&block[8..], false);
+            let mut texels = decode_eac11(block, false).map(|r| [unorm11(r), 0, 0, 255]);
+            for (texel, g) in texels.iter_mut().zip(green) {
+                texel[1] = unorm11(g);
+            }
+
+            texels
+        }
+        EacRg11Snorm => {
+            let green = decode_eac11(&block[8..], true);
+            let mut texels = decode_eac11(block, true).map(|r| [snorm11(r), 0, 0, 127]);
+            for (texel, g) in texels.iter_mut().zip(green) {
+                texel[1] = snorm11(g);
+            }
+
+            texels
+        }
+        _ => return None,
+    };
+
+    Some(texels)
+}
+
+// Blocks are big endian, the indices of the texels go by columns
+fn texel_indices(low: u32) -> [usize; 16] {
+    std::array::from_fn(|i| {
+        let p = i % 4 * 4 + i / 4;
+        ((low >> (16 + p) & 1) << 1 | (low >> p & 1)) as usize
+    })
+}
+
+fn decode_etc2(block: &[u8], punch_through: bool) -> [[u8; 4]; 16] {
+    let mut bytes = [0; 8];
+    bytes.copy_from_slice(&block[..8]);
+    let bits = u64::from_be_bytes(bytes);
+    let (high, low) = ((bits >> 32) as u32, bits as u32);
+
+    // Punch-through blocks are always differential, the bit tells that the block is opaque
+    let differential = punch_through || high & 0x2 != 0;
+    let opaque = !punch_through || high & 0x2 != 0;
+    let flip = high & 0x1 != 0;
+    let indices = texel_indices(low);
+
+    if !differential {
+        let base = |shift: u32| {
+            let color = |offset: u32| extend(high >> (offset + shift) & 0xf, 4);
+            [color(24), color(16), color(8)]
+        };
+        return etc1_texels([base(4), base(0)], high, flip, indices, opaque);
+    }
+
+    let (r, g, b) = (high >> 27 & 0x1f, high >> 19 & 0x1f, high >> 11 & 0x1f);
+    let (dr, dg, db) = (delta(high >> 24), delta(high >> 16), delta(high >> 8));
+    let overflows = |c: u32, d: i32| !(0..32).contains(&(c as i32 + d));
+
+    if overflows(r, dr) {
+        decode_t_mode(high, indices, opaque)
+    } else if overflows(g, dg) {
+        decode_h_mode(high, indices, opaque)
+    } else if overflows(b, db) {
+        decode_planar(high, low)
+    } else {
+        let second = |c: u32, d: i32| extend((c as i32 + d) as u32, 5);
+        let bases = [
+            [extend(r, 5), extend(g, 5), extend(b, 5)],
+            [second(r, dr), second(g, dg), second(b, db)],
+        ];
+
+        etc1_texels(bases, high, flip, indices, opaque)
+    }
+}
+
+// Two sub-blocks with the base colors and the modifier tables, they're side by side or stacked
+fn etc1_texels(
+    bases: [[i32; 3]; 2],
+    high: u32,
+    flip: bool,
+    indices: [usize; 16],
+    opaque: bool,
+) -> [[u8; 4]; 16] {
+    let tables = [(high >> 5 & 0x7) as usize, (high >> 2 & 0x7) as usize];
+
+    std::array::from_fn(|i| {
+        let (x, y) = (i % 4, i / 4);
+        let sub_block = if flip { y / 2 } else { x / 2 };
+        let [small, large] = ETC1_MODIFIERS[tables[sub_block]];
+
+        // Non-opaque punch-through blocks have no small modifiers, the third one is transparent
+        let modifier = match (indices[i], opaque) {
+            (0, true) => small,
+            (0, false) => 0,
+            (1, _) => large,
+            (2, true) => -small,
+            (2, false) => return [0; 4],
+            _ => -large,
+        };
+
+        offset_color(bases[sub_block], modifier)
+    })
+}
+
+fn decode_t_mode(high: u32, indices: [usize; 16], opaque: bool) -> [[u8; 4]; 16] {
+    let c1 = [
+        extend((high >> 27 & 0x3) << 2 | (high >> 24 & 0x3), 4),
+        extend(high >> 20 & 0xf, 4),
+        extend(high >> 16 & 0xf, 4),
+    ];
+    let c2 = [
+        extend(high >> 12 & 0xf, 4),
+        extend(high >> 8 & 0xf, 4),
+        extend(high >> 4 & 0xf, 4),
+    ];
+    let distance = ETC2_DISTANCES[((high >> 1 & 0x6) | (high & 0x1)) as usize];
+
+    let paint = [
+        offset_color(c1, 0),
+        offset_color(c2, distance),
+        offset_color(c2, 0),
+        offset_color(c2, -distance),
+    ];
+
+    paint_texels(paint, indices, opaque)
+}
+
+fn decode_h_mode(high: u32, indices: [usize; 16], opaque: bool) -> [[u8; 4]; 16] {
+    let c1 = [
+        high >> 27 & 0xf,
+        (high >> 24 & 0x7) << 1 | (high >> 20 & 0x1),
+        (high >> 19 & 0x1) << 3 | (high >> 15 & 0x7),
+    ];
+    let c2 = [high >> 11 & 0xf, high >> 7 & 0xf, high >> 3 & 0xf];
+
+    // The lowest bit of the distance index is the order of the base colors
+    let packed = |c: [u32; 3]| c[0] << 8 | c[1] << 4 | c[2];
+    let order = (packed(c1) >= packed(c2)) as u32;
+    let distance = ETC2_DISTANCES[((high >> 2 & 0x1) << 2 | (high & 0x1) << 1 | order) as usize];
+
+    let (c1, c2) = (c1.map(|c| extend(c, 4)), c2.map(|c| extend(c, 4)));
+    let paint = [
+        offset_color(c1, distance),
+        offset_color(c1, -distance),
+        offset_color(c2, distance),
+        offset_color(c2, -distance),
+    ];
+
+    paint_texels(paint, indices, opaque)
+}
+
+fn paint_texels(paint: [[u8; 4]; 4], indices: [usize; 16], opaque: bool) -> [[u8; 4]; 16] {
+    indices.map(|index| {
+        if !opaque && index == 2 {
+            return [0; 4];
+        }
+        paint[index]
+    })
+}
+
+// Colors of the origin, the horizontal and the vertical corners are interpolated
+fn decode_planar(high: u32, low: u32) -> [[u8; 4]; 16] {
+    let origin = [
+        extend(high >> 25 & 0x3f, 6),
+        extend((high >> 24 & 0x1) << 6 | (high >> 17 & 0x3f), 7),
+        extend(
+            (high >> 16 & 0x1) << 5 | (high >> 11 & 0x3) << 3 | (high >> 7 & 0x7),
+            6,
+        ),
+    ];
+    let horizontal = [
+        extend((high >> 2 & 0x1f) << 1 | (high & 0x1), 6),
+        extend(low >> 25 & 0x7f, 7),
+        extend(low >> 19 & 0x3f, 6),
+    ];
+    let vertical = [
+        extend(low >> 13 & 0x3f, 6),
+        extend(low >> 6 & 0x7f, 7),
+        extend(low & 0x3f, 6),
+    ];
+
+    std::array::from_fn(|i| {
+        let (x, y) = ((i % 4) as i32, (i / 4) as i32);
+        let mut texel = [255; 4];
+        for c in 0..3 {
+            let value =
+                x * (horizontal[c] - origin[c]) + y * (vertical[c] - origin[c]) + 4 * origin[c] + 2;
+            texel[c] = (value >> 2).clamp(0, 255) as u8;
+        }
+
+        texel
+    })
+}
+
+fn decode_eac(block: &[u8]) -> [u8; 16] {
+    let (base, multiplier, table, indices) = eac_fields(block);
+
+    std::array::from_fn(|i| {
+        let value = base as i32 + table[indices[i]] * multiplier;
+        value.clamp(0, 255) as u8
+    })
+}
+
+// 11-bit values are in 0..2047 or in -1023..1023
+fn decode_eac11(block: &[u8], signed: bool) -> [i32; 16] {
+    let (base, multiplier, table, indices) = eac_fields(block);
+    let (base, range) = if signed {
+        ((base as i8).max(-127) as i32 * 8, -1023..=1023)
+    } else {
+        (base as i32 * 8 + 4, 0..=2047)
+    };
+
+    std::array::from_fn(|i| {
+        let modifier = table[indices[i]];
+        let value = match multiplier {
+            0 => base + modifier,
+            m => base + modifier * m * 8,
+        };
+        value.clamp(*range.start(), *range.end())
+    })
+}
+
+fn eac_fields(block: &[u8]) -> (u8, i32, [i32; 8], [usize; 16]) {
+    let mut bytes = [0; 8];
+    bytes.copy_from_slice(&block[..8]);
+    let bits = u64::from_be_bytes(bytes);
+
+    let indices = std::array::from_fn(|i| {
+        let p = i % 4 * 4 + i / 4;
+        (bits >> (45 - 3 * p) & 0x7) as usize
+    });
+
+    (
+        block[0],
+        (block[1] >> 4) as i32,
+        EAC_MODIFIERS[(block[1] & 0xf) as usize],
+        indices,
+    )
+}
+
+fn unorm11(value: i32) -> u8 {
+    (value as f32 / 2047. * 255.).round() as u8
+}
+
+fn snorm11(value: i32) -> u8 {
+    (value as f32 / 1023. * 127.).round() as i8 as u8
+}
+
+fn delta(bits: u32) -> i32 {
+    ((bits & 0x7) as i32) << 29 >> 29
+}
+
+fn extend(value: u32, bits: u32) -> i32 {
+    (value << (8 - bits) | value >> (2 * bits - 8)) as i32
+}
+
+fn offset_color(base: [i32; 3], modifier: i32) -> [u8; 4] {
+    let [r, g, b] = base.map(|c| (c + modifier).clamp(0, 255) as u8);
+    [r, g, b, 255]
+}
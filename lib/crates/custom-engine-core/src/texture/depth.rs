@@ -21,6 +21,8 @@ pub struct DepthTextureBuilder<'a> {
     is_sampler: bool,
     texture_size: Option<(u32, u32)>,
     depth_or_array_layers: u32,
+    sample_count: u32,
+    usage: Option<wgpu::TextureUsages>,
     texture_desc: Option<wgpu::TextureDescriptor<'a>>,
     sampler_desc: Option<wgpu::SamplerDescriptor<'a>>,
     texture_view_desc: Option<wgpu::TextureViewDescriptor<'a>>,
@@ -46,6 +48,8 @@ impl<'a> Builder<'a> for DepthTextureBuilder<'a> {
             texture_view_desc: None,
             texture_size: None,
             depth_or_array_layers: 1,
+            sample_count: 1,
+            usage: None,
         }
     }
 
@@ -64,6 +68,8 @@ impl<'a> Builder<'a> for DepthTextureBuilder<'a> {
             texture_view_desc: None,
             texture_size: None,
             depth_or_array_layers: 1,
+            sample_count: 1,
+            usage: None,
         }
     }
 
@@ -76,18 +82,23 @@ impl<'a> Builder<'a> for DepthTextureBuilder<'a> {
 
         let label = self.label.unwrap_or(&texture_name);
         let depth_or_array_layers = self.depth_or_array_layers;
+        let sample_count = self.sample_count;
 
         let texture_desc = self.texture_desc;
         let is_sampler = self.is_sampler;
         let sampler_desc = self.sampler_desc;
         let t_view_desc = self.texture_view_desc;
         let texture_size = self.texture_size;
+        let usage = self.usage.unwrap_or(
+            wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::TEXTURE_BINDING,
+        );
 
         debug!(
             "
 Build `{label}`: 
     Is Sampler: {is_sampler},
     Depth layers: {depth_or_array_layers},
+    Sample count: {sample_count},
     Texture description: {texture_desc:#?},
     Texture view description: {t_view_desc:#?},
     Sampler description: {sampler_desc:#?},
@@ -104,10 +115,10 @@ Build `{label}`:
             label: Some(label),
             size,
             mip_level_count: 1,
-            sample_count: 1,
+            sample_count,
             dimension: wgpu::TextureDimension::D2,
             format: wgpu::TextureFormat::Depth32Float,
-            usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::TEXTURE_BINDING,
+            usage,
             view_formats: &[],
         });
         let texture = self.device.create_texture(&t_desc);
@@ -147,6 +158,11 @@ impl<'a> DepthTextureBuilder<'a> {
         self
     }
 
+    pub fn usage(mut self, usage: wgpu::TextureUsages) -> Self {
+        self.usage = Some(usage);
+        self
+    }
+
     pub fn bytes(mut self, data: &'a [u8]) -> Self {
         self.data = Some(data);
         self
@@ -181,4 +197,9 @@ impl<'a> DepthTextureBuilder<'a> {
         self.depth_or_array_layers = depth_or_array_layers;
         self
     }
+
+    pub fn sample_count(mut self, sample_count: u32) -> Self {
+        self.sample_count = sample_count;
+        self
+    }
 }
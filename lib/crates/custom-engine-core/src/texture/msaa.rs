@@ -0,0 +1,59 @@
+use log::debug;
+
+use crate::{
+    errors::CoreError,
+    texture::{DepthTexture, DepthTextureBuilder, RenderTexture, RenderTextureBuilder},
+    traits::Builder,
+};
+
+// Multisampled attachments of the worker, colors are resolved into the surface or a render texture
+#[derive(Debug)]
+pub struct MsaaTextures {
+    pub color: RenderTexture,
+    pub depth: DepthTexture,
+
+    sample_count: u32,
+}
+
+impl MsaaTextures {
+    pub(crate) fn new(
+        device: &wgpu::Device,
+        format: wgpu::TextureFormat,
+        size: (u32, u32),
+        sample_count: u32,
+    ) -> Result<Self, CoreError> {
+        debug!(
+            "
+Allocate MSAA textures:
+    Format: {format:?},
+    Size: {size:?},
+    Sample count: {sample_count},"
+        );
+
+        let color = RenderTextureBuilder::new(device)
+            .label("MSAA color texture")
+            .format(format)
+            .texture_size(size)
+            .sample_count(sample_count)
+            .usage(wgpu::TextureUsages::RENDER_ATTACHMENT)
+            .is_sampler(false)
+            .build()?;
+        let depth = DepthTextureBuilder::new(device)
+            .label("MSAA depth texture")
+            .texture_size(size)
+            .sample_count(sample_count)
+            .is_sampler(false)
+            .build()?;
+
+        Ok(Self {
+            color,
+            depth,
+            sample_count,
+        })
+    }
+
+    #[inline]
+    pub fn sample_count(&self) -> u32 {
+        self.sample_count
+    }
+}
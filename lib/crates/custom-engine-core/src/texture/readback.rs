@@ -0,0 +1,225 @@
+use std::mem::size_of;
+
+use image::{DynamicImage, ImageBuffer};
+
+use crate::errors::CoreError;
+
+// Subresource of the texture, which is copied to the buffer
+#[derive(Debug, Clone, Copy)]
+pub struct ReadbackRegion {
+    pub mip_level: u32,
+    pub array_layer: u32,
+    pub aspect: wgpu::TextureAspect,
+}
+
+impl Default for ReadbackRegion {
+    fn default() -> Self {
+        Self {
+            mip_level: 0,
+            array_layer: 0,
+            aspect: wgpu::TextureAspect::All,
+        }
+    }
+}
+
+impl ReadbackRegion {
+    pub fn new(mip_level: u32, array_layer: u32) -> Self {
+        Self {
+            mip_level,
+            array_layer,
+            ..Default::default()
+        }
+    }
+
+    pub fn aspect(mut self, aspect: wgpu::TextureAspect) -> Self {
+        self.aspect = aspect;
+        self
+    }
+}
+
+// Buffer layout of the copied texture, rows are padded to `COPY_BYTES_PER_ROW_ALIGNMENT`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TextureLayout {
+    pub format: wgpu::TextureFormat,
+    pub width: u32,
+    pub height: u32,
+    pub rows: u32,
+    pub bytes_per_row: u32,
+    pub padded_bytes_per_row: u32,
+}
+
+impl TextureLayout {
+    pub fn new(
+        format: wgpu::TextureFormat,
+        aspect: wgpu::TextureAspect,
+        (width, height): (u32, u32),
+    ) -> Result<Self, CoreError> {
+        let block_size = format
+            .block_copy_size(Some(aspect))
+            .ok_or(CoreError::UnsupportedReadbackFormat(format, aspect))?;
+        let (block_width, block_height) = format.block_dimensions();
+
+        let bytes_per_row = width.div_ceil(block_width) * block_size;
+        let alignment = wgpu::COPY_BYTES_PER_ROW_ALIGNMENT;
+
+        Ok(Self {
+            format,
+            width,
+            height,
+            rows: height.div_ceil(block_height),
+            bytes_per_row,
+            padded_bytes_per_row: bytes_per_row.div_ceil(alignment) * alignment,
+        })
+    }
+
+    #[inline]
+    pub fn buffer_size(&self) -> u64 {
+        self.padded_bytes_per_row as u64 * self.rows as u64
+    }
+
+    #[inline]
+    pub fn data_layout(&self) -> wgpu::ImageDataLayout {
+        wgpu::ImageDataLayout {
+            offset: 0,
+            bytes_per_row: Some(self.padded_bytes_per_row),
+            rows_per_image: Some(self.rows),
+        }
+    }
+
+    pub fn unpad(&self, data: &[u8]) -> Vec<u8> {
+        if self.bytes_per_row == self.padded_bytes_per_row {
+            return data[..(self.bytes_per_row * self.rows) as usize].to_vec();
+        }
+
+        data.chunks(self.padded_bytes_per_row as usize)
+            .take(self.rows as usize)
+            .flat_map(|row| &row[..self.bytes_per_row as usize])
+            .copied()
+            .collect()
+    }
+}
+
+// Tightly packed texels of the read back texture
+#[derive(Debug, Clone)]
+pub struct TextureReadback {
+    pub format: wgpu::TextureFormat,
+    pub width: u32,
+    pub height: u32,
+    pub data: Vec<u8>,
+}
+
+impl TextureReadback {
+    pub(crate) fn new(layout: &TextureLayout, padded_data: &[u8]) -> Self {
+        Self {
+            format: layout.format,
+            width: layout.width,
+            height: layout.height,
+            data: layout.unpad(padded_data),
+        }
+    }
+
+    pub fn typed<T: bytemuck::Pod>(&self) -> Vec<T> {
+        cast_vec(&self.data)
+    }
+
+    // Float formats are converted to `Rgba32F`, depth is copied to every color channel
+    pub fn into_image(self) -> Result<DynamicImage, CoreError> {
+        use wgpu::TextureFormat::*;
+
+        let Self {
+            format,
+            width,
+            height,
+            data,
+        } = self;
+
+        let image = match format {
+            Rgba8Unorm | Rgba8UnormSrgb => {
+                ImageBuffer::from_raw(width, height, data).map(DynamicImage::ImageRgba8)
+            }
+            Bgra8Unorm | Bgra8UnormSrgb => {
+                let data = data
+                    .chunks_exact(4)
+                    .flat_map(|p| [p[2], p[1], p[0], p[3]])
+                    .collect();
+
+                ImageBuffer::from_raw(width, height, data).map(DynamicImage::ImageRgba8)
+            }
+            R8Unorm => ImageBuffer::from_raw(width, height, data).map(DynamicImage::ImageLuma8),
+            Rg8Unorm => ImageBuffer::from_raw(width, height, data).map(DynamicImage::ImageLumaA8),
+            Rgba16Float => {
+                let data = cast_vec::<u16>(&data).into_iter().map(f16_to_f32).collect();
+
+                ImageBuffer::from_raw(width, height, data).map(DynamicImage::ImageRgba32F)
+            }
+            Rgba32Float => ImageBuffer::from_raw(width, height, cast_vec(&data))
+                .map(DynamicImage::ImageRgba32F),
+            R32Float | Depth32Float => {
+                let data = cast_vec::<f32>(&data)
+                    .into_iter()
+                    .flat_map(|d| [d, d, d, 1.])
+                    .collect();
+
+                ImageBuffer::from_raw(width, height, data).map(DynamicImage::ImageRgba32F)
+            }
+            _ => return Err(CoreError::UnsupportedImageFormat(format)),
+        };
+
+        image.ok_or(CoreError::ImageBufferCreate)
+    }
+}
+
+// Mapped data of `u8` isn't aligned for the target type, so it's copied
+fn cast_vec<T: bytemuck::Pod>(data: &[u8]) -> Vec<T> {
+    let mut typed = vec![T::zeroed(); data.len() / size_of::<T>()];
+    let len = typed.len() * size_of::<T>();
+    bytemuck::cast_slice_mut::<T, u8>(&mut typed).copy_from_slice(&data[..len]);
+
+    typed
+}
+
+fn f16_to_f32(bits: u16) -> f32 {
+    let sign = if bits & 0x8000 != 0 { -1. } else { 1. };
+    let exponent = ((bits >> 10) & 0x1f) as i32;
+    let mantissa = (bits & 0x3ff) as f32;
+
+    sign * match exponent {
+        0 => mantissa * 2f32.powi(-24),
+        0x1f if mantissa == 0. => f32::INFINITY,
+        0x1f => f32::NAN,
+        _ => (1. + mantissa / 1024.) * 2f32.powi(exponent - 15),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn texture_readback() {
+        let layout = TextureLayout::new(
+            wgpu::TextureFormat::Rgba8Unorm,
+            wgpu::TextureAspect::All,
+            (3, 2),
+        )
+        .unwrap();
+        assert_eq!(layout.bytes_per_row, 12);
+        assert_eq!(layout.padded_bytes_per_row, 256);
+        assert_eq!(layout.buffer_size(), 512);
+
+        let mut data = vec![0; 512];
+        data[..12].fill(1);
+        data[256..268].fill(2);
+
+        let unpadded = layout.unpad(&data);
+        assert_eq!(unpadded.len(), 24);
+        assert!(unpadded[..12].iter().all(|&b| b == 1));
+        assert!(unpadded[12..].iter().all(|&b| b == 2));
+
+        assert_eq!(f16_to_f32(0x3c00), 1.);
+        assert_eq!(f16_to_f32(0xc000), -2.);
+        assert_eq!(f16_to_f32(0x3800), 0.5);
+        assert_eq!(f16_to_f32(0x0000), 0.);
+        assert!(f16_to_f32(0x7c00).is_infinite());
+    }
+}
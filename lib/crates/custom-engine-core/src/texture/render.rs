@@ -1,6 +1,6 @@
 use derive_more::{Deref, DerefMut};
-use image::{load_from_memory, GenericImageView};
-use log::{debug, info};
+use image::load_from_memory;
+use log::{debug, error, info, warn};
 
 use crate::{
     bind_group::{
@@ -9,7 +9,7 @@ use crate::{
     },
     buffer::Buffer,
     errors::CoreError,
-    texture::TextureKind,
+    texture::{CompressedImage, TextureKind, TextureLayout},
     traits::Builder,
 };
 
@@ -24,8 +24,10 @@ pub struct RenderTexture {
     bind_group: Option<BindGroup>,
     bind_group_layout: Option<BindGroupLayout>,
 
+    // Mip levels, which are written on load
     #[derivative(Debug = "ignore")]
-    data: Option<Vec<u8>>,
+    data: Option<Vec<Vec<u8>>>,
+    mipmaps: bool,
     #[deref]
     #[deref_mut]
     texture: wgpu::Texture,
@@ -34,15 +36,20 @@ pub struct RenderTexture {
 pub struct RenderTextureBuilder<'a> {
     id: Option<usize>,
     data: Option<&'a [u8]>,
+    fallback_data: Option<&'a [u8]>,
+    layer_data: Vec<&'a [u8]>,
     label: Option<&'a str>,
     format: wgpu::TextureFormat,
     is_sampler: bool,
     texture_size: Option<(u32, u32)>,
     depth_or_array_layers: u32,
+    sample_count: u32,
+    mipmaps: bool,
     texture_desc: Option<wgpu::TextureDescriptor<'a>>,
     sampler_desc: Option<wgpu::SamplerDescriptor<'a>>,
     texture_view_desc: Option<wgpu::TextureViewDescriptor<'a>>,
     dimension: Option<wgpu::TextureDimension>,
+    view_dimension: Option<wgpu::TextureViewDimension>,
     usage: Option<wgpu::TextureUsages>,
 
     bind_group_binding: Option<u32>,
@@ -66,16 +73,21 @@ impl<'a> Builder<'a> for RenderTextureBuilder<'a> {
             format: TextureKind::Render.into(),
             is_sampler: true,
             data: None,
+            fallback_data: None,
+            layer_data: vec![],
             texture_desc: None,
             sampler_desc: None,
             texture_view_desc: None,
             texture_size: None,
             depth_or_array_layers: 1,
+            sample_count: 1,
+            mipmaps: false,
             bind_group_binding: None,
             view_layout_entry: None,
             sampler_layout_entry: None,
             usage: None,
             dimension: None,
+            view_dimension: None,
         }
     }
 
@@ -90,16 +102,21 @@ impl<'a> Builder<'a> for RenderTextureBuilder<'a> {
             format: TextureKind::Render.into(),
             is_sampler: true,
             data: None,
+            fallback_data: None,
+            layer_data: vec![],
             texture_desc: None,
             sampler_desc: None,
             texture_view_desc: None,
             texture_size: None,
             depth_or_array_layers: 1,
+            sample_count: 1,
+            mipmaps: false,
             bind_group_binding: None,
             sampler_layout_entry: None,
             view_layout_entry: None,
             usage: None,
             dimension: None,
+            view_dimension: None,
         }
     }
 
@@ -111,32 +128,59 @@ impl<'a> Builder<'a> for RenderTextureBuilder<'a> {
         let texture_name = format!("Texture: {id}");
 
         let label = self.label.unwrap_or(&texture_name);
-        let depth_or_array_layers = self.depth_or_array_layers;
+        let mut depth_or_array_layers = self.depth_or_array_layers;
+        let sample_count = self.sample_count;
+        let mut mipmaps = self.mipmaps;
 
         let texture_desc = self.texture_desc;
         let is_sampler = self.is_sampler;
         let sampler_desc = self.sampler_desc;
         let t_view_desc = self.texture_view_desc;
-        let texture_size = self.texture_size;
-        let format = self.format.into();
+        let mut texture_size = self.texture_size;
+        let mut format = self.format;
         let dimension = self.dimension.unwrap_or(wgpu::TextureDimension::D2);
-        let usage = self
+        let mut usage = self
             .usage
             .unwrap_or(wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::COPY_DST);
 
+        let mut data = self.data.map(|d| vec![d.to_vec()]);
+        let mut mip_level_count = 1;
+        let mut cube = false;
+        if texture_desc.is_none() {
+            let images = self
+                .data
+                .map(|d| load_image(self.device, self.format, d, self.fallback_data, label))
+                .into_iter()
+                .chain(
+                    self.layer_data
+                        .iter()
+                        .map(|d| load_image(self.device, self.format, d, None, label)),
+                )
+                .collect::<Result<Vec<_>, _>>()?;
+
+            if !images.is_empty() {
+                // Format of the containers replaces the builder one, stored levels aren't generated
+                let image = CompressedImage::join(images)?;
+                format = image.format;
+                cube = image.cube;
+                depth_or_array_layers = depth_or_array_layers.max(image.layers);
+                mip_level_count = image.levels.len() as u32;
+                mipmaps &= mip_level_count == 1 && !format.is_compressed();
+                if format.is_compressed() {
+                    usage &= format
+                        .guaranteed_format_features(self.device.features())
+                        .allowed_usages;
+                }
+                texture_size = Some((image.width, image.height));
+                data = Some(image.levels);
+            }
+        }
+        mipmaps &= dimension == wgpu::TextureDimension::D2;
+        if mipmaps {
+            usage |= wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::TEXTURE_BINDING;
+        }
+
         let bind_group_binding = self.bind_group_binding;
-        let view_layout_entry = self
-            .view_layout_entry
-            .unwrap_or(wgpu::BindGroupLayoutEntry {
-                binding: 0,
-                visibility: wgpu::ShaderStages::FRAGMENT,
-                ty: wgpu::BindingType::Texture {
-                    multisampled: false,
-                    view_dimension: wgpu::TextureViewDimension::D2,
-                    sample_type: wgpu::TextureSampleType::Float { filterable: true },
-                },
-                count: None,
-            });
         let sampler_layout_entry =
             self.sampler_layout_entry
                 .unwrap_or(wgpu::BindGroupLayoutEntry {
@@ -151,24 +195,18 @@ impl<'a> Builder<'a> for RenderTextureBuilder<'a> {
 Build `{label}`: 
     Is Sampler: {is_sampler},
     Depth layers: {depth_or_array_layers},
+    Sample count: {sample_count},
+    Mipmaps: {mipmaps},
     Texture description: {texture_desc:#?},
     Texture view description: {t_view_desc:#?},
     Sampler description: {sampler_desc:#?},
     "
         );
 
-        let mut data = self.data.map(|d| d.to_vec());
         let texture = if let Some(t_d) = texture_desc {
             self.device.create_texture(&t_d)
         } else {
-            let dimensions = if let Some(d) = data.as_ref() {
-                let img = load_from_memory(&*d)?;
-                data = Some(img.to_rgba8().to_vec());
-
-                img.dimensions()
-            } else {
-                texture_size.ok_or(CoreError::EmptyTextureSize(label.to_string()))?
-            };
+            let dimensions = texture_size.ok_or(CoreError::EmptyTextureSize(label.to_string()))?;
 
             let size = wgpu::Extent3d {
                 width: dimensions.0,
@@ -176,11 +214,14 @@ Build `{label}`:
                 depth_or_array_layers,
             };
 
+            if mipmaps {
+                mip_level_count = size.max_mips(dimension);
+            }
             let t_desc = texture_desc.unwrap_or(wgpu::TextureDescriptor {
                 label: Some(label),
                 size,
-                mip_level_count: 1,
-                sample_count: 1,
+                mip_level_count,
+                sample_count,
                 dimension,
                 format,
                 usage,
@@ -189,16 +230,54 @@ Build `{label}`:
 
             self.device.create_texture(&t_desc)
         };
-        let view = texture.create_view(&t_view_desc.unwrap_or_default());
+
+        let layers = texture.depth_or_array_layers();
+        let view_dimension = self.view_dimension.unwrap_or(match texture.dimension() {
+            wgpu::TextureDimension::D1 => wgpu::TextureViewDimension::D1,
+            wgpu::TextureDimension::D3 => wgpu::TextureViewDimension::D3,
+            _ if cube && layers > 6 => wgpu::TextureViewDimension::CubeArray,
+            _ if cube => wgpu::TextureViewDimension::Cube,
+            _ if layers > 1 => wgpu::TextureViewDimension::D2Array,
+            _ => wgpu::TextureViewDimension::D2,
+        });
+        if matches!(
+            view_dimension,
+            wgpu::TextureViewDimension::Cube | wgpu::TextureViewDimension::CubeArray
+        ) && (texture.width() != texture.height() || layers % 6 != 0)
+        {
+            return Err(CoreError::InvalidCubeTexture(label.to_string()));
+        }
+
+        let view = texture.create_view(&t_view_desc.unwrap_or(wgpu::TextureViewDescriptor {
+            dimension: Some(view_dimension),
+            ..Default::default()
+        }));
+        let view_layout_entry = self
+            .view_layout_entry
+            .unwrap_or(wgpu::BindGroupLayoutEntry {
+                binding: 0,
+                visibility: wgpu::ShaderStages::FRAGMENT,
+                ty: wgpu::BindingType::Texture {
+                    multisampled: false,
+                    view_dimension,
+                    sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                },
+                count: None,
+            });
 
         let sampler = if is_sampler {
+            let mip_filter = if texture.mip_level_count() > 1 {
+                wgpu::FilterMode::Linear
+            } else {
+                wgpu::FilterMode::Nearest
+            };
             let s_desc = sampler_desc.unwrap_or(wgpu::SamplerDescriptor {
                 address_mode_u: wgpu::AddressMode::ClampToEdge,
                 address_mode_v: wgpu::AddressMode::ClampToEdge,
                 address_mode_w: wgpu::AddressMode::ClampToEdge,
                 mag_filter: wgpu::FilterMode::Linear,
-                min_filter: wgpu::FilterMode::Nearest,
-                mipmap_filter: wgpu::FilterMode::Nearest,
+                min_filter: mip_filter,
+                mipmap_filter: mip_filter,
                 ..Default::default()
             });
 
@@ -233,6 +312,7 @@ Build `{label}`:
                 sampler,
                 data,
                 texture,
+                mipmaps,
                 bind_group: Some(bind_group),
                 bind_group_layout: Some(bind_group_layout),
             })
@@ -243,6 +323,7 @@ Build `{label}`:
                 view,
                 sampler,
                 data,
+                mipmaps,
                 bind_group_layout: None,
                 bind_group: None,
             })
@@ -266,11 +347,36 @@ impl<'a> RenderTextureBuilder<'a> {
         self
     }
 
+    // Dimension of the view and the bind group layout, it's derived from the texture by default
+    pub fn view_dimension(mut self, view_dimension: wgpu::TextureViewDimension) -> Self {
+        self.view_dimension = Some(view_dimension);
+        self
+    }
+
     pub fn bytes(mut self, data: &'a [u8]) -> Self {
         self.data = Some(data);
         self
     }
 
+    // Image, which is loaded when the container of `bytes` isn't supported
+    pub fn fallback_bytes(mut self, fallback_data: &'a [u8]) -> Self {
+        self.fallback_data = Some(fallback_data);
+        self
+    }
+
+    // Images of the array layers or depth slices, which follow `bytes`
+    pub fn layer_bytes(mut self, layer_data: Vec<&'a [u8]>) -> Self {
+        self.layer_data = layer_data;
+        self
+    }
+
+    // Faces are ordered as `+X`, `-X`, `+Y`, `-Y`, `+Z`, `-Z`
+    pub fn cube_faces(mut self, faces: [&'a [u8]; 6]) -> Self {
+        self.layer_data = faces.to_vec();
+        self.view_dimension = Some(wgpu::TextureViewDimension::Cube);
+        self
+    }
+
     pub fn format<T: Into<wgpu::TextureFormat>>(mut self, format: T) -> Self {
         self.format = format.into();
         self
@@ -306,6 +412,17 @@ impl<'a> RenderTextureBuilder<'a> {
         self
     }
 
+    pub fn sample_count(mut self, sample_count: u32) -> Self {
+        self.sample_count = sample_count;
+        self
+    }
+
+    // Full mip chain, which is generated by `Worker::generate_mipmaps` or on load
+    pub fn mipmaps(mut self, mipmaps: bool) -> Self {
+        self.mipmaps = mipmaps;
+        self
+    }
+
     pub fn bind_group_binding(mut self, bind_group_binding: u32) -> Self {
         self.bind_group_binding = Some(bind_group_binding);
         self
@@ -357,38 +474,88 @@ impl RenderTexture {
             )))
     }
 
+    // Mip chain is generated on load, stored levels of containers aren't overwritten
+    pub fn generates_mipmaps(&self) -> bool {
+        self.mipmaps
+    }
+
     pub fn store_to_memory(&self, queue: &wgpu::Queue) {
-        if let Some(img_data) = self.data.as_ref() {
-            let aspect = wgpu::TextureAspect::All;
-            let components = self.format().components_with_aspect(aspect) as u32;
-            let size = self.size();
-
-            info!(
-                "Store to memory: aspect {aspect:?}, components {components:?}, size: {:?}",
-                size
-            );
-
-            queue.write_texture(
-                wgpu::ImageCopyTexture {
-                    aspect,
-                    texture: &self.texture,
-                    mip_level: 0,
-                    origin: wgpu::Origin3d::ZERO,
-                },
-                img_data,
-                wgpu::ImageDataLayout {
-                    offset: 0,
-                    bytes_per_row: Some(components * self.width()),
-                    rows_per_image: Some(self.height()),
-                },
-                size,
-            );
+        let Some(levels) = self.data.as_ref() else {
+            return;
+        };
+
+        for (mip_level, level_data) in (0..self.mip_level_count()).zip(levels) {
+            if let Err(e) = self.store_layers(queue, mip_level, 0, level_data) {
+                error!("{e}");
+                return;
+            }
         }
     }
 
-    pub fn load_to_buffer(&self, encoder: &mut wgpu::CommandEncoder, output_buffer: &Buffer) {
+    // Tightly packed images of the layers or depth slices, which start from `first_layer`
+    pub fn store_layers(
+        &self,
+        queue: &wgpu::Queue,
+        mip_level: u32,
+        first_layer: u32,
+        data: &[u8],
+    ) -> Result<(), CoreError> {
         let aspect = wgpu::TextureAspect::All;
-        let components = self.format().components_with_aspect(aspect) as u32;
+        let size = self.size().mip_level_size(mip_level, self.dimension());
+        let layout = TextureLayout::new(self.format(), aspect, (size.width, size.height))?;
+
+        let image_size = (layout.bytes_per_row * layout.rows) as usize;
+        let layers = (data.len() / image_size) as u32;
+        if layers == 0 {
+            return Err(CoreError::EmptyData(format!("Render texture: {}", self.id)));
+        }
+        if mip_level >= self.mip_level_count() || first_layer + layers > size.depth_or_array_layers
+        {
+            return Err(CoreError::TextureSubresourceOutOfRange(
+                mip_level,
+                first_layer + layers - 1,
+            ));
+        }
+
+        info!(
+            "Store to memory: mip level {mip_level}, layers {first_layer}..{}, size: {size:?}",
+            first_layer + layers
+        );
+
+        queue.write_texture(
+            wgpu::ImageCopyTexture {
+                aspect,
+                texture: &self.texture,
+                mip_level,
+                origin: wgpu::Origin3d {
+                    x: 0,
+                    y: 0,
+                    z: first_layer,
+                },
+            },
+            &data[..image_size * layers as usize],
+            wgpu::ImageDataLayout {
+                offset: 0,
+                bytes_per_row: Some(layout.bytes_per_row),
+                rows_per_image: Some(layout.rows),
+            },
+            wgpu::Extent3d {
+                depth_or_array_layers: layers,
+                ..size.physical_size(self.format())
+            },
+        );
+
+        Ok(())
+    }
+
+    // The buffer is sized by `TextureLayout::buffer_size`, rows are padded
+    pub fn load_to_buffer(
+        &self,
+        encoder: &mut wgpu::CommandEncoder,
+        output_buffer: &Buffer,
+    ) -> Result<(), CoreError> {
+        let aspect = wgpu::TextureAspect::All;
+        let layout = TextureLayout::new(self.format(), aspect, (self.width(), self.height()))?;
 
         encoder.copy_texture_to_buffer(
             wgpu::ImageCopyTexture {
@@ -399,13 +566,38 @@ impl RenderTexture {
             },
             wgpu::ImageCopyBuffer {
                 buffer: output_buffer,
-                layout: wgpu::ImageDataLayout {
-                    offset: 0,
-                    bytes_per_row: Some(components * self.width()),
-                    rows_per_image: Some(self.height()),
-                },
+                layout: layout.data_layout(),
             },
             self.size(),
         );
+
+        Ok(())
+    }
+}
+
+fn load_image(
+    device: &wgpu::Device,
+    format: wgpu::TextureFormat,
+    data: &[u8],
+    fallback_data: Option<&[u8]>,
+    label: &str,
+) -> Result<CompressedImage, CoreError> {
+    let (image, data) = match (
+        CompressedImage::load(data, device.features()),
+        fallback_data,
+    ) {
+        (Err(e), Some(fallback)) => {
+            warn!("{e}. Load the fallback image of `{label}`");
+            (None, fallback)
+        }
+        (image, _) => (image?, data),
+    };
+
+    match image {
+        Some(image) => Ok(image),
+        None => Ok(CompressedImage::from_image(
+            &load_from_memory(data)?,
+            format,
+        )),
     }
 }
@@ -47,12 +47,13 @@ pub trait RenderWorker {
     fn render(&mut self, _: &mut Worker<'_>) -> Result<(), CoreError> {
         Ok(())
     }
-    fn update(
-        &mut self,
-        _: &mut Worker<'_>,
-        _: &WindowEvent,
-        _: Duration,
-    ) -> Result<(), CoreError> {
+    fn input(&mut self, _: &mut Worker<'_>, _: &WindowEvent) -> Result<(), CoreError> {
+        Ok(())
+    }
+    fn update(&mut self, _: &mut Worker<'_>, _: Duration) -> Result<(), CoreError> {
+        Ok(())
+    }
+    fn fixed_update(&mut self, _: &mut Worker<'_>, _: Duration) -> Result<(), CoreError> {
         Ok(())
     }
     fn resize(&mut self, _: &mut Worker<'_>) -> Result<(), CoreError> {
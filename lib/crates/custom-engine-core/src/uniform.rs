@@ -1,4 +1,4 @@
-use std::collections::HashMap;
+use std::{cell::RefCell, collections::HashMap, mem::size_of, num::NonZeroU64};
 
 use log::debug;
 
@@ -20,6 +20,7 @@ pub struct Uniforms {
     bind_group: BindGroup,
     bind_group_layout: BindGroupLayout,
     buffers: HashMap<String, Buffer>,
+    records: HashMap<String, UniformRecords>,
 }
 
 impl Uniforms {
@@ -31,9 +32,85 @@ impl Uniforms {
         &self.bind_group_layout
     }
 
+    pub(crate) fn buffers(&self) -> impl Iterator<Item = &Buffer> {
+        self.buffers.values()
+    }
+
     pub fn get_buffer(&self, name: &str) -> Option<&Buffer> {
         self.buffers.get(name)
     }
+
+    // Offset of a free record in the dynamic uniform, it's passed as the dynamic offset of the draw
+    pub fn allocate_record(&self, name: &str) -> Result<u32, CoreError> {
+        let records = self.get_records(name)?;
+        let index = records
+            .allocator
+            .borrow_mut()
+            .allocate(records.capacity)
+            .ok_or(CoreError::UniformRecordsExhausted(name.to_string()))?;
+
+        Ok((index as u64 * records.stride) as u32)
+    }
+
+    pub fn free_record(&self, name: &str, offset: u32) -> Result<(), CoreError> {
+        let records = self.get_records(name)?;
+        records
+            .allocator
+            .borrow_mut()
+            .free((offset as u64 / records.stride) as u32);
+
+        Ok(())
+    }
+
+    pub fn record_stride(&self, name: &str) -> Option<u64> {
+        self.records.get(name).map(|r| r.stride)
+    }
+
+    pub fn record_capacity(&self, name: &str) -> Option<u32> {
+        self.records.get(name).map(|r| r.capacity)
+    }
+
+    fn get_records(&self, name: &str) -> Result<&UniformRecords, CoreError> {
+        self.records
+            .get(name)
+            .ok_or(CoreError::NotDynamicUniform(name.to_string()))
+    }
+}
+
+// Records of the dynamic uniform share one buffer, every record is aligned
+// by `min_uniform_buffer_offset_alignment`
+#[derive(Debug)]
+struct UniformRecords {
+    stride: u64,
+    capacity: u32,
+    allocator: RefCell<RecordAllocator>,
+}
+
+#[derive(Debug, Default)]
+struct RecordAllocator {
+    next: u32,
+    free: Vec<u32>,
+}
+
+impl RecordAllocator {
+    fn allocate(&mut self, capacity: u32) -> Option<u32> {
+        if let Some(index) = self.free.pop() {
+            return Some(index);
+        }
+
+        if self.next < capacity {
+            self.next += 1;
+            return Some(self.next - 1);
+        }
+
+        None
+    }
+
+    fn free(&mut self, index: u32) {
+        if index < self.next && !self.free.contains(&index) {
+            self.free.push(index);
+        }
+    }
 }
 
 #[derive(derivative::Derivative)]
@@ -44,6 +121,8 @@ pub struct UniformDescription<'a> {
     visibility: wgpu::ShaderStages,
     #[derivative(Debug = "ignore")]
     data: &'a [u8],
+    // Size of the record and count of records of the dynamic uniform
+    records: Option<(u64, u32)>,
 }
 
 impl<'a> UniformDescription<'a> {
@@ -58,6 +137,23 @@ impl<'a> UniformDescription<'a> {
             binding,
             visibility,
             data: bytemuck::cast_slice(data),
+            records: None,
+        }
+    }
+
+    // Uniform with `has_dynamic_offset`, records are allocated by `Uniforms::allocate_record`
+    pub fn dynamic<T: bytemuck::Pod + bytemuck::Zeroable>(
+        name: &'a str,
+        binding: u32,
+        visibility: wgpu::ShaderStages,
+        capacity: u32,
+    ) -> Self {
+        Self {
+            name,
+            binding,
+            visibility,
+            data: &[],
+            records: Some((size_of::<T>() as u64, capacity)),
         }
     }
 }
@@ -117,14 +213,17 @@ impl<'a> Builder<'a> for UniformsBuilder<'a> {
         let bgl_name = format!("Bind group layout of `{name}`");
         let mut bgl_builder = BindGroupLayoutBuilder::new(self.device).label(&bgl_name);
 
+        let alignment = self.device.limits().min_uniform_buffer_offset_alignment as u64;
+
         let mut buffers = HashMap::new();
+        let mut records = HashMap::new();
         for entry in entries.into_iter() {
             let UniformDescription {
                 name,
                 binding,
                 visibility,
                 data,
-                ..
+                records: entry_records,
             } = entry;
 
             bgl_builder = bgl_builder.entries(wgpu::BindGroupLayoutEntry {
@@ -132,37 +231,55 @@ impl<'a> Builder<'a> for UniformsBuilder<'a> {
                 binding,
                 ty: wgpu::BindingType::Buffer {
                     ty: wgpu::BufferBindingType::Uniform,
-                    has_dynamic_offset: false,
-                    min_binding_size: None,
+                    has_dynamic_offset: entry_records.is_some(),
+                    min_binding_size: entry_records.and_then(|(size, _)| NonZeroU64::new(size)),
                 },
                 count: None,
             });
 
-            buffers.insert(
-                name.to_string(),
-                BufferBuilder::new(self.device)
-                    .label(&name)
-                    .binding(binding)
-                    .data(data)
-                    .usage(wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST)
-                    .build()?,
-            );
+            let mut buffer_builder = BufferBuilder::new(self.device)
+                .label(name)
+                .binding(binding)
+                .usage(wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST);
+            if let Some((size, capacity)) = entry_records {
+                let stride = size.div_ceil(alignment) * alignment;
+
+                buffer_builder = buffer_builder.size(stride * capacity as u64);
+                records.insert(
+                    name.to_string(),
+                    UniformRecords {
+                        stride,
+                        capacity,
+                        allocator: Default::default(),
+                    },
+                );
+            } else {
+                buffer_builder = buffer_builder.data(data);
+            }
+
+            buffers.insert(name.to_string(), buffer_builder.build()?);
         }
 
         let bg_name = format!("Bind group of `{name}`");
         let bind_group_layout = bgl_builder.build()?;
-        let bind_group = BindGroupBuilder::new(self.device)
+        let mut bind_group_builder = BindGroupBuilder::new(self.device)
             .label(&bg_name)
             .binding(bind_group_binding)
-            .layout(&bind_group_layout)
-            .entries_buffers(buffers.iter().map(|(_, b)| b).collect::<Vec<_>>())
-            .build()?;
+            .layout(&bind_group_layout);
+        for (buffer_name, buffer) in buffers.iter() {
+            bind_group_builder = match records.get(buffer_name) {
+                Some(r) => bind_group_builder.entries_buffer_range(buffer, r.stride),
+                None => bind_group_builder.entries_buffer(buffer),
+            };
+        }
+        let bind_group = bind_group_builder.build()?;
         let name = name.to_string();
 
         debug!(
             "
 Build `{name}`: 
     Buffers: {buffers:#?},
+    Dynamic records: {records:#?},
     Bind Group Layout: {bind_group_layout:#?},
     Bind Group: {bind_group:#?},
     "
@@ -174,6 +291,7 @@ Build `{name}`:
             bind_group,
             bind_group_layout,
             buffers,
+            records,
         })
     }
 }
@@ -194,3 +312,23 @@ impl<'a> UniformsBuilder<'a> {
         self
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn record_allocator() {
+        let mut allocator = RecordAllocator::default();
+
+        assert_eq!(allocator.allocate(2), Some(0));
+        assert_eq!(allocator.allocate(2), Some(1));
+        assert_eq!(allocator.allocate(2), None);
+
+        allocator.free(0);
+        allocator.free(0);
+        allocator.free(5);
+        assert_eq!(allocator.allocate(2), Some(0));
+        assert_eq!(allocator.allocate(2), None);
+    }
+}
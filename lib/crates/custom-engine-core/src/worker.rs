@@ -1,12 +1,17 @@
 mod context_impls;
 mod inner;
 
+use std::cell::OnceCell;
+
 use crate::{
     buffer::Buffer,
+    clock::FrameClock,
     context::Context,
     errors::CoreError,
-    runtime::{ImageFormat, SurfaceProperties},
-    texture::{RenderTexture, TextureKind},
+    pipeline::push_constants::PushConstantsBuffers,
+    profiler::GpuProfiler,
+    runtime::{AdapterCapabilities, ImageFormat, SurfaceProperties},
+    texture::{MipmapGenerator, MsaaTextures, RenderTexture, TextureKind},
 };
 
 #[derive(Debug)]
@@ -21,29 +26,29 @@ pub(crate) struct ViewTexture {
 pub(crate) enum View {
     Surface(wgpu::SurfaceTexture),
     Texture(ViewTexture),
-}
-
-impl View {
-    fn texture_view(&self) -> wgpu::TextureView {
-        match self {
-            View::Surface(s) => s.texture.create_view(&Default::default()),
-            View::Texture(ViewTexture { render_texture, .. }) => {
-                render_texture.create_view(&Default::default())
-            }
-        }
-    }
+    Offscreen,
 }
 
 #[derive(Debug)]
 pub struct Worker<'a> {
+    pub(crate) adapter: wgpu::Adapter,
     pub(crate) device: wgpu::Device,
     pub(crate) queue: wgpu::Queue,
     pub(crate) limits: wgpu::Limits,
+    pub(crate) capabilities: AdapterCapabilities,
 
-    pub(crate) surface_properties: SurfaceProperties<'a>,
+    pub(crate) surface_properties: Option<SurfaceProperties<'a>>,
     pub(crate) context: Context,
 
+    offscreen: Option<RenderTexture>,
+    msaa: Option<MsaaTextures>,
+    mipmaps: OnceCell<MipmapGenerator>,
+    profiler: Option<GpuProfiler>,
+    push_constants: PushConstantsBuffers,
+    clock: FrameClock,
+
     format: wgpu::TextureFormat,
+    sample_count: u32,
     size: (u32, u32),
     scale_factor: f64,
 
@@ -54,24 +59,44 @@ impl<'a> Worker<'a> {
     pub(crate) fn new(
         size: (u32, u32),
         scale_factor: f64,
-        surface_properties: SurfaceProperties<'a>,
+        surface_properties: Option<SurfaceProperties<'a>>,
+        adapter: wgpu::Adapter,
         device: wgpu::Device,
         queue: wgpu::Queue,
-        limits: wgpu::Limits,
+        capabilities: AdapterCapabilities,
         view: Option<View>,
         context: Context,
     ) -> Result<Self, CoreError> {
-        Ok(Self {
+        let format = surface_properties
+            .as_ref()
+            .map(|s_p| s_p.config.format)
+            .unwrap_or(TextureKind::Render.into());
+        let mut worker = Self {
             size,
             scale_factor,
+            format,
+            sample_count: 1,
             surface_properties,
-            format: TextureKind::Surface.into(),
+            offscreen: None,
+            msaa: None,
+            mipmaps: OnceCell::new(),
+            profiler: None,
+            push_constants: PushConstantsBuffers::default(),
+            clock: FrameClock::new(),
+            limits: capabilities.limits.clone(),
+            capabilities,
+            adapter,
             device,
             queue,
-            limits,
             view,
             context,
-        })
+        };
+
+        if worker.is_headless() {
+            worker.init_offscreen()?;
+        }
+
+        Ok(worker)
     }
 
     pub fn into_context(self) -> Context {
@@ -1,15 +1,22 @@
+use std::sync::Arc;
+
 use crate::{
     bind_group::{
         layout::{BindGroupLayout, BindGroupLayoutBuilder},
         BindGroup, BindGroupBuilder,
     },
     buffer::{Buffer, BufferBuilder},
+    context::Handle,
     errors::CoreError,
     model::{Model, ModelBuilder},
     pipeline::{
         layout::{PipelineLayout, PipelineLayoutBuilder},
         Pipeline, PipelineBuilder,
     },
+    render_pass::{
+        bundle::{RenderBundle, RenderBundleBuilder},
+        query_set::QuerySetBuilder,
+    },
     shader::{Shader, ShaderBuilder},
     storage::{Storages, StoragesBuilder},
     texture::{DepthTexture, DepthTextureBuilder, RenderTexture, RenderTextureBuilder},
@@ -18,396 +25,574 @@ use crate::{
     worker::Worker,
 };
 
-use custom_engine_utils::Ref;
-
 impl<'a> Worker<'a> {
     // Foreign functions
     // Storages
-    pub fn create_storage_id(&self) -> (usize, StoragesBuilder<'_>) {
-        let id = self.context.generate_unique_id();
-        (id, StoragesBuilder::new_indexed(&self.device, id))
+    pub fn create_storage_id(&self) -> (Handle<Storages>, StoragesBuilder<'_>) {
+        let handle = self.context.reserve_storage();
+        (
+            handle,
+            StoragesBuilder::new_indexed(&self.device, handle.index()),
+        )
     }
 
     pub fn create_storage(&self) -> StoragesBuilder<'_> {
         StoragesBuilder::new(&self.device)
     }
 
-    pub fn add_storage(&mut self, u: Storages) {
+    pub fn add_storage(&mut self, u: Storages) -> Handle<Storages> {
         self.context.add_storage(u)
     }
 
-    pub fn replace_storage(&mut self, id: usize, u: Storages) -> Result<(), CoreError> {
-        self.context.replace_storage(id, u)
+    pub fn replace_storage(
+        &mut self,
+        handle: Handle<Storages>,
+        u: Storages,
+    ) -> Result<(), CoreError> {
+        self.context.replace_storage(handle, u)
     }
 
-    pub fn get_storage(&self, id: usize) -> Result<&Storages, CoreError> {
-        self.context.get_storage(id)
+    pub fn get_storage(&self, handle: Handle<Storages>) -> Result<&Storages, CoreError> {
+        self.context.get_storage(handle)
     }
 
-    pub fn get_storage_mut(&mut self, id: usize) -> Result<&mut Storages, CoreError> {
-        self.context.get_storage_mut(id)
+    pub fn get_storage_mut(
+        &mut self,
+        handle: Handle<Storages>,
+    ) -> Result<&mut Storages, CoreError> {
+        self.context.get_storage_mut(handle)
     }
 
-    pub fn get_storage_ref(&self, id: usize) -> Result<Ref<Storages>, CoreError> {
-        self.context.get_storage_ref(id)
+    pub fn get_storage_ref(&self, handle: Handle<Storages>) -> Result<Arc<Storages>, CoreError> {
+        self.context.get_storage_ref(handle)
     }
 
-    pub fn take_storage(&mut self, id: usize) -> Result<Storages, CoreError> {
-        self.context.take_storage(id)
+    pub fn take_storage(&mut self, handle: Handle<Storages>) -> Result<Storages, CoreError> {
+        self.context.take_storage(handle)
     }
 
     // Uniforms
-    pub fn create_uniform_id(&self) -> (usize, UniformsBuilder<'_>) {
-        let id = self.context.generate_unique_id();
-        (id, UniformsBuilder::new_indexed(&self.device, id))
+    pub fn create_uniform_id(&self) -> (Handle<Uniforms>, UniformsBuilder<'_>) {
+        let handle = self.context.reserve_uniform();
+        (
+            handle,
+            UniformsBuilder::new_indexed(&self.device, handle.index()),
+        )
     }
 
     pub fn create_uniform(&self) -> UniformsBuilder<'_> {
         UniformsBuilder::new(&self.device)
     }
 
-    pub fn add_uniform(&mut self, u: Uniforms) {
+    pub fn add_uniform(&mut self, u: Uniforms) -> Handle<Uniforms> {
         self.context.add_uniform(u)
     }
 
-    pub fn replace_uniform(&mut self, id: usize, u: Uniforms) -> Result<(), CoreError> {
-        self.context.replace_uniform(id, u)
+    pub fn replace_uniform(
+        &mut self,
+        handle: Handle<Uniforms>,
+        u: Uniforms,
+    ) -> Result<(), CoreError> {
+        self.context.replace_uniform(handle, u)
     }
 
-    pub fn get_uniform(&self, id: usize) -> Result<&Uniforms, CoreError> {
-        self.context.get_uniform(id)
+    pub fn get_uniform(&self, handle: Handle<Uniforms>) -> Result<&Uniforms, CoreError> {
+        self.context.get_uniform(handle)
     }
 
-    pub fn get_uniform_mut(&mut self, id: usize) -> Result<&mut Uniforms, CoreError> {
-        self.context.get_uniform_mut(id)
+    pub fn get_uniform_mut(
+        &mut self,
+        handle: Handle<Uniforms>,
+    ) -> Result<&mut Uniforms, CoreError> {
+        self.context.get_uniform_mut(handle)
     }
 
-    pub fn get_uniform_ref(&self, id: usize) -> Result<Ref<Uniforms>, CoreError> {
-        self.context.get_uniform_ref(id)
+    pub fn get_uniform_ref(&self, handle: Handle<Uniforms>) -> Result<Arc<Uniforms>, CoreError> {
+        self.context.get_uniform_ref(handle)
     }
 
-    pub fn take_uniform(&mut self, id: usize) -> Result<Uniforms, CoreError> {
-        self.context.take_uniform(id)
+    pub fn take_uniform(&mut self, handle: Handle<Uniforms>) -> Result<Uniforms, CoreError> {
+        self.context.take_uniform(handle)
     }
 
     // Model
-    pub fn create_model_id(&self) -> (usize, ModelBuilder<'_>) {
-        let id = self.context.generate_unique_id();
-        (id, ModelBuilder::new_indexed(&self.device, id))
+    pub fn create_model_id(&self) -> (Handle<Model>, ModelBuilder<'_>) {
+        let handle = self.context.reserve_model();
+        (
+            handle,
+            ModelBuilder::new_indexed(&self.device, handle.index())
+                .vertex_storage(self.vertex_storage()),
+        )
     }
 
     pub fn create_model(&self) -> ModelBuilder<'_> {
-        ModelBuilder::new(&self.device)
+        ModelBuilder::new(&self.device).vertex_storage(self.vertex_storage())
     }
 
-    pub fn add_model(&mut self, m: Model) {
+    pub fn add_model(&mut self, m: Model) -> Handle<Model> {
         self.context.add_model(m)
     }
 
-    pub fn replace_model(&mut self, id: usize, m: Model) -> Result<(), CoreError> {
-        self.context.replace_model(id, m)
+    pub fn replace_model(&mut self, handle: Handle<Model>, m: Model) -> Result<(), CoreError> {
+        self.context.replace_model(handle, m)
     }
 
-    pub fn get_model(&self, id: usize) -> Result<&Model, CoreError> {
-        self.context.get_model(id)
+    pub fn get_model(&self, handle: Handle<Model>) -> Result<&Model, CoreError> {
+        self.context.get_model(handle)
     }
 
-    pub fn get_model_mut(&mut self, id: usize) -> Result<&mut Model, CoreError> {
-        self.context.get_model_mut(id)
+    pub fn get_model_mut(&mut self, handle: Handle<Model>) -> Result<&mut Model, CoreError> {
+        self.context.get_model_mut(handle)
     }
 
-    pub fn get_model_ref(&self, id: usize) -> Result<Ref<Model>, CoreError> {
-        self.context.get_model_ref(id)
+    pub fn get_model_ref(&self, handle: Handle<Model>) -> Result<Arc<Model>, CoreError> {
+        self.context.get_model_ref(handle)
     }
 
-    pub fn take_model(&mut self, id: usize) -> Result<Model, CoreError> {
-        self.context.take_model(id)
+    pub fn take_model(&mut self, handle: Handle<Model>) -> Result<Model, CoreError> {
+        self.context.take_model(handle)
     }
 
     // Buffer
-    pub fn create_buffer_id(&self) -> (usize, BufferBuilder<'_>) {
-        let id = self.context.generate_unique_id();
-        (id, BufferBuilder::new_indexed(&self.device, id))
+    pub fn create_buffer_id(&self) -> (Handle<Buffer>, BufferBuilder<'_>) {
+        let handle = self.context.reserve_buffer();
+        (
+            handle,
+            BufferBuilder::new_indexed(&self.device, handle.index()),
+        )
     }
 
     pub fn create_buffer(&self) -> BufferBuilder<'_> {
         BufferBuilder::new(&self.device)
     }
 
-    pub fn add_buffer(&mut self, b: Buffer) {
+    pub fn add_buffer(&mut self, b: Buffer) -> Handle<Buffer> {
         self.context.add_buffer(b)
     }
 
-    pub fn replace_buffer(&mut self, id: usize, b: Buffer) -> Result<(), CoreError> {
-        self.context.replace_buffer(id, b)
+    pub fn replace_buffer(&mut self, handle: Handle<Buffer>, b: Buffer) -> Result<(), CoreError> {
+        self.context.replace_buffer(handle, b)
     }
 
-    pub fn get_buffer(&self, id: usize) -> Result<&Buffer, CoreError> {
-        self.context.get_buffer(id)
+    pub fn get_buffer(&self, handle: Handle<Buffer>) -> Result<&Buffer, CoreError> {
+        self.context.get_buffer(handle)
     }
 
-    pub fn get_buffer_mut(&mut self, id: usize) -> Result<&mut Buffer, CoreError> {
-        self.context.get_buffer_mut(id)
+    pub fn get_buffer_mut(&mut self, handle: Handle<Buffer>) -> Result<&mut Buffer, CoreError> {
+        self.context.get_buffer_mut(handle)
     }
 
-    pub fn get_buffer_ref(&self, id: usize) -> Result<Ref<Buffer>, CoreError> {
-        self.context.get_buffer_ref(id)
+    pub fn get_buffer_ref(&self, handle: Handle<Buffer>) -> Result<Arc<Buffer>, CoreError> {
+        self.context.get_buffer_ref(handle)
     }
 
-    pub fn take_buffer(&mut self, id: usize) -> Result<Buffer, CoreError> {
-        self.context.take_buffer(id)
+    pub fn take_buffer(&mut self, handle: Handle<Buffer>) -> Result<Buffer, CoreError> {
+        self.context.take_buffer(handle)
     }
 
     // Bind group
-    pub fn create_bind_group_layout_id(&self) -> (usize, BindGroupLayoutBuilder<'_>) {
-        let id = self.context.generate_unique_id();
-        (id, BindGroupLayoutBuilder::new_indexed(&self.device, id))
+    pub fn create_bind_group_layout_id(
+        &self,
+    ) -> (Handle<BindGroupLayout>, BindGroupLayoutBuilder<'_>) {
+        let handle = self.context.reserve_bind_group_layout();
+        (
+            handle,
+            BindGroupLayoutBuilder::new_indexed(&self.device, handle.index()),
+        )
     }
 
     pub fn create_bind_group_layout(&self) -> BindGroupLayoutBuilder<'_> {
         BindGroupLayoutBuilder::new(&self.device)
     }
 
-    pub fn get_bind_group_layout(&self, id: usize) -> Result<&BindGroupLayout, CoreError> {
-        self.context.get_bind_group_layout(id)
+    pub fn get_bind_group_layout(
+        &self,
+        handle: Handle<BindGroupLayout>,
+    ) -> Result<&BindGroupLayout, CoreError> {
+        self.context.get_bind_group_layout(handle)
     }
 
     pub fn get_bind_group_layout_mut(
         &mut self,
-        id: usize,
+        handle: Handle<BindGroupLayout>,
     ) -> Result<&mut BindGroupLayout, CoreError> {
-        self.context.get_bind_group_layout_mut(id)
+        self.context.get_bind_group_layout_mut(handle)
     }
 
-    pub fn get_bind_group_layout_ref(&self, id: usize) -> Result<Ref<BindGroupLayout>, CoreError> {
-        self.context.get_bind_group_layout_ref(id)
+    pub fn get_bind_group_layout_ref(
+        &self,
+        handle: Handle<BindGroupLayout>,
+    ) -> Result<Arc<BindGroupLayout>, CoreError> {
+        self.context.get_bind_group_layout_ref(handle)
     }
 
-    pub fn add_bind_group_layout(&mut self, bgl: BindGroupLayout) {
+    pub fn add_bind_group_layout(&mut self, bgl: BindGroupLayout) -> Handle<BindGroupLayout> {
         self.context.add_bind_group_layout(bgl)
     }
 
     pub fn replace_bind_group_layout(
         &mut self,
-        id: usize,
+        handle: Handle<BindGroupLayout>,
         bgl: BindGroupLayout,
     ) -> Result<(), CoreError> {
-        self.context.replace_bind_group_layout(id, bgl)
+        self.context.replace_bind_group_layout(handle, bgl)
     }
 
-    pub fn take_bind_group_layout(&mut self, id: usize) -> Result<BindGroupLayout, CoreError> {
-        self.context.take_bind_group_layout(id)
+    pub fn take_bind_group_layout(
+        &mut self,
+        handle: Handle<BindGroupLayout>,
+    ) -> Result<BindGroupLayout, CoreError> {
+        self.context.take_bind_group_layout(handle)
     }
 
-    pub fn create_bind_group_id(&self) -> (usize, BindGroupBuilder<'_>) {
-        let id = self.context.generate_unique_id();
-        (id, BindGroupBuilder::new_indexed(&self.device, id))
+    pub fn create_bind_group_id(&self) -> (Handle<BindGroup>, BindGroupBuilder<'_>) {
+        let handle = self.context.reserve_bind_group();
+        (
+            handle,
+            BindGroupBuilder::new_indexed(&self.device, handle.index()),
+        )
     }
 
     pub fn create_bind_group(&self) -> BindGroupBuilder<'_> {
         BindGroupBuilder::new(&self.device)
     }
 
-    pub fn get_bind_group(&self, id: usize) -> Result<&BindGroup, CoreError> {
-        self.context.get_bind_group(id)
+    pub fn get_bind_group(&self, handle: Handle<BindGroup>) -> Result<&BindGroup, CoreError> {
+        self.context.get_bind_group(handle)
     }
 
-    pub fn get_bind_group_mut(&mut self, id: usize) -> Result<&mut BindGroup, CoreError> {
-        self.context.get_bind_group_mut(id)
+    pub fn get_bind_group_mut(
+        &mut self,
+        handle: Handle<BindGroup>,
+    ) -> Result<&mut BindGroup, CoreError> {
+        self.context.get_bind_group_mut(handle)
     }
 
-    pub fn get_bind_group_ref(&self, id: usize) -> Result<Ref<BindGroup>, CoreError> {
-        self.context.get_bind_group_ref(id)
+    pub fn get_bind_group_ref(
+        &self,
+        handle: Handle<BindGroup>,
+    ) -> Result<Arc<BindGroup>, CoreError> {
+        self.context.get_bind_group_ref(handle)
     }
 
-    pub fn add_bind_group(&mut self, bg: BindGroup) {
+    pub fn add_bind_group(&mut self, bg: BindGroup) -> Handle<BindGroup> {
         self.context.add_bind_group(bg)
     }
 
-    pub fn replace_bind_group(&mut self, id: usize, bg: BindGroup) -> Result<(), CoreError> {
-        self.context.replace_bind_group(id, bg)
+    pub fn replace_bind_group(
+        &mut self,
+        handle: Handle<BindGroup>,
+        bg: BindGroup,
+    ) -> Result<(), CoreError> {
+        self.context.replace_bind_group(handle, bg)
     }
 
-    pub fn take_bind_group(&mut self, id: usize) -> Result<BindGroup, CoreError> {
-        self.context.take_bind_group(id)
+    pub fn take_bind_group(&mut self, handle: Handle<BindGroup>) -> Result<BindGroup, CoreError> {
+        self.context.take_bind_group(handle)
     }
 
     // Pipeline
-    pub fn create_pipeline_layout_id(&self) -> (usize, PipelineLayoutBuilder<'_>) {
-        let id = self.context.generate_unique_id();
-        (id, PipelineLayoutBuilder::new_indexed(&self.device, id))
+    pub fn create_pipeline_layout_id(&self) -> (Handle<PipelineLayout>, PipelineLayoutBuilder<'_>) {
+        let handle = self.context.reserve_pipeline_layout();
+        (
+            handle,
+            PipelineLayoutBuilder::new_indexed(&self.device, handle.index()),
+        )
     }
 
     pub fn create_pipeline_layout(&self) -> PipelineLayoutBuilder<'_> {
         PipelineLayoutBuilder::new(&self.device)
     }
 
-    pub fn get_pipeline_layout(&self, id: usize) -> Result<&PipelineLayout, CoreError> {
-        self.context.get_pipeline_layout(id)
+    pub fn get_pipeline_layout(
+        &self,
+        handle: Handle<PipelineLayout>,
+    ) -> Result<&PipelineLayout, CoreError> {
+        self.context.get_pipeline_layout(handle)
     }
 
-    pub fn get_pipeline_layout_mut(&mut self, id: usize) -> Result<&mut PipelineLayout, CoreError> {
-        self.context.get_pipeline_layout_mut(id)
+    pub fn get_pipeline_layout_mut(
+        &mut self,
+        handle: Handle<PipelineLayout>,
+    ) -> Result<&mut PipelineLayout, CoreError> {
+        self.context.get_pipeline_layout_mut(handle)
     }
 
-    pub fn get_pipeline_layout_ref(&self, id: usize) -> Result<Ref<PipelineLayout>, CoreError> {
-        self.context.get_pipeline_layout_ref(id)
+    pub fn get_pipeline_layout_ref(
+        &self,
+        handle: Handle<PipelineLayout>,
+    ) -> Result<Arc<PipelineLayout>, CoreError> {
+        self.context.get_pipeline_layout_ref(handle)
     }
 
-    pub fn add_pipeline_layout(&mut self, pl: PipelineLayout) {
+    pub fn add_pipeline_layout(&mut self, pl: PipelineLayout) -> Handle<PipelineLayout> {
         self.context.add_pipeline_layout(pl)
     }
 
     pub fn replace_pipeline_layout(
         &mut self,
-        id: usize,
+        handle: Handle<PipelineLayout>,
         pl: PipelineLayout,
     ) -> Result<(), CoreError> {
-        self.context.replace_pipeline_layout(id, pl)
+        self.context.replace_pipeline_layout(handle, pl)
     }
 
-    pub fn take_pipeline_layout(&mut self, id: usize) -> Result<PipelineLayout, CoreError> {
-        self.context.take_pipeline_layout(id)
+    pub fn take_pipeline_layout(
+        &mut self,
+        handle: Handle<PipelineLayout>,
+    ) -> Result<PipelineLayout, CoreError> {
+        self.context.take_pipeline_layout(handle)
     }
 
-    pub fn create_pipeline_id(&self) -> (usize, PipelineBuilder<'_>) {
-        let id = self.context.generate_unique_id();
-        (id, PipelineBuilder::new_indexed(&self.device, id))
+    pub fn create_pipeline_id(&self) -> (Handle<Pipeline>, PipelineBuilder<'_>) {
+        let handle = self.context.reserve_pipeline();
+        (
+            handle,
+            PipelineBuilder::new_indexed(&self.device, handle.index()),
+        )
     }
 
     pub fn create_pipeline(&self) -> PipelineBuilder<'_> {
         PipelineBuilder::new(&self.device)
     }
 
-    pub fn get_pipeline(&self, id: usize) -> Result<&Pipeline, CoreError> {
-        self.context.get_pipeline(id)
+    pub fn get_pipeline(&self, handle: Handle<Pipeline>) -> Result<&Pipeline, CoreError> {
+        self.context.get_pipeline(handle)
     }
 
-    pub fn get_pipeline_ref(&self, id: usize) -> Result<Ref<Pipeline>, CoreError> {
-        self.context.get_pipeline_ref(id)
+    pub fn get_pipeline_ref(&self, handle: Handle<Pipeline>) -> Result<Arc<Pipeline>, CoreError> {
+        self.context.get_pipeline_ref(handle)
     }
 
-    pub fn get_pipeline_mut(&mut self, id: usize) -> Result<&mut Pipeline, CoreError> {
-        self.context.get_pipeline_mut(id)
+    pub fn get_pipeline_mut(
+        &mut self,
+        handle: Handle<Pipeline>,
+    ) -> Result<&mut Pipeline, CoreError> {
+        self.context.get_pipeline_mut(handle)
     }
 
-    pub fn add_pipeline(&mut self, p: Pipeline) {
+    pub fn add_pipeline(&mut self, p: Pipeline) -> Handle<Pipeline> {
         self.context.add_pipeline(p)
     }
 
-    pub fn replace_pipeline(&mut self, id: usize, p: Pipeline) -> Result<(), CoreError> {
-        self.context.replace_pipeline(id, p)
+    pub fn replace_pipeline(
+        &mut self,
+        handle: Handle<Pipeline>,
+        p: Pipeline,
+    ) -> Result<(), CoreError> {
+        self.context.replace_pipeline(handle, p)
     }
 
-    pub fn take_pipeline(&mut self, id: usize) -> Result<Pipeline, CoreError> {
-        self.context.take_pipeline(id)
+    pub fn take_pipeline(&mut self, handle: Handle<Pipeline>) -> Result<Pipeline, CoreError> {
+        self.context.take_pipeline(handle)
     }
 
     // Shader
-    pub fn create_shader_id(&self) -> (usize, ShaderBuilder<'_>) {
-        let id = self.context.generate_unique_id();
-        (id, ShaderBuilder::new_indexed(&self.device, id))
+    pub fn create_shader_id(&self) -> (Handle<Shader>, ShaderBuilder<'_>) {
+        let handle = self.context.reserve_shader();
+        (
+            handle,
+            ShaderBuilder::new_indexed(&self.device, handle.index()),
+        )
     }
 
     pub fn create_shader(&self) -> ShaderBuilder<'_> {
         ShaderBuilder::new(&self.device)
     }
 
-    pub fn get_shader(&self, id: usize) -> Result<&Shader, CoreError> {
-        self.context.get_shader(id)
+    pub fn get_shader(&self, handle: Handle<Shader>) -> Result<&Shader, CoreError> {
+        self.context.get_shader(handle)
     }
 
-    pub fn get_shader_mut(&mut self, id: usize) -> Result<&mut Shader, CoreError> {
-        self.context.get_shader_mut(id)
+    pub fn get_shader_mut(&mut self, handle: Handle<Shader>) -> Result<&mut Shader, CoreError> {
+        self.context.get_shader_mut(handle)
     }
 
-    pub fn get_shader_ref(&self, id: usize) -> Result<Ref<Shader>, CoreError> {
-        self.context.get_shader_ref(id)
+    pub fn get_shader_ref(&self, handle: Handle<Shader>) -> Result<Arc<Shader>, CoreError> {
+        self.context.get_shader_ref(handle)
     }
 
-    pub fn add_shader(&mut self, sh: Shader) {
+    pub fn add_shader(&mut self, sh: Shader) -> Handle<Shader> {
         self.context.add_shader(sh)
     }
 
-    pub fn replace_shader(&mut self, id: usize, sh: Shader) -> Result<(), CoreError> {
-        self.context.replace_shader(id, sh)
+    pub fn replace_shader(&mut self, handle: Handle<Shader>, sh: Shader) -> Result<(), CoreError> {
+        self.context.replace_shader(handle, sh)
     }
 
-    pub fn take_shader(&mut self, id: usize) -> Result<Shader, CoreError> {
-        self.context.take_shader(id)
+    pub fn take_shader(&mut self, handle: Handle<Shader>) -> Result<Shader, CoreError> {
+        self.context.take_shader(handle)
     }
 
     // Render texture
-    pub fn create_render_texture_id(&self) -> (usize, RenderTextureBuilder<'_>) {
-        let id = self.context.generate_unique_id();
-        (id, RenderTextureBuilder::new_indexed(&self.device, id))
+    pub fn create_render_texture_id(&self) -> (Handle<RenderTexture>, RenderTextureBuilder<'_>) {
+        let handle = self.context.reserve_render_texture();
+        (
+            handle,
+            RenderTextureBuilder::new_indexed(&self.device, handle.index()),
+        )
     }
 
     pub fn create_render_texture(&self) -> RenderTextureBuilder<'_> {
         RenderTextureBuilder::new(&self.device)
     }
 
-    pub fn get_render_texture(&self, id: usize) -> Result<&RenderTexture, CoreError> {
-        self.context.get_render_texture(id)
+    pub fn get_render_texture(
+        &self,
+        handle: Handle<RenderTexture>,
+    ) -> Result<&RenderTexture, CoreError> {
+        self.context.get_render_texture(handle)
     }
 
-    pub fn get_render_texture_mut(&mut self, id: usize) -> Result<&mut RenderTexture, CoreError> {
-        self.context.get_render_texture_mut(id)
+    pub fn get_render_texture_mut(
+        &mut self,
+        handle: Handle<RenderTexture>,
+    ) -> Result<&mut RenderTexture, CoreError> {
+        self.context.get_render_texture_mut(handle)
     }
 
-    pub fn get_render_texture_ref(&self, id: usize) -> Result<Ref<RenderTexture>, CoreError> {
-        self.context.get_render_texture_ref(id)
+    pub fn get_render_texture_ref(
+        &self,
+        handle: Handle<RenderTexture>,
+    ) -> Result<Arc<RenderTexture>, CoreError> {
+        self.context.get_render_texture_ref(handle)
     }
 
-    pub fn add_render_texture(&mut self, rt: RenderTexture) {
+    pub fn add_render_texture(&mut self, rt: RenderTexture) -> Handle<RenderTexture> {
         self.load_texture(&rt);
         self.context.add_render_texture(rt)
     }
 
     pub fn replace_render_texture(
         &mut self,
-        id: usize,
+        handle: Handle<RenderTexture>,
         rt: RenderTexture,
     ) -> Result<(), CoreError> {
-        self.context.replace_render_texture(id, rt)
+        self.context.replace_render_texture(handle, rt)
     }
 
-    pub fn take_render_texture(&mut self, id: usize) -> Result<RenderTexture, CoreError> {
-        self.context.take_render_texture(id)
+    pub fn take_render_texture(
+        &mut self,
+        handle: Handle<RenderTexture>,
+    ) -> Result<RenderTexture, CoreError> {
+        self.context.take_render_texture(handle)
     }
 
     // Depth texture
-    pub fn create_depth_texture_id(&self) -> (usize, DepthTextureBuilder<'_>) {
-        let id = self.context.generate_unique_id();
-        (id, DepthTextureBuilder::new_indexed(&self.device, id))
+    pub fn create_depth_texture_id(&self) -> (Handle<DepthTexture>, DepthTextureBuilder<'_>) {
+        let handle = self.context.reserve_depth_texture();
+        (
+            handle,
+            DepthTextureBuilder::new_indexed(&self.device, handle.index()),
+        )
     }
 
     pub fn create_depth_texture(&self) -> DepthTextureBuilder<'_> {
         DepthTextureBuilder::new(&self.device)
     }
 
-    pub fn get_depth_texture(&self, id: usize) -> Result<&DepthTexture, CoreError> {
-        self.context.get_depth_texture(id)
+    pub fn get_depth_texture(
+        &self,
+        handle: Handle<DepthTexture>,
+    ) -> Result<&DepthTexture, CoreError> {
+        self.context.get_depth_texture(handle)
     }
 
-    pub fn get_depth_texture_mut(&mut self, id: usize) -> Result<&mut DepthTexture, CoreError> {
-        self.context.get_depth_texture_mut(id)
+    pub fn get_depth_texture_mut(
+        &mut self,
+        handle: Handle<DepthTexture>,
+    ) -> Result<&mut DepthTexture, CoreError> {
+        self.context.get_depth_texture_mut(handle)
     }
 
-    pub fn get_depth_texture_ref(&self, id: usize) -> Result<Ref<DepthTexture>, CoreError> {
-        self.context.get_depth_texture_ref(id)
+    pub fn get_depth_texture_ref(
+        &self,
+        handle: Handle<DepthTexture>,
+    ) -> Result<Arc<DepthTexture>, CoreError> {
+        self.context.get_depth_texture_ref(handle)
     }
 
-    pub fn add_depth_texture(&mut self, rt: DepthTexture) {
+    pub fn add_depth_texture(&mut self, rt: DepthTexture) -> Handle<DepthTexture> {
         self.context.add_depth_texture(rt)
     }
 
-    pub fn replace_depth_texture(&mut self, id: usize, rt: DepthTexture) -> Result<(), CoreError> {
-        self.context.replace_depth_texture(id, rt)
+    pub fn replace_depth_texture(
+        &mut self,
+        handle: Handle<DepthTexture>,
+        rt: DepthTexture,
+    ) -> Result<(), CoreError> {
+        self.context.replace_depth_texture(handle, rt)
+    }
+
+    pub fn take_depth_texture(
+        &mut self,
+        handle: Handle<DepthTexture>,
+    ) -> Result<DepthTexture, CoreError> {
+        self.context.take_depth_texture(handle)
+    }
+
+    // Render bundles
+    pub fn create_render_bundle_id(&self) -> (Handle<RenderBundle>, RenderBundleBuilder<'_>) {
+        let handle = self.context.reserve_render_bundle();
+        (
+            handle,
+            RenderBundleBuilder::new_indexed(&self.device, handle.index()),
+        )
+    }
+
+    pub fn create_render_bundle(&self) -> RenderBundleBuilder<'_> {
+        RenderBundleBuilder::new(&self.device)
+    }
+
+    pub fn add_render_bundle(&mut self, rb: RenderBundle) -> Handle<RenderBundle> {
+        self.context.add_render_bundle(rb)
+    }
+
+    pub fn replace_render_bundle(
+        &mut self,
+        handle: Handle<RenderBundle>,
+        rb: RenderBundle,
+    ) -> Result<(), CoreError> {
+        self.context.replace_render_bundle(handle, rb)
+    }
+
+    // Invalidated bundles must be recorded again and replaced
+    pub fn get_render_bundle(
+        &self,
+        handle: Handle<RenderBundle>,
+    ) -> Result<&RenderBundle, CoreError> {
+        let rb = self.context.get_render_bundle(handle)?;
+
+        if rb.is_valid() {
+            Ok(rb)
+        } else {
+            Err(CoreError::RenderBundleInvalidated(rb.label().to_string()))
+        }
+    }
+
+    pub fn get_render_bundle_ref(
+        &self,
+        handle: Handle<RenderBundle>,
+    ) -> Result<Arc<RenderBundle>, CoreError> {
+        self.context.get_render_bundle_ref(handle)
+    }
+
+    pub fn is_render_bundle_valid(&self, handle: Handle<RenderBundle>) -> bool {
+        self.context
+            .get_render_bundle(handle)
+            .is_ok_and(RenderBundle::is_valid)
+    }
+
+    pub fn take_render_bundle(
+        &mut self,
+        handle: Handle<RenderBundle>,
+    ) -> Result<RenderBundle, CoreError> {
+        self.context.take_render_bundle(handle)
     }
 
-    pub fn take_depth_texture(&mut self, id: usize) -> Result<DepthTexture, CoreError> {
-        self.context.take_depth_texture(id)
+    // Query sets
+    pub fn create_query_set(&self) -> QuerySetBuilder<'_> {
+        QuerySetBuilder::new(&self.device)
     }
 
     // Process texture
@@ -1,17 +1,32 @@
-use std::mem::size_of_val;
+use std::{iter::once, mem::size_of_val, slice};
 
-use image::{ImageBuffer, Rgba};
-use log::{debug, info, warn};
+use custom_engine_models::gltf::Transform;
+use log::{debug, error, info, warn};
 use pollster::block_on;
 
 use crate::{
     buffer::Buffer,
+    clock::FrameClock,
+    context::Handle,
     errors::CoreError,
-    model::Model,
-    render_pass::RenderPass,
-    runtime::ImageFormat,
+    model::{
+        material::Material,
+        morph::{MorphWeights, MORPH_WEIGHTS_OFFSET},
+        Model,
+    },
+    profiler::{FrameProfile, GpuProfiler},
+    render_graph::{GraphPass, RenderGraph},
+    render_pass::{
+        color_attachment::ColorAttachmentBuilder,
+        query_set::{OcclusionQuery, QuerySet},
+        RenderPass,
+    },
+    runtime::{AdapterCapabilities, ImageFormat},
     storage::Storages,
-    texture::{CopyTextureParams, RenderTexture},
+    texture::{
+        CopyTextureParams, MipmapGenerator, MsaaTextures, ReadbackRegion, RenderTexture,
+        TextureKind, TextureLayout, TextureReadback,
+    },
     traits::Builder,
     uniform::Uniforms,
     worker::{View, ViewTexture, Worker},
@@ -53,11 +68,17 @@ impl<'a> Worker<'a> {
         if new_size.0 > 0 && new_size.1 > 0 {
             self.size = new_size;
 
-            self.surface_properties.config.width = new_size.0;
-            self.surface_properties.config.height = new_size.1;
-            self.surface_properties
-                .surface
-                .configure(&self.device, &self.surface_properties.config);
+            if let Some(s_p) = self.surface_properties.as_mut() {
+                s_p.config.width = new_size.0;
+                s_p.config.height = new_size.1;
+                s_p.surface.configure(&self.device, &s_p.config);
+            } else if let Err(e) = self.init_offscreen() {
+                error!("{e}");
+            }
+
+            if let Err(e) = self.init_msaa() {
+                error!("{e}");
+            }
         }
     }
 
@@ -77,36 +98,126 @@ impl<'a> Worker<'a> {
                 }) => render_pass
                     .copy_params(CopyTextureParams::new(buffer, render_texture))
                     .render(&self.queue)?,
-                View::Surface(_) => render_pass.render(&self.queue)?,
+                View::Surface(_) | View::Offscreen => render_pass.render(&self.queue)?,
             }
         }
 
         Ok(())
     }
 
+    // Passes are ordered by their dependencies and submitted in one command buffer
+    pub fn render_graph(&self, passes: Vec<GraphPass<'_>>) -> Result<(), CoreError> {
+        let passes = RenderGraph::order(passes)?;
+
+        let mut encoder = self
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                label: Some("Command Encoder of `Render graph`"),
+            });
+
+        for p in passes {
+            debug!("Encode `{}` of `Render graph`", p.name());
+            p.pass.encode(&mut encoder)?;
+        }
+
+        if let Some(View::Texture(ViewTexture {
+            render_texture,
+            buffer,
+            ..
+        })) = self.view.as_ref()
+        {
+            CopyTextureParams::new(buffer, render_texture).process(&mut encoder)?;
+        }
+
+        self.queue.submit(once(encoder.finish()));
+        self.push_constants.reset();
+
+        Ok(())
+    }
+
     #[inline]
     pub fn render_pass(&self) -> RenderPass<'_> {
         RenderPass::new(&self.device, 0)
+            .profiler(self.profiler.as_ref())
+            .push_constants_buffers(&self.push_constants, &self.queue)
+    }
+
+    // Renders into the MSAA texture and resolves it into `target`, which has the worker format
+    pub fn color_attachment<'v>(
+        &'v self,
+        target: &'v wgpu::TextureView,
+    ) -> ColorAttachmentBuilder<'v> {
+        let attachment = ColorAttachmentBuilder::new().format(self.format);
+
+        match self.msaa.as_ref() {
+            Some(msaa) => attachment.view(msaa.color.view()).resolve_target(target),
+            None => attachment.view(target),
+        }
     }
 
     // Helpers
     #[inline]
     pub fn load_texture(&self, rt: &RenderTexture) {
         rt.store_to_memory(&self.queue);
+
+        if !rt.generates_mipmaps() {
+            return;
+        }
+        if let Err(e) = self.generate_mipmaps(once(&**rt)) {
+            error!("{e}");
+        }
     }
 
     #[inline]
     pub fn load_model(&self, model: &Model) {
-        model.load(&self.queue)
+        model.load(&self.queue);
+
+        let textures = model
+            .materials()
+            .iter()
+            .flat_map(Material::textures)
+            .filter(|t| t.generates_mipmaps());
+        if let Err(e) = self.generate_mipmaps(textures.map(|t| &**t)) {
+            error!("{e}");
+        }
+    }
+
+    // Mip levels are downsampled from the first one, textures with one level are skipped
+    pub fn generate_mipmaps<'t>(
+        &self,
+        textures: impl IntoIterator<Item = &'t wgpu::Texture>,
+    ) -> Result<(), CoreError> {
+        let mut textures = textures
+            .into_iter()
+            .filter(|t| t.mip_level_count() > 1)
+            .peekable();
+        if textures.peek().is_none() {
+            return Ok(());
+        }
+
+        let mipmaps = self
+            .mipmaps
+            .get_or_init(|| MipmapGenerator::new(&self.device));
+        let mut encoder = self
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                label: Some("Command Encoder of `Mipmaps`"),
+            });
+        for t in textures {
+            mipmaps.generate(&self.device, &mut encoder, t)?;
+        }
+        self.queue.submit(once(encoder.finish()));
+
+        Ok(())
     }
 
     pub fn update_uniform<T: bytemuck::Pod + bytemuck::Zeroable>(
         &self,
-        id: usize,
+        handle: Handle<Uniforms>,
         name: &str,
         data: &'_ [T],
     ) -> Result<(), CoreError> {
-        let uniform = self.get_uniform_ref(id)?;
+        let uniform = self.get_uniform_ref(handle)?;
 
         self.update_uniform_direct(&uniform, name, data)
     }
@@ -124,13 +235,40 @@ impl<'a> Worker<'a> {
         self.update_buffer_data(buffer, 0, data)
     }
 
+    // Writes the record of the dynamic uniform at the offset returned by `Uniforms::allocate_record`
+    pub fn update_uniform_record<T: bytemuck::Pod + bytemuck::Zeroable>(
+        &self,
+        handle: Handle<Uniforms>,
+        name: &str,
+        offset: u32,
+        data: &'_ T,
+    ) -> Result<(), CoreError> {
+        let uniform = self.get_uniform_ref(handle)?;
+
+        self.update_uniform_record_direct(&uniform, name, offset, data)
+    }
+
+    pub fn update_uniform_record_direct<T: bytemuck::Pod + bytemuck::Zeroable>(
+        &self,
+        uniform: &'_ Uniforms,
+        name: &str,
+        offset: u32,
+        data: &'_ T,
+    ) -> Result<(), CoreError> {
+        let buffer = uniform
+            .get_buffer(name)
+            .ok_or(CoreError::UniformBufferNotFound(name.to_string()))?;
+
+        self.update_buffer_data(buffer, offset as u64, slice::from_ref(data))
+    }
+
     pub fn update_storage<T: bytemuck::Pod + bytemuck::Zeroable>(
         &self,
-        id: usize,
+        handle: Handle<Storages>,
         name: &str,
         data: &'_ [T],
     ) -> Result<(), CoreError> {
-        let storage = self.get_storage_ref(id)?;
+        let storage = self.get_storage_ref(handle)?;
 
         self.update_storage_direct(&storage, name, data)
     }
@@ -150,11 +288,11 @@ impl<'a> Worker<'a> {
 
     pub fn update_buffer<T: bytemuck::Pod + bytemuck::Zeroable>(
         &self,
-        id: usize,
+        handle: Handle<Buffer>,
         offset: u64,
         data: &'_ [T],
     ) -> Result<(), CoreError> {
-        let buffer = self.get_buffer_ref(id)?;
+        let buffer = self.get_buffer_ref(handle)?;
 
         self.update_buffer_data(&buffer, offset, data)
     }
@@ -168,12 +306,41 @@ impl<'a> Worker<'a> {
         self.update_buffer_data(buffer, offset, data)
     }
 
+    // Pose is the local transforms of the nodes, e.g. `AnimationPlayer::pose`
+    // or the edited `Skeleton::rest_pose`
+    pub fn update_model_pose(&self, model: &Model, pose: &[Transform]) -> Result<(), CoreError> {
+        for (node, transform) in model.node_transforms(pose) {
+            self.update_buffer_data(node.transform_buffer(), 0, &[transform])?;
+        }
+        for (skin, joint_matrices) in model.joint_matrices(pose) {
+            self.update_buffer_data(skin.joints_buffer(), 0, &joint_matrices)?;
+        }
+        for (morph_weights, weights) in model.morph_weights(pose) {
+            self.update_morph_weights(morph_weights, weights)?;
+        }
+
+        Ok(())
+    }
+
+    // Weights over the count of the targets are rejected
+    pub fn update_morph_weights(
+        &self,
+        morph_weights: &MorphWeights,
+        weights: &[f32],
+    ) -> Result<(), CoreError> {
+        self.update_buffer_data(
+            morph_weights.weights_buffer(),
+            MORPH_WEIGHTS_OFFSET,
+            weights,
+        )
+    }
+
     pub fn read_uniform<T: bytemuck::Pod + bytemuck::Zeroable>(
         &self,
-        id: usize,
+        handle: Handle<Uniforms>,
         name: &str,
     ) -> Result<Vec<T>, CoreError> {
-        let uniform = self.get_uniform_ref(id)?;
+        let uniform = self.get_uniform_ref(handle)?;
         let buffer = uniform
             .get_buffer(name)
             .ok_or(CoreError::UniformBufferNotFound(name.to_string()))?;
@@ -186,10 +353,10 @@ impl<'a> Worker<'a> {
 
     pub fn read_storage_buffer<T: bytemuck::Pod + bytemuck::Zeroable>(
         &self,
-        id: usize,
+        handle: Handle<Storages>,
         name: &str,
     ) -> Result<Vec<T>, CoreError> {
-        let storage = self.get_storage_ref(id)?;
+        let storage = self.get_storage_ref(handle)?;
         let buffer = storage
             .get_buffer(name)
             .ok_or(CoreError::StorageNotFound(name.to_string()))?;
@@ -202,9 +369,9 @@ impl<'a> Worker<'a> {
 
     pub fn read_buffer<T: bytemuck::Pod + bytemuck::Zeroable>(
         &self,
-        id: usize,
+        handle: Handle<Buffer>,
     ) -> Result<Vec<T>, CoreError> {
-        let buffer = self.get_buffer_ref(id)?;
+        let buffer = self.get_buffer_ref(handle)?;
         let buffer_data = block_on(buffer.read_buffer_async(&self.device))?;
 
         let cast_data: &[T] = bytemuck::cast_slice(&buffer_data);
@@ -223,6 +390,108 @@ impl<'a> Worker<'a> {
         Ok(cast_data.to_vec())
     }
 
+    // Queries are resolved at the end of the render stage, which used the query set
+    pub fn resolve_query_set(&self, query_set: &QuerySet) -> Result<(), CoreError> {
+        let mut encoder = self
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                label: Some("Command Encoder of `Query set`"),
+            });
+        query_set.resolve(&mut encoder)?;
+
+        self.queue.submit(once(encoder.finish()));
+
+        Ok(())
+    }
+
+    pub async fn read_occlusion_queries_async(
+        &self,
+        query_set: &QuerySet,
+    ) -> Result<Vec<OcclusionQuery>, CoreError> {
+        query_set.read_async(&self.device).await
+    }
+
+    pub fn read_occlusion_queries(
+        &self,
+        query_set: &QuerySet,
+    ) -> Result<Vec<OcclusionQuery>, CoreError> {
+        block_on(query_set.read_async(&self.device))
+    }
+
+    // Any mip level and array layer of the texture with `COPY_SRC` usage
+    pub async fn read_texture_async(
+        &self,
+        texture: &wgpu::Texture,
+        region: ReadbackRegion,
+    ) -> Result<TextureReadback, CoreError> {
+        let ReadbackRegion {
+            mip_level,
+            array_layer,
+            aspect,
+        } = region;
+
+        if !texture.usage().contains(wgpu::TextureUsages::COPY_SRC) {
+            return Err(CoreError::TextureNotCopySrc(texture.usage()));
+        }
+        let size = texture
+            .size()
+            .mip_level_size(mip_level, texture.dimension());
+        if mip_level >= texture.mip_level_count() || array_layer >= size.depth_or_array_layers {
+            return Err(CoreError::TextureSubresourceOutOfRange(
+                mip_level,
+                array_layer,
+            ));
+        }
+
+        let layout = TextureLayout::new(texture.format(), aspect, (size.width, size.height))?;
+        let buffer = self
+            .create_buffer()
+            .label("Texture readback buffer")
+            .size(layout.buffer_size())
+            .usage(wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ)
+            .build()?;
+
+        let mut encoder = self
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                label: Some("Command Encoder of `Texture readback`"),
+            });
+        encoder.copy_texture_to_buffer(
+            wgpu::ImageCopyTexture {
+                aspect,
+                texture,
+                mip_level,
+                origin: wgpu::Origin3d {
+                    x: 0,
+                    y: 0,
+                    z: array_layer,
+                },
+            },
+            wgpu::ImageCopyBuffer {
+                buffer: &buffer,
+                layout: layout.data_layout(),
+            },
+            wgpu::Extent3d {
+                depth_or_array_layers: 1,
+                ..size.physical_size(texture.format())
+            },
+        );
+        self.queue.submit(once(encoder.finish()));
+
+        let data = buffer.read_buffer_async(&self.device).await?;
+
+        Ok(TextureReadback::new(&layout, &data))
+    }
+
+    #[inline]
+    pub fn read_texture(
+        &self,
+        texture: &wgpu::Texture,
+        region: ReadbackRegion,
+    ) -> Result<TextureReadback, CoreError> {
+        block_on(self.read_texture_async(texture, region))
+    }
+
     pub fn view_texture(
         &mut self,
         image_format: ImageFormat,
@@ -240,9 +509,10 @@ impl<'a> Worker<'a> {
     }
 
     pub fn view_surface(&mut self) -> Result<wgpu::TextureView, CoreError> {
-        self.view = Some(View::Surface(
-            self.surface_properties.surface.get_current_texture()?,
-        ));
+        self.view = Some(match self.surface_properties.as_ref() {
+            Some(s_p) => View::Surface(s_p.surface.get_current_texture()?),
+            None => View::Offscreen,
+        });
 
         self.view()
     }
@@ -254,18 +524,27 @@ impl<'a> Worker<'a> {
             Some(View::Surface(s)) => s.present(),
             Some(View::Texture(t)) => {
                 let data = block_on(t.buffer.read_buffer_async(&self.device))?;
-                let i_b = ImageBuffer::<Rgba<u8>, _>::from_raw(self.size.0, self.size.1, data)
-                    .ok_or(CoreError::ImageBufferCreate)?;
+                let layout = TextureLayout::new(
+                    t.render_texture.format(),
+                    wgpu::TextureAspect::All,
+                    (t.render_texture.width(), t.render_texture.height()),
+                )?;
+                let image = TextureReadback::new(&layout, &data).into_image()?;
                 let save_path = format!("{}.{}", t.path_to_save.clone(), t.image_format);
 
                 debug!("Save texture to {save_path}");
-                i_b.save(save_path)?;
+                image.to_rgba8().save(save_path)?;
 
                 //self.view = Some(View::Texture(t));
             }
             _ => {}
         }
 
+        if let Some(p) = self.profiler.as_mut() {
+            p.end_frame(&self.device, &self.queue, self.clock.frame());
+        }
+        self.push_constants.reset();
+
         Ok(())
     }
 
@@ -284,6 +563,133 @@ impl<'a> Worker<'a> {
         self.scale_factor
     }
 
+    #[inline]
+    pub fn clock(&self) -> &FrameClock {
+        &self.clock
+    }
+
+    #[inline]
+    pub fn clock_mut(&mut self) -> &mut FrameClock {
+        &mut self.clock
+    }
+
+    #[inline]
+    pub fn adapter(&self) -> &wgpu::Adapter {
+        &self.adapter
+    }
+
+    #[inline]
+    pub fn capabilities(&self) -> &AdapterCapabilities {
+        &self.capabilities
+    }
+
+    #[inline]
+    pub fn features(&self) -> wgpu::Features {
+        self.capabilities.features
+    }
+
+    #[inline]
+    pub fn limits(&self) -> &wgpu::Limits {
+        &self.limits
+    }
+
+    // Skinned and morphed models need it, e.g. WebGL doesn't support it
+    #[inline]
+    pub fn vertex_storage(&self) -> bool {
+        self.capabilities
+            .downlevel
+            .flags
+            .contains(wgpu::DownlevelFlags::VERTEX_STORAGE)
+    }
+
+    // Profiling requires `TIMESTAMP_QUERY`, without it the worker renders as usual
+    pub fn enable_profiler(&mut self) -> bool {
+        if !self.features().contains(wgpu::Features::TIMESTAMP_QUERY) {
+            warn!("`TIMESTAMP_QUERY` is not supported by the device. GPU profiling is disabled");
+            return false;
+        }
+
+        if self.profiler.is_none() {
+            self.profiler = Some(GpuProfiler::new(&self.device, &self.queue));
+        }
+
+        true
+    }
+
+    #[inline]
+    pub fn disable_profiler(&mut self) {
+        self.profiler = None;
+    }
+
+    #[inline]
+    pub fn is_profiling(&self) -> bool {
+        self.profiler.is_some()
+    }
+
+    // Profile of the last frame, which results are read back from GPU
+    #[inline]
+    pub fn frame_profile(&self) -> Option<&FrameProfile> {
+        self.profiler.as_ref().and_then(GpuProfiler::last_profile)
+    }
+
+    #[inline]
+    pub fn sample_count(&self) -> u32 {
+        self.sample_count
+    }
+
+    // Pipelines, which draw into the MSAA textures, are built with this state
+    #[inline]
+    pub fn multisample_state(&self) -> wgpu::MultisampleState {
+        wgpu::MultisampleState {
+            count: self.sample_count,
+            mask: !0,
+            alpha_to_coverage_enabled: false,
+        }
+    }
+
+    pub fn is_sample_count_supported(
+        &self,
+        format: wgpu::TextureFormat,
+        sample_count: u32,
+    ) -> bool {
+        let features = self.features();
+        let format_features =
+            if features.contains(wgpu::Features::TEXTURE_ADAPTER_SPECIFIC_FORMAT_FEATURES) {
+                self.adapter.get_texture_format_features(format)
+            } else {
+                format.guaranteed_format_features(features)
+            };
+
+        format_features.flags.sample_count_supported(sample_count)
+    }
+
+    // MSAA textures are allocated for the worker format and recreated on resize
+    pub fn set_sample_count(&mut self, sample_count: u32) -> Result<(), CoreError> {
+        for format in [self.format, TextureKind::Depth.into()] {
+            if !self.is_sample_count_supported(format, sample_count) {
+                return Err(CoreError::UnsupportedSampleCount(format, sample_count));
+            }
+        }
+
+        self.sample_count = sample_count;
+        self.init_msaa()
+    }
+
+    #[inline]
+    pub fn msaa_textures(&self) -> Option<&MsaaTextures> {
+        self.msaa.as_ref()
+    }
+
+    #[inline]
+    pub fn is_headless(&self) -> bool {
+        self.surface_properties.is_none()
+    }
+
+    #[inline]
+    pub fn offscreen_texture(&self) -> Option<&RenderTexture> {
+        self.offscreen.as_ref()
+    }
+
     // Protected helpers
     //pub(crate) fn init_with_size(&mut self, size: (u32, u32)) -> Result<(), CoreError> {
     //    self.size = size;
@@ -291,11 +697,43 @@ impl<'a> Worker<'a> {
     //    self.init()
     //}
 
+    pub(crate) fn init_offscreen(&mut self) -> Result<(), CoreError> {
+        let t = self
+            .create_render_texture()
+            .label("Offscreen texture")
+            .is_sampler(false)
+            .format(self.format)
+            .texture_size(self.size)
+            .usage(
+                wgpu::TextureUsages::RENDER_ATTACHMENT
+                    | wgpu::TextureUsages::COPY_SRC
+                    | wgpu::TextureUsages::TEXTURE_BINDING,
+            )
+            .build()?;
+        self.offscreen = Some(t);
+
+        Ok(())
+    }
+
     // Private helpers
+    fn init_msaa(&mut self) -> Result<(), CoreError> {
+        self.msaa = if self.sample_count > 1 {
+            Some(MsaaTextures::new(
+                &self.device,
+                self.format,
+                self.size,
+                self.sample_count,
+            )?)
+        } else {
+            None
+        };
+
+        Ok(())
+    }
+
     fn init_runtime_texture(&mut self) -> Result<(RenderTexture, Buffer), CoreError> {
         let format = wgpu::TextureFormat::Rgba8UnormSrgb;
-        let aspect = wgpu::TextureAspect::All;
-        let components = format.components_with_aspect(aspect) as u32;
+        let layout = TextureLayout::new(format, wgpu::TextureAspect::All, self.size)?;
 
         let t = self
             .create_render_texture()
@@ -319,22 +757,32 @@ impl<'a> Worker<'a> {
             .create_buffer()
             .label("Render texture buffer")
             .binding(0)
-            .size((self.size.0 * self.size.1 * components).into())
+            .size(layout.buffer_size())
             .usage(wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ)
             .build()?;
 
+        // MSAA targets must match the format of the view texture
         self.format = format;
+        self.init_msaa()?;
 
         Ok((t, b))
     }
 
     #[inline(always)]
     fn view(&mut self) -> Result<wgpu::TextureView, CoreError> {
-        Ok(self
-            .view
-            .as_ref()
-            .ok_or(CoreError::NotInitView)?
-            .texture_view())
+        let view = match self.view.as_ref().ok_or(CoreError::NotInitView)? {
+            View::Surface(s) => s.texture.create_view(&Default::default()),
+            View::Texture(ViewTexture { render_texture, .. }) => {
+                render_texture.create_view(&Default::default())
+            }
+            View::Offscreen => self
+                .offscreen
+                .as_ref()
+                .ok_or(CoreError::NotInitView)?
+                .create_view(&Default::default()),
+        };
+
+        Ok(view)
     }
 
     fn update_buffer_data<T: bytemuck::Pod + bytemuck::Zeroable>(
@@ -1,21 +1,27 @@
+mod animation;
 mod camera;
 mod document;
 mod material;
 mod mesh;
 mod node;
+mod player;
 mod primitive;
 mod root;
 mod scene;
+mod skin;
 mod texture;
 
+pub use animation::*;
 pub use camera::*;
 pub use document::*;
 pub use material::*;
 pub use mesh::*;
 pub use node::*;
+pub use player::*;
 pub use primitive::*;
 pub use root::*;
 pub use scene::*;
+pub use skin::*;
 pub use texture::*;
 
 use std::path::Path;
@@ -35,20 +41,23 @@ pub struct GltfFile {
 
 impl GltfFile {
     pub fn new(file_name: &str) -> Result<Self> {
-        let (inner, buffers, images) = if cfg!(target_arch = "wasm32") {
+        let base_path = Path::new(file_name);
+
+        // Images aren't decoded on import, textures keep the encoded data of PNG, KTX2 or DDS
+        let (gltf::Gltf { document, blob }, base) = if cfg!(target_arch = "wasm32") {
             let slice = get_data(file_name)
                 .ok_or(anyhow!("File source of `{file_name}` is not availiable"))?;
-            gltf::import_slice(slice)?
+            (gltf::Gltf::from_slice(&slice)?, None)
         } else {
-            gltf::import(file_name)?
+            let base = base_path.parent().unwrap_or_else(|| Path::new("./"));
+            (gltf::Gltf::open(file_name)?, Some(base))
         };
+        let buffers = gltf::import_buffers(&document, base, blob)?;
 
         let doc = Document {
-            inner,
+            inner: document,
             buffers,
-            images,
         };
-        let base_path = Path::new(file_name);
         let name = base_path
             .file_name()
             .ok_or(anyhow!("File name is not available"))?
@@ -75,6 +84,29 @@ impl GltfFile {
         Ok(Scene::new(&scene, &mut self.root))
     }
 
+    pub fn skeleton(&self) -> Skeleton {
+        Skeleton::new(&self.root)
+    }
+
+    // Cameras of all nodes in the order of the nodes, transforms are taken in the rest pose
+    pub fn cameras(&self) -> Vec<CameraNode> {
+        let skeleton = self.skeleton();
+        let globals = skeleton.global_transforms(&skeleton.rest_pose);
+
+        self.root
+            .nodes
+            .iter()
+            .filter_map(|n| {
+                Some(CameraNode {
+                    node: n.index,
+                    name: n.name.clone(),
+                    camera: n.camera.as_deref()?.clone(),
+                    transform: *globals.get(n.index)?,
+                })
+            })
+            .collect()
+    }
+
     pub fn scenes(&mut self) -> Result<Vec<Scene>> {
         let mut scenes = self.doc.inner.scenes();
         let scenes_len = scenes.len();
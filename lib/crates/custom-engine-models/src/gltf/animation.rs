@@ -0,0 +1,338 @@
+use cgmath::{InnerSpace, Matrix4, Quaternion, Vector3, Vector4, VectorSpace};
+use gltf::animation::{util::ReadOutputs, Interpolation};
+use log::warn;
+
+use crate::gltf::Document;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Transform {
+    pub translation: Vector3<f32>,
+    pub rotation: Quaternion<f32>,
+    pub scale: Vector3<f32>,
+    // Morph target weights of the node mesh
+    pub weights: Vec<f32>,
+}
+
+impl Default for Transform {
+    fn default() -> Self {
+        Self {
+            translation: Vector3::new(0., 0., 0.),
+            rotation: Quaternion::new(1., 0., 0., 0.),
+            scale: Vector3::new(1., 1., 1.),
+            weights: vec![],
+        }
+    }
+}
+
+impl Transform {
+    pub fn matrix(&self) -> Matrix4<f32> {
+        Matrix4::from_translation(self.translation)
+            * Matrix4::from(self.rotation)
+            * Matrix4::from_nonuniform_scale(self.scale.x, self.scale.y, self.scale.z)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Property {
+    Translation,
+    Rotation,
+    Scale,
+    // Number of the morph targets
+    Weights(usize),
+}
+
+impl Property {
+    // Weights are packed by four into the outputs of a keyframe
+    pub fn stride(&self) -> usize {
+        match self {
+            Property::Weights(targets) => targets.div_ceil(4),
+            _ => 1,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Channel {
+    pub node: usize,
+    pub property: Property,
+    pub interpolation: Interpolation,
+
+    pub inputs: Vec<f32>,
+    // Vectors have zero `w`, rotations are `xyzw`. Cubic splines store in-tangent, value, out-tangent
+    pub outputs: Vec<Vector4<f32>>,
+}
+
+impl Channel {
+    pub fn sample(&self, time: f32) -> Option<Vector4<f32>> {
+        self.sample_lane(time, 0)
+    }
+
+    pub fn sample_weights(&self, time: f32) -> Vec<f32> {
+        let Property::Weights(targets) = self.property else {
+            return vec![];
+        };
+
+        (0..self.property.stride())
+            .filter_map(|lane| self.sample_lane(time, lane))
+            .flat_map(|v| [v.x, v.y, v.z, v.w])
+            .take(targets)
+            .collect()
+    }
+
+    fn sample_lane(&self, time: f32, lane: usize) -> Option<Vector4<f32>> {
+        let stride = self.property.stride();
+        let output = |i: usize| self.outputs.get(i * stride + lane);
+        let cubic = self.interpolation == Interpolation::CubicSpline;
+        let value = |i: usize| if cubic { output(i * 3 + 1) } else { output(i) };
+
+        let last = self.inputs.len().checked_sub(1)?;
+        if time <= self.inputs[0] {
+            return value(0).copied();
+        }
+        if time >= self.inputs[last] {
+            return value(last).copied();
+        }
+
+        let next = self.inputs.partition_point(|t| *t <= time);
+        let prev = next - 1;
+        let delta = self.inputs[next] - self.inputs[prev];
+        let s = (time - self.inputs[prev]) / delta;
+
+        let (p0, p1) = (*value(prev)?, *value(next)?);
+        let sampled = match self.interpolation {
+            Interpolation::Step => p0,
+            Interpolation::Linear if self.property == Property::Rotation => {
+                let (q0, q1) = (to_quaternion(p0), to_quaternion(p1));
+                let q1 = if q0.dot(q1) < 0. { -q1 } else { q1 };
+
+                from_quaternion(q0.slerp(q1, s))
+            }
+            Interpolation::Linear => p0.lerp(p1, s),
+            Interpolation::CubicSpline => {
+                let m0 = *output(prev * 3 + 2)? * delta;
+                let m1 = *output(next * 3)? * delta;
+                let (s2, s3) = (s * s, s * s * s);
+
+                p0 * (2. * s3 - 3. * s2 + 1.)
+                    + m0 * (s3 - 2. * s2 + s)
+                    + p1 * (-2. * s3 + 3. * s2)
+                    + m1 * (s3 - s2)
+            }
+        };
+
+        if self.property == Property::Rotation {
+            Some(sampled.normalize())
+        } else {
+            Some(sampled)
+        }
+    }
+
+    pub fn apply(&self, time: f32, transform: &mut Transform) {
+        if let Property::Weights(_) = self.property {
+            transform.weights = self.sample_weights(time);
+            return;
+        }
+        let Some(v) = self.sample(time) else {
+            return;
+        };
+
+        match self.property {
+            Property::Translation => transform.translation = v.truncate(),
+            Property::Rotation => transform.rotation = to_quaternion(v),
+            Property::Scale => transform.scale = v.truncate(),
+            Property::Weights(_) => {}
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Animation {
+    pub index: usize,
+    pub name: Option<String>,
+
+    pub channels: Vec<Channel>,
+    pub duration: f32,
+}
+
+impl Animation {
+    pub fn new(g_animation: &gltf::Animation<'_>, document: &Document) -> Self {
+        let index = g_animation.index();
+        let buffers = &document.buffers;
+
+        let channels = g_animation
+            .channels()
+            .filter_map(|g_channel| {
+                let reader = g_channel.reader(|b| Some(&buffers[b.index()]));
+                let inputs = reader.read_inputs()?.collect::<Vec<_>>();
+                let interpolation = g_channel.sampler().interpolation();
+
+                let (property, outputs) = match reader.read_outputs()? {
+                    ReadOutputs::Translations(t) => (
+                        Property::Translation,
+                        t.map(|v| Vector3::from(v).extend(0.)).collect(),
+                    ),
+                    ReadOutputs::Rotations(r) => (
+                        Property::Rotation,
+                        r.into_f32().map(Vector4::from).collect(),
+                    ),
+                    ReadOutputs::Scales(s) => (
+                        Property::Scale,
+                        s.map(|v| Vector3::from(v).extend(0.)).collect(),
+                    ),
+                    ReadOutputs::MorphTargetWeights(w) => {
+                        let weights = w.into_f32().collect::<Vec<_>>();
+                        let keys = inputs.len()
+                            * if interpolation == Interpolation::CubicSpline {
+                                3
+                            } else {
+                                1
+                            };
+                        if keys == 0 || weights.len() % keys != 0 {
+                            warn!("Ignoring malformed weights channel of animation {index}");
+                            return None;
+                        }
+                        let targets = weights.len() / keys;
+
+                        // Every output of the keyframe is padded to the multiple of four weights
+                        let outputs = weights
+                            .chunks(targets)
+                            .flat_map(|key| {
+                                key.chunks(4).map(|c| {
+                                    let mut v = [0.; 4];
+                                    v[..c.len()].copy_from_slice(c);
+                                    Vector4::from(v)
+                                })
+                            })
+                            .collect();
+
+                        (Property::Weights(targets), outputs)
+                    }
+                };
+
+                Some(Channel {
+                    node: g_channel.target().node().index(),
+                    property,
+                    interpolation,
+                    inputs,
+                    outputs,
+                })
+            })
+            .collect::<Vec<_>>();
+        let duration = channels
+            .iter()
+            .filter_map(|c| c.inputs.last())
+            .fold(0., |d: f32, t| d.max(*t));
+
+        Self {
+            index,
+            name: g_animation.name().map(|s| s.into()),
+            channels,
+            duration,
+        }
+    }
+
+    // Channels overwrite the animated properties of `pose`, which is indexed by nodes
+    pub fn sample(&self, time: f32, pose: &mut [Transform]) {
+        for c in &self.channels {
+            if let Some(transform) = pose.get_mut(c.node) {
+                c.apply(time, transform);
+            }
+        }
+    }
+}
+
+fn to_quaternion(v: Vector4<f32>) -> Quaternion<f32> {
+    Quaternion::new(v.w, v.x, v.y, v.z)
+}
+
+fn from_quaternion(q: Quaternion<f32>) -> Vector4<f32> {
+    q.v.extend(q.s)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn channel(
+        property: Property,
+        interpolation: Interpolation,
+        outputs: Vec<Vector4<f32>>,
+    ) -> Channel {
+        Channel {
+            node: 0,
+            property,
+            interpolation,
+            inputs: vec![0., 1., 3.],
+            outputs,
+        }
+    }
+
+    #[test]
+    fn channel_sampling() {
+        let outputs = vec![
+            Vector4::new(0., 0., 0., 0.),
+            Vector4::new(2., 4., 0., 0.),
+            Vector4::new(6., 0., 0., 0.),
+        ];
+
+        let linear = channel(
+            Property::Translation,
+            Interpolation::Linear,
+            outputs.clone(),
+        );
+        assert_eq!(linear.sample(-1.), Some(outputs[0]));
+        assert_eq!(linear.sample(0.5), Some(Vector4::new(1., 2., 0., 0.)));
+        assert_eq!(linear.sample(2.), Some(Vector4::new(4., 2., 0., 0.)));
+        assert_eq!(linear.sample(5.), Some(outputs[2]));
+
+        let step = channel(Property::Translation, Interpolation::Step, outputs.clone());
+        assert_eq!(step.sample(0.99), Some(outputs[0]));
+        assert_eq!(step.sample(1.), Some(outputs[1]));
+
+        // Zero tangents keep the keyframe values at the ends of the segment
+        let zero = Vector4::new(0., 0., 0., 0.);
+        let cubic = channel(
+            Property::Translation,
+            Interpolation::CubicSpline,
+            outputs.iter().flat_map(|v| [zero, *v, zero]).collect(),
+        );
+        assert_eq!(cubic.sample(1.), Some(outputs[1]));
+        assert_eq!(cubic.sample(2.), Some(Vector4::new(4., 2., 0., 0.)));
+
+        // Rotations take the shortest path and stay normalized
+        let half = std::f32::consts::FRAC_1_SQRT_2;
+        let (sin, cos) = std::f32::consts::FRAC_PI_8.sin_cos();
+        let rotation = channel(
+            Property::Rotation,
+            Interpolation::Linear,
+            vec![
+                Vector4::new(0., 0., 0., 1.),
+                Vector4::new(0., 0., -half, -half),
+                Vector4::new(0., 0., 0., 1.),
+            ],
+        );
+        let q = rotation.sample(0.5).unwrap();
+        assert!((q - Vector4::new(0., 0., sin, cos)).magnitude() < 1e-5);
+
+        let mut transform = Transform::default();
+        rotation.apply(0.5, &mut transform);
+        assert!((transform.rotation.s - cos).abs() < 1e-5);
+
+        // Five weights take two lanes of every keyframe
+        let weights = channel(
+            Property::Weights(5),
+            Interpolation::Linear,
+            vec![
+                Vector4::new(0., 0., 0., 0.),
+                Vector4::new(0., 0., 0., 0.),
+                Vector4::new(1., 0., 0., 0.),
+                Vector4::new(0.5, 0., 0., 0.),
+                Vector4::new(1., 0., 0., 0.),
+                Vector4::new(0.5, 0., 0., 0.),
+            ],
+        );
+        assert_eq!(weights.sample_weights(0.5), vec![0.5, 0., 0., 0., 0.25]);
+        weights.apply(2., &mut transform);
+        assert_eq!(transform.weights, vec![1., 0., 0., 0., 0.5]);
+    }
+}
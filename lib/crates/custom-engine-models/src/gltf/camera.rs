@@ -33,6 +33,16 @@ pub enum Camera {
     Perspective(PerspectiveCamera),
 }
 
+// Camera with the world transform of its node, the camera looks down the `-Z` axis of the node
+#[derive(Debug, Clone)]
+pub struct CameraNode {
+    pub node: usize,
+    pub name: Option<String>,
+
+    pub camera: Camera,
+    pub transform: Matrix4<f32>,
+}
+
 impl Camera {
     pub fn new<'a>(gltf_camera: &'a gltf::Camera<'a>) -> Self {
         Self::from(gltf_camera)
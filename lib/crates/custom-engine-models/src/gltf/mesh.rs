@@ -10,6 +10,8 @@ pub struct Mesh {
     pub name: Option<String>,
 
     pub primitives: Vec<Primitive>,
+    // Default weights of the morph targets of the primitives
+    pub weights: Vec<f32>,
     pub bounds: Aabb3<f32>,
 }
 
@@ -39,6 +41,7 @@ impl Mesh {
         Mesh {
             index: g_mesh.index(),
             primitives,
+            weights: g_mesh.weights().map(|w| w.to_vec()).unwrap_or_default(),
             name: g_mesh.name().map(|s| s.into()),
             bounds,
         }
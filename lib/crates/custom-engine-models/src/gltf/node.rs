@@ -14,6 +14,9 @@ pub struct Node {
     pub scale: Vector3<f32>,
     pub translation: Vector3<f32>,
     pub camera: Option<Rc<Camera>>,
+    pub skin: Option<usize>,
+    // Morph target weights, which override the mesh ones
+    pub weights: Option<Vec<f32>>,
     pub name: Option<String>,
 
     pub final_transform: Matrix4<f32>,
@@ -55,6 +58,8 @@ impl Node {
             scale: scale.into(),
             translation: trans.into(),
             camera: g_node.camera().as_ref().map(|c| Rc::new(Camera::new(c))),
+            skin: g_node.skin().map(|s| s.index()),
+            weights: g_node.weights().map(|w| w.to_vec()),
             name: g_node.name().map(|s| s.into()),
 
             final_transform: Matrix4::identity(),
@@ -66,8 +71,8 @@ impl Node {
     pub fn update_transform(&mut self, root: &mut Root, parent_transform: &Matrix4<f32>) {
         self.final_transform = *parent_transform
             * Matrix4::from_translation(self.translation)
-            * Matrix4::from_nonuniform_scale(self.scale.x, self.scale.y, self.scale.z)
-            * Matrix4::from(self.rotation);
+            * Matrix4::from(self.rotation)
+            * Matrix4::from_nonuniform_scale(self.scale.x, self.scale.y, self.scale.z);
 
         self.children.iter().for_each(|id| {
             root.unsafe_get_node_mut(*id)
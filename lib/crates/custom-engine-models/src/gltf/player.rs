@@ -0,0 +1,116 @@
+use std::time::Duration;
+
+use cgmath::{InnerSpace, Quaternion, Vector3};
+
+use crate::gltf::{Skeleton, Transform};
+
+#[derive(Debug, Clone)]
+pub struct Clip {
+    pub animation: usize,
+    pub time: f32,
+    pub speed: f32,
+    pub weight: f32,
+    pub looping: bool,
+}
+
+#[derive(Debug, Default)]
+pub struct AnimationPlayer {
+    clips: Vec<Clip>,
+}
+
+impl AnimationPlayer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // Clip starts from the beginning, it's blended with the playing ones by `weight`
+    pub fn play(&mut self, animation: usize, weight: f32, looping: bool) {
+        self.stop(animation);
+        self.clips.push(Clip {
+            animation,
+            time: 0.,
+            speed: 1.,
+            weight,
+            looping,
+        });
+    }
+
+    pub fn stop(&mut self, animation: usize) {
+        self.clips.retain(|c| c.animation != animation);
+    }
+
+    pub fn clip_mut(&mut self, animation: usize) -> Option<&mut Clip> {
+        self.clips.iter_mut().find(|c| c.animation == animation)
+    }
+
+    pub fn clips(&self) -> &[Clip] {
+        &self.clips
+    }
+
+    pub fn update(&mut self, skeleton: &Skeleton, dt: Duration) {
+        for c in &mut self.clips {
+            let Some(animation) = skeleton.animations.get(c.animation) else {
+                continue;
+            };
+
+            c.time += dt.as_secs_f32() * c.speed;
+            c.time = if c.looping && animation.duration > 0. {
+                c.time.rem_euclid(animation.duration)
+            } else {
+                c.time.clamp(0., animation.duration)
+            };
+        }
+    }
+
+    // Local transforms of the nodes, which are averaged by the weights of the clips
+    pub fn pose(&self, skeleton: &Skeleton) -> Vec<Transform> {
+        let clips = self
+            .clips
+            .iter()
+            .filter_map(|c| Some((c, skeleton.animations.get(c.animation)?)))
+            .filter(|(c, _)| c.weight > 0.)
+            .collect::<Vec<_>>();
+        let total_weight = clips.iter().map(|(c, _)| c.weight).sum::<f32>();
+        if clips.is_empty() {
+            return skeleton.rest_pose.clone();
+        }
+
+        let zero = Transform {
+            translation: Vector3::new(0., 0., 0.),
+            rotation: Quaternion::new(0., 0., 0., 0.),
+            scale: Vector3::new(0., 0., 0.),
+            weights: vec![],
+        };
+        let mut pose = vec![zero; skeleton.rest_pose.len()];
+        for (c, animation) in clips {
+            let mut clip_pose = skeleton.rest_pose.clone();
+            animation.sample(c.time, &mut clip_pose);
+
+            let weight = c.weight / total_weight;
+            for ((p, clip), rest) in pose.iter_mut().zip(clip_pose).zip(&skeleton.rest_pose) {
+                // Quaternions are summed in the hemisphere of the rest rotation
+                let rotation = if clip.rotation.dot(rest.rotation) < 0. {
+                    -clip.rotation
+                } else {
+                    clip.rotation
+                };
+
+                p.translation += clip.translation * weight;
+                p.rotation += rotation * weight;
+                p.scale += clip.scale * weight;
+
+                if p.weights.len() < clip.weights.len() {
+                    p.weights.resize(clip.weights.len(), 0.);
+                }
+                p.weights
+                    .iter_mut()
+                    .zip(&clip.weights)
+                    .for_each(|(p, w)| *p += w * weight);
+            }
+        }
+        pose.iter_mut()
+            .for_each(|p| p.rotation = p.rotation.normalize());
+
+        pose
+    }
+}
@@ -34,6 +34,13 @@ impl Default for PrimitiveVertex {
     }
 }
 
+#[derive(Debug, Clone, Default)]
+pub struct MorphTarget {
+    pub positions: Vec<Vector3<f32>>,
+    pub normals: Vec<Vector3<f32>>,
+    pub tangents: Vec<Vector3<f32>>,
+}
+
 #[derive(Debug, Clone)]
 pub struct Primitive {
     pub index: usize,
@@ -42,6 +49,8 @@ pub struct Primitive {
     pub material: Rc<Material>,
     pub vertices: Vec<PrimitiveVertex>,
     pub indices: Option<Vec<u32>>,
+    // Deltas have the length of `vertices`, missing attributes are zero
+    pub morph_targets: Vec<MorphTarget>,
 
     pub mode: gltf::mesh::Mode,
 }
@@ -135,6 +144,22 @@ impl Primitive {
             warn!("Ignoring further weight attributes, only supporting WEIGHTS_0. (mesh: {mesh_index}, primitive: {index})");
         }
 
+        let deltas = |d: Option<gltf::accessor::Iter<'_, [f32; 3]>>| {
+            let mut deltas = d
+                .map(|d| d.map(Vector3::from).collect::<Vec<_>>())
+                .unwrap_or_default();
+            deltas.resize(vertices.len(), Vector3::zero());
+            deltas
+        };
+        let morph_targets = reader
+            .read_morph_targets()
+            .map(|(positions, normals, tangents)| MorphTarget {
+                positions: deltas(positions),
+                normals: deltas(normals),
+                tangents: deltas(tangents),
+            })
+            .collect::<Vec<_>>();
+
         let indices = reader
             .read_indices()
             .map(|read_indices| read_indices.into_u32().collect::<Vec<_>>());
@@ -164,6 +189,7 @@ impl Primitive {
             material,
             indices,
             vertices,
+            morph_targets,
             mode,
         })
     }
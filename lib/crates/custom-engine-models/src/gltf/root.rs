@@ -1,8 +1,8 @@
 use std::{path::Path, rc::Rc};
 
 use crate::gltf::{
-    camera::Camera, document::Document, material::Material, mesh::Mesh, node::Node,
-    texture::Texture,
+    animation::Animation, camera::Camera, document::Document, material::Material, mesh::Mesh,
+    node::Node, skin::Skin, texture::Texture,
 };
 
 #[derive(Default, Debug)]
@@ -12,6 +12,8 @@ pub struct Root {
     pub textures: Vec<Rc<Texture>>,
     pub materials: Vec<Rc<Material>>,
     pub camera_nodes: Vec<Rc<Camera>>,
+    pub skins: Vec<Skin>,
+    pub animations: Vec<Animation>,
 }
 
 impl Root {
@@ -38,6 +40,17 @@ impl Root {
                 }
             })
             .collect();
+        root.skins = document
+            .inner
+            .skins()
+            .map(|s| Skin::new(&s, document))
+            .collect();
+        root.animations = document
+            .inner
+            .animations()
+            .map(|a| Animation::new(&a, document))
+            .collect();
+
         root
     }
 
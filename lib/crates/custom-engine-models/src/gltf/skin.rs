@@ -0,0 +1,113 @@
+use cgmath::{Matrix4, SquareMatrix};
+
+use crate::gltf::{Animation, Document, Root, Transform};
+
+#[derive(Debug, Clone)]
+pub struct Skin {
+    pub index: usize,
+    pub name: Option<String>,
+
+    pub joints: Vec<usize>,
+    pub inverse_bind_matrices: Vec<Matrix4<f32>>,
+    pub skeleton: Option<usize>,
+}
+
+impl Skin {
+    pub fn new(g_skin: &gltf::Skin<'_>, document: &Document) -> Self {
+        let buffers = &document.buffers;
+        let reader = g_skin.reader(|b| Some(&buffers[b.index()]));
+
+        let joints = g_skin.joints().map(|n| n.index()).collect::<Vec<_>>();
+        let inverse_bind_matrices = reader
+            .read_inverse_bind_matrices()
+            .map(|m| m.map(Matrix4::from).collect())
+            .unwrap_or_else(|| vec![Matrix4::identity(); joints.len()]);
+
+        Self {
+            index: g_skin.index(),
+            name: g_skin.name().map(|s| s.into()),
+            joints,
+            inverse_bind_matrices,
+            skeleton: g_skin.skeleton().map(|n| n.index()),
+        }
+    }
+
+    // Transform of the skinned mesh node is ignored, joint matrices move vertices to the world
+    pub fn joint_matrices(&self, global_transforms: &[Matrix4<f32>]) -> Vec<Matrix4<f32>> {
+        self.joints
+            .iter()
+            .zip(&self.inverse_bind_matrices)
+            .map(|(joint, ibm)| {
+                global_transforms
+                    .get(*joint)
+                    .map_or(Matrix4::identity(), |global| global * ibm)
+            })
+            .collect()
+    }
+}
+
+// Node hierarchy of the file with its skins and animations, which isn't tied to `Root`
+#[derive(Debug, Clone, Default)]
+pub struct Skeleton {
+    pub rest_pose: Vec<Transform>,
+    pub children: Vec<Vec<usize>>,
+    pub roots: Vec<usize>,
+
+    pub skins: Vec<Skin>,
+    pub animations: Vec<Animation>,
+}
+
+impl Skeleton {
+    pub fn new(root: &Root) -> Self {
+        let rest_pose = root
+            .nodes
+            .iter()
+            .map(|n| Transform {
+                translation: n.translation,
+                rotation: n.rotation,
+                scale: n.scale,
+                weights: n
+                    .weights
+                    .clone()
+                    .or_else(|| n.mesh.as_ref().map(|m| m.weights.clone()))
+                    .unwrap_or_default(),
+            })
+            .collect();
+        let children = root
+            .nodes
+            .iter()
+            .map(|n| n.children.clone())
+            .collect::<Vec<_>>();
+        let roots = (0..root.nodes.len())
+            .filter(|i| !children.iter().any(|c| c.contains(i)))
+            .collect();
+
+        Self {
+            rest_pose,
+            children,
+            roots,
+            skins: root.skins.clone(),
+            animations: root.animations.clone(),
+        }
+    }
+
+    // `pose` is indexed by nodes, missing nodes keep the rest pose
+    pub fn global_transforms(&self, pose: &[Transform]) -> Vec<Matrix4<f32>> {
+        let mut globals = vec![Matrix4::identity(); self.rest_pose.len()];
+
+        let mut stack = self
+            .roots
+            .iter()
+            .map(|r| (*r, Matrix4::identity()))
+            .collect::<Vec<_>>();
+        while let Some((node, parent)) = stack.pop() {
+            let local = pose.get(node).unwrap_or(&self.rest_pose[node]);
+            let global = parent * local.matrix();
+
+            globals[node] = global;
+            stack.extend(self.children[node].iter().map(|c| (*c, global)));
+        }
+
+        globals
+    }
+}
@@ -17,8 +17,13 @@ pub struct Texture {
     pub tex_coord: u32, // the tex coord set to use
     #[derivative(Debug = "ignore")]
     pub dyn_image: Vec<u8>,
+    // Image of `source`, when `dyn_image` is a compressed extension image
+    #[derivative(Debug = "ignore")]
+    pub fallback_image: Option<Vec<u8>>,
 }
 
+const COMPRESSED_EXTENSIONS: [&str; 2] = ["KHR_texture_basisu", "MSFT_texture_dds"];
+
 impl Texture {
     pub fn new(
         g_texture: &gltf::Texture<'_>,
@@ -26,45 +31,65 @@ impl Texture {
         document: &Document,
         base_path: &Path,
     ) -> Result<Texture> {
-        use gltf::image::Source;
-
-        let buffers = &document.buffers;
-
-        let g_img = g_texture.source();
-        let dyn_image = match g_img.source() {
-            Source::View { view, .. } => {
-                let parent_buffer_data = &buffers[view.buffer().index()].0;
-                let begin = view.offset();
-                let end = begin + view.length();
-                let data = &parent_buffer_data[begin..end];
-
-                data.to_vec()
-            }
-            Source::Uri { uri, .. } => {
-                if uri.starts_with("data:") {
-                    let encoded = uri.split(',').nth(1).unwrap();
-                    let data = BASE64_STANDARD.decode(&encoded).unwrap();
-
-                    data
-                } else {
-                    get_data(
-                        base_path
-                            .parent()
-                            .unwrap_or_else(|| Path::new("./"))
-                            .join(uri)
-                            .to_str()
-                            .ok_or(anyhow!("Base path is wrong"))?,
-                    )
-                    .ok_or(anyhow!("Source URI `{uri}` data is not found"))?
-                }
-            }
+        // Images of `KHR_texture_basisu` and `MSFT_texture_dds` are preferred, `source` is the fallback
+        let compressed = COMPRESSED_EXTENSIONS
+            .into_iter()
+            .filter_map(|ext| g_texture.extension_value(ext)?.get("source")?.as_u64())
+            .find_map(|index| document.inner.images().nth(index as usize));
+        let (g_img, g_fallback) = match (compressed, g_texture.source()) {
+            (Some(g_img), g_fallback) => (g_img, g_fallback),
+            (None, Some(g_img)) => (g_img, None),
+            (None, None) => return Err(anyhow!("Texture {} has no image", g_texture.index())),
         };
 
+        let dyn_image = image_data(&g_img, document, base_path)?;
+        let fallback_image = g_fallback
+            .map(|g_img| image_data(&g_img, document, base_path))
+            .transpose()?;
+
         Ok(Texture {
             index: g_texture.index(),
             name: g_texture.name().map(|s| s.into()),
             tex_coord,
             dyn_image,
+            fallback_image,
         })
     }
 }
+
+fn image_data(g_img: &gltf::Image<'_>, document: &Document, base_path: &Path) -> Result<Vec<u8>> {
+    use gltf::image::Source;
+
+    let buffers = &document.buffers;
+
+    let data = match g_img.source() {
+        Source::View { view, .. } => {
+            let parent_buffer_data = &buffers[view.buffer().index()].0;
+            let begin = view.offset();
+            let end = begin + view.length();
+            let data = &parent_buffer_data[begin..end];
+
+            data.to_vec()
+        }
+        Source::Uri { uri, .. } => {
+            if uri.starts_with("data:") {
+                let encoded = uri.split(',').nth(1).unwrap();
+                let data = BASE64_STANDARD.decode(&encoded).unwrap();
+
+                data
+            } else {
+                get_data(
+                    base_path
+                        .parent()
+                        .unwrap_or_else(|| Path::new("./"))
+                        .join(uri)
+                        .to_str()
+                        .ok_or(anyhow!("Base path is wrong"))?,
+                )
+                .ok_or(anyhow!("Source URI `{uri}` data is not found"))?
+            }
+        }
+    };
+
+    Ok(data)
+}
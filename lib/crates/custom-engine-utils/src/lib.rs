@@ -1,5 +1,3 @@
 mod image;
-mod reference;
 
 pub use image::*;
-pub use reference::*;
@@ -6,7 +6,10 @@ use winit::{dpi::PhysicalPosition, event::ElementState};
 
 use custom_engine_core::traits::EventHandler;
 
-use crate::workers::model::SimpleModelRender;
+use crate::workers::{
+    custom::SimpleCustomRender, model::SimpleModelRender, render_texture::SimpleRenderTexture,
+    render_to_texture::SimpleRenderToTexture, simple::SimpleRender,
+};
 
 #[derive(Debug)]
 pub enum ClickType {
@@ -57,4 +60,8 @@ impl AppState {
     }
 }
 
+impl EventHandler<SimpleRender> for AppState {}
+impl EventHandler<SimpleCustomRender> for AppState {}
 impl EventHandler<SimpleModelRender> for AppState {}
+impl EventHandler<SimpleRenderTexture> for AppState {}
+impl EventHandler<SimpleRenderToTexture> for AppState {}
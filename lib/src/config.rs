@@ -4,6 +4,8 @@ use anyhow::{anyhow, Result};
 use log::trace;
 use serde::{de::DeserializeOwned, Deserialize};
 
+use custom_engine_core::runtime::{LimitsKind, RuntimeOptions};
+
 #[derive(Debug, Deserialize, Default, Clone, Copy)]
 pub enum WorkerKind {
     #[default]
@@ -23,6 +25,172 @@ pub struct EngineConfig {
     pub width: u32,
     #[serde(default = "default_height")]
     pub height: u32,
+    #[serde(default)]
+    pub runtime: RuntimeConfig,
+}
+
+#[derive(Debug, Deserialize, Default, Clone, Copy)]
+pub enum PowerPreferenceKind {
+    #[default]
+    None,
+    LowPower,
+    HighPerformance,
+}
+
+impl From<PowerPreferenceKind> for wgpu::PowerPreference {
+    fn from(value: PowerPreferenceKind) -> Self {
+        match value {
+            PowerPreferenceKind::None => wgpu::PowerPreference::None,
+            PowerPreferenceKind::LowPower => wgpu::PowerPreference::LowPower,
+            PowerPreferenceKind::HighPerformance => wgpu::PowerPreference::HighPerformance,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Clone, Copy)]
+pub enum AdapterKind {
+    Other,
+    IntegratedGpu,
+    DiscreteGpu,
+    VirtualGpu,
+    Cpu,
+}
+
+impl From<AdapterKind> for wgpu::DeviceType {
+    fn from(value: AdapterKind) -> Self {
+        match value {
+            AdapterKind::Other => wgpu::DeviceType::Other,
+            AdapterKind::IntegratedGpu => wgpu::DeviceType::IntegratedGpu,
+            AdapterKind::DiscreteGpu => wgpu::DeviceType::DiscreteGpu,
+            AdapterKind::VirtualGpu => wgpu::DeviceType::VirtualGpu,
+            AdapterKind::Cpu => wgpu::DeviceType::Cpu,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Default, Clone, Copy)]
+pub enum LimitsPreset {
+    #[default]
+    Default,
+    Downlevel,
+    WebGl2,
+    Adapter,
+}
+
+impl From<LimitsPreset> for LimitsKind {
+    fn from(value: LimitsPreset) -> Self {
+        match value {
+            LimitsPreset::Default => LimitsKind::Default,
+            LimitsPreset::Downlevel => LimitsKind::Downlevel,
+            LimitsPreset::WebGl2 => LimitsKind::WebGl2,
+            LimitsPreset::Adapter => LimitsKind::Adapter,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Clone, Copy)]
+pub enum PresentModeKind {
+    AutoVsync,
+    AutoNoVsync,
+    Fifo,
+    FifoRelaxed,
+    Immediate,
+    Mailbox,
+}
+
+impl From<PresentModeKind> for wgpu::PresentMode {
+    fn from(value: PresentModeKind) -> Self {
+        match value {
+            PresentModeKind::AutoVsync => wgpu::PresentMode::AutoVsync,
+            PresentModeKind::AutoNoVsync => wgpu::PresentMode::AutoNoVsync,
+            PresentModeKind::Fifo => wgpu::PresentMode::Fifo,
+            PresentModeKind::FifoRelaxed => wgpu::PresentMode::FifoRelaxed,
+            PresentModeKind::Immediate => wgpu::PresentMode::Immediate,
+            PresentModeKind::Mailbox => wgpu::PresentMode::Mailbox,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Default)]
+pub struct RuntimeConfig {
+    #[serde(default)]
+    pub backends: Vec<String>,
+    #[serde(default)]
+    pub power_preference: PowerPreferenceKind,
+    pub adapter_name: Option<String>,
+    pub adapter_type: Option<AdapterKind>,
+    #[serde(default)]
+    pub force_fallback_adapter: bool,
+    #[serde(default)]
+    pub required_features: Vec<String>,
+    #[serde(default)]
+    pub optional_features: Vec<String>,
+    #[serde(default)]
+    pub limits: LimitsPreset,
+    #[serde(default)]
+    pub gpu_profiling: bool,
+    pub sample_count: Option<u32>,
+    pub texture_compression: Option<bool>,
+    pub vsync: Option<bool>,
+    pub present_mode: Option<PresentModeKind>,
+    pub frame_latency: Option<u32>,
+}
+
+impl RuntimeConfig {
+    pub fn options(&self) -> Result<RuntimeOptions> {
+        let mut options = RuntimeOptions::new()
+            .power_preference(self.power_preference.into())
+            .force_fallback_adapter(self.force_fallback_adapter)
+            .required_features(parse_flags(
+                &self.required_features,
+                wgpu::Features::from_name,
+            )?)
+            .optional_features(parse_flags(
+                &self.optional_features,
+                wgpu::Features::from_name,
+            )?)
+            .limits(self.limits.into())
+            .gpu_profiling(self.gpu_profiling);
+
+        if !self.backends.is_empty() {
+            options = options.backends(parse_flags(&self.backends, wgpu::Backends::from_name)?);
+        }
+        if let Some(adapter_name) = self.adapter_name.as_ref() {
+            options = options.adapter_name(adapter_name);
+        }
+        if let Some(adapter_type) = self.adapter_type {
+            options = options.adapter_type(adapter_type.into());
+        }
+        if let Some(sample_count) = self.sample_count {
+            options = options.sample_count(sample_count);
+        }
+        if let Some(texture_compression) = self.texture_compression {
+            options = options.texture_compression(texture_compression);
+        }
+        if let Some(vsync) = self.vsync {
+            options = options.vsync(vsync);
+        }
+        if let Some(present_mode) = self.present_mode {
+            options = options.present_mode(present_mode.into());
+        }
+        if let Some(frame_latency) = self.frame_latency {
+            options = options.frame_latency(frame_latency);
+        }
+
+        Ok(options)
+    }
+}
+
+fn parse_flags<F, P>(names: &[String], from_name: P) -> Result<F>
+where
+    F: std::ops::BitOr<Output = F> + Default,
+    P: Fn(&str) -> Option<F>,
+{
+    names.iter().try_fold(F::default(), |flags, name| {
+        from_name(&name.to_uppercase())
+            .map(|flag| flags | flag)
+            .ok_or(anyhow!("Unknown flag `{name}` in runtime config"))
+    })
 }
 
 pub trait LoadConfig {
@@ -1,4 +1,5 @@
 use anyhow::Result;
+use log::info;
 #[cfg(not(target_arch = "wasm32"))]
 use log::LevelFilter;
 #[cfg(not(target_arch = "wasm32"))]
@@ -11,12 +12,18 @@ use winit::event_loop::EventLoop;
 #[cfg(target_arch = "wasm32")]
 use winit::event_loop::EventLoopProxy;
 
-use custom_engine_core::runtime::Runtime;
+use custom_engine_core::{
+    runtime::{Runtime, RuntimeOptions},
+    traits::{EventHandler, RenderWorker},
+};
 
 use crate::{
     application::{foreign::UserEvent, AppState},
-    config::{EngineConfig, LoadConfig},
-    workers::model::SimpleModelRender,
+    config::{EngineConfig, LoadConfig, WorkerKind},
+    workers::{
+        custom::SimpleCustomRender, model::SimpleModelRender, render_texture::SimpleRenderTexture,
+        render_to_texture::SimpleRenderToTexture, simple::SimpleRender,
+    },
 };
 
 #[cfg(target_arch = "wasm32")]
@@ -97,10 +104,42 @@ impl EngineRunner {
             }
         }
 
-        event_loop.run_app(&mut Runtime::<SimpleModelRender, AppState>::new((
-            1600, 1200,
-        )))?;
+        let EngineConfig {
+            worker,
+            width,
+            height,
+            runtime,
+            ..
+        } = self.config;
+        let size = (width, height);
+        let options = runtime.options()?;
+
+        info!("Run `{worker:?}` worker with size: {size:?}");
 
-        Ok(())
+        match worker {
+            WorkerKind::Simple => run_worker::<SimpleRender>(event_loop, size, options),
+            WorkerKind::Custom => run_worker::<SimpleCustomRender>(event_loop, size, options),
+            WorkerKind::Model => run_worker::<SimpleModelRender>(event_loop, size, options),
+            WorkerKind::RenderTexture => {
+                run_worker::<SimpleRenderTexture>(event_loop, size, options)
+            }
+            WorkerKind::RenderToTexture => {
+                run_worker::<SimpleRenderToTexture>(event_loop, size, options)
+            }
+        }
     }
 }
+
+fn run_worker<R>(
+    event_loop: EventLoop<UserEvent>,
+    size: (u32, u32),
+    options: RuntimeOptions,
+) -> Result<()>
+where
+    R: RenderWorker,
+    AppState: EventHandler<R>,
+{
+    event_loop.run_app(&mut Runtime::<R, AppState>::with_options(size, options))?;
+
+    Ok(())
+}
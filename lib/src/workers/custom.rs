@@ -1,10 +1,13 @@
 use anyhow::Result;
 
 use custom_engine_core::{
+    buffer::Buffer,
+    context::Handle,
     errors::CoreError,
+    pipeline::Pipeline,
     render_pass::color_attachment::ColorAttachmentBuilder,
     render_pass::RenderStage,
-    storage::{StorageDescription, StorageKind},
+    storage::{StorageDescription, StorageKind, Storages},
     traits::{Builder, RenderWorker, VertexLayout},
     worker::Worker,
 };
@@ -46,9 +49,9 @@ const VERTICES: &[Vertex] = &[
 
 #[derive(Debug, Default)]
 pub struct SimpleCustomRender {
-    vb_id: usize,
-    s_id: usize,
-    p_id: usize,
+    vb_id: Handle<Buffer>,
+    s_id: Handle<Storages>,
+    p_id: Handle<Pipeline>,
 
     counter: f32,
 }
@@ -191,10 +194,10 @@ impl RenderWorker for SimpleCustomRender {
         w.render(r_p)?;
         w.present()?;
 
-        let out = w.read_storage_buffer::<Vertex>(s.id, "Storage")?;
+        let out = w.read_storage_buffer::<Vertex>(*s_id, "Storage")?;
 
         w.update_storage(
-            s.id,
+            *s_id,
             "Storage",
             &out.into_iter()
                 .map(|mut v| {
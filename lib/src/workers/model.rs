@@ -1,22 +1,29 @@
 use anyhow::Result;
+use cgmath::{InnerSpace, Vector3, VectorSpace};
+use image::{codecs::hdr::HdrEncoder, Rgb};
 use instant::Duration;
 use winit::event::WindowEvent;
 
 use custom_engine_components::{
-    components::{camera::Camera, light::Light},
+    components::{camera::Camera, ibl::Ibl, light::Light},
     traits::Component,
 };
 use custom_engine_core::{
+    buffer::Buffer,
+    context::Handle,
     errors::CoreError,
     instance::Instances,
     model::{Model, TextureParams},
+    pipeline::{layout::PipelineLayout, Pipeline},
+    render_graph::{GraphPass, RenderGraph, TransientTexture},
     render_pass::RenderStage,
     render_pass::{
         color_attachment::ColorAttachmentBuilder, depth_stencil::DepthStencilAttachmentBuilder,
     },
+    shader::Shader,
     texture::TextureKind,
     traits::{Builder, RenderWorker},
-    uniform::UniformDescription,
+    uniform::{UniformDescription, Uniforms},
     worker::Worker,
 };
 use custom_engine_models::{gltf::GltfFile, obj::ObjFile};
@@ -25,25 +32,28 @@ use crate::files::{ShaderFiles, ShaderKind};
 
 const NUM_INSTANCES_PER_ROW: u32 = 10;
 const SPACE_BETWEEN: f32 = 3.0;
+// Groups of the model pipeline, IBL needs one more group than the default limits
+const IBL_GROUP: u32 = 4;
+const ENVIRONMENT_SIZE: u32 = 64;
 
 #[derive(Debug, Default)]
 pub struct SimpleModelRender {
-    sh_id: usize,
-    pl_id: usize,
-    p_id: usize,
-    m_id: usize,
-    vb_id: usize,
+    sh_id: Handle<Shader>,
+    pl_id: Handle<PipelineLayout>,
+    p_id: Handle<Pipeline>,
+    m_id: Handle<Model>,
+    vb_id: Handle<Buffer>,
 
-    c_id: usize,
+    c_id: Handle<Uniforms>,
 
-    hdr_t_id: usize,
-    hdr_p_id: usize,
-    hdr_sh_id: usize,
-    hdr_pl_id: usize,
+    hdr_p_id: Handle<Pipeline>,
+    hdr_sh_id: Handle<Shader>,
+    hdr_pl_id: Handle<PipelineLayout>,
 
+    graph: RenderGraph,
     camera: Option<Camera>,
+    ibl: Option<Ibl>,
     light: Light,
-    size: (u32, u32),
 }
 
 impl RenderWorker for SimpleModelRender {
@@ -60,6 +70,13 @@ impl RenderWorker for SimpleModelRender {
     where
         Self: Sized,
     {
+        if w.limits().max_bind_groups <= IBL_GROUP {
+            return Err(CoreError::UnsupportedLimits(format!(
+                "the model pipeline needs {} bind groups, set the `Adapter` limits",
+                IBL_GROUP + 1
+            )));
+        }
+
         let _obj_file = ObjFile::new("./assets/models/cube/cube.obj")?;
         let gltf_file = GltfFile::new("./assets/models/avocado/Avocado.glb")?;
 
@@ -93,6 +110,19 @@ impl RenderWorker for SimpleModelRender {
 
         let camera = Camera::init(w, 2)?;
 
+        let faces = environment_faces()?;
+        let environment = w
+            .create_render_texture()
+            .label("Environment cube map")
+            .layer_bytes(faces.iter().map(Vec::as_slice).collect())
+            .view_dimension(wgpu::TextureViewDimension::Cube)
+            .format(wgpu::TextureFormat::Rgba16Float)
+            .usage(wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST)
+            .mipmaps(false)
+            .build()?;
+        w.load_texture(&environment);
+        let ibl = Ibl::init(w, &environment, IBL_GROUP)?;
+
         let light = Light::default();
         let (c_id, c_b_builder) = w.create_uniform_id();
         let c_b = c_b_builder
@@ -132,7 +162,13 @@ impl RenderWorker for SimpleModelRender {
         let (pl_id, pipeline_layout_builder) = w.create_pipeline_layout_id();
         let pipeline_layout = pipeline_layout_builder
             .label("Some pipeline layout")
-            .entries(vec![bgl, c_b.get_layout(), camera.bind_group_layout()])
+            .entries(vec![
+                bgl,
+                c_b.get_layout(),
+                camera.bind_group_layout(),
+                m.node_bind_group_layout(),
+                ibl.bind_group_layout(),
+            ])
             .build()?;
         let (p_id, pipeline_builder) = w.create_pipeline_id();
         let pipeline = pipeline_builder
@@ -155,11 +191,7 @@ impl RenderWorker for SimpleModelRender {
                 stencil: wgpu::StencilState::default(),     // 2.
                 bias: wgpu::DepthBiasState::default(),
             })
-            .multisample(&wgpu::MultisampleState {
-                count: 1,
-                mask: !0,
-                alpha_to_coverage_enabled: false,
-            })
+            .multisample(&w.multisample_state())
             .build()?;
 
         let (hdr_sh_id, hdr_sh_builder) = w.create_shader_id();
@@ -179,43 +211,21 @@ impl RenderWorker for SimpleModelRender {
             .source(sh_data)
             .build()?;
 
-        let size = w.size();
-        let (hdr_t_id, hdr_t_builder) = w.create_render_texture_id();
-        let hdr_t = hdr_t_builder
-            .label("HDR texture")
-            .format(format)
-            .usage(wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::RENDER_ATTACHMENT)
-            .texture_size(size)
-            .view_layout_entry(wgpu::BindGroupLayoutEntry {
-                binding: 0,
-                visibility: wgpu::ShaderStages::FRAGMENT,
-                ty: wgpu::BindingType::Texture {
-                    sample_type: wgpu::TextureSampleType::Float { filterable: true },
-                    view_dimension: wgpu::TextureViewDimension::D2,
-                    multisampled: false,
-                },
-                count: None,
-            })
-            .sampler_layout_entry(wgpu::BindGroupLayoutEntry {
-                binding: 1,
-                visibility: wgpu::ShaderStages::FRAGMENT,
-                ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
-                count: None,
-            })
-            .sampler_desc(wgpu::SamplerDescriptor {
-                address_mode_u: wgpu::AddressMode::ClampToEdge,
-                address_mode_v: wgpu::AddressMode::ClampToEdge,
-                address_mode_w: wgpu::AddressMode::ClampToEdge,
-                mag_filter: wgpu::FilterMode::Nearest,
-                min_filter: wgpu::FilterMode::Nearest,
-                mipmap_filter: wgpu::FilterMode::Nearest,
-                ..Default::default()
-            })
-            .bind_group_binding(0)
-            .is_sampler(true)
-            .build()?;
+        let mut graph = RenderGraph::new()
+            .transient(
+                "HDR texture",
+                TransientTexture::color(format)
+                    .filter(wgpu::FilterMode::Nearest)
+                    .bind_group_binding(0)
+                    .multisampled(true),
+            )
+            .transient(
+                "Depth texture",
+                TransientTexture::depth().multisampled(true),
+            );
+        graph.prepare(w)?;
 
-        let hdr_bgl = hdr_t.bind_group_layout()?;
+        let hdr_bgl = graph.texture("HDR texture")?.bind_group_layout()?;
 
         let (hdr_pl_id, hdr_pl_builder) = w.create_pipeline_layout_id();
         let hdr_pl = hdr_pl_builder
@@ -250,7 +260,6 @@ impl RenderWorker for SimpleModelRender {
         w.add_buffer(v_b);
         w.add_uniform(c_b);
 
-        w.add_render_texture(hdr_t);
         w.add_shader(hdr_sh);
         w.add_pipeline_layout(hdr_pl);
         w.add_pipeline(hdr_p);
@@ -263,14 +272,14 @@ impl RenderWorker for SimpleModelRender {
             m_id,
             vb_id,
 
-            hdr_t_id,
             hdr_p_id,
             hdr_sh_id,
             hdr_pl_id,
 
+            graph,
             light,
             camera: Some(camera),
-            size,
+            ibl: Some(ibl),
         };
 
         Ok(())
@@ -283,69 +292,32 @@ impl RenderWorker for SimpleModelRender {
             vb_id,
             c_id,
             hdr_p_id,
-            hdr_t_id,
-            size,
+            graph,
             camera,
+            ibl,
             ..
         } = self;
 
+        graph.prepare(w)?;
+
         let camera = camera.as_ref().unwrap();
+        let ibl = ibl.as_ref().unwrap();
         let pipeline = w.get_pipeline_ref(*p_id)?;
         let m = w.get_model_ref(*m_id)?;
         let vb = w.get_buffer_ref(*vb_id)?;
         let c = w.get_uniform_ref(*c_id)?;
 
         let hdr_pipeline = w.get_pipeline_ref(*hdr_p_id)?;
-        let hdr_texture = w.get_render_texture_ref(*hdr_t_id)?;
-
+        let hdr_texture = graph.texture("HDR texture")?;
         let hdr_bind_group = hdr_texture.bind_group()?;
-        let hdr_t_view = hdr_texture.view();
-
-        let d_t = w
-            .create_depth_texture()
-            .label("Depth Texture")
-            .texture_size(*size)
-            .build()?;
-        let d_t_view = d_t.view;
+        let hdr_attachment = graph.color_attachment("HDR texture")?;
+        let d_t_view = graph.view("Depth texture")?;
 
         let view = w.view_surface()?;
-        let r_p = w
-            .render_pass()
-            .label("Render Pass")
-            .render_stage(
+        let hdr_pass = GraphPass::new(
+            "HDR pass",
+            w.render_pass().label("HDR pass").render_stage(
                 0,
-                RenderStage::new(&pipeline)
-                    .depth_stencil_builder(
-                        DepthStencilAttachmentBuilder::new()
-                            .label("Some depth attach")
-                            .view(&d_t_view)
-                            .depth_ops(wgpu::Operations {
-                                load: wgpu::LoadOp::Clear(1.0),
-                                store: wgpu::StoreOp::Store,
-                            }),
-                    )
-                    .color_attachments_builder(
-                        ColorAttachmentBuilder::new()
-                            .label("Some color attach")
-                            .view(hdr_t_view)
-                            .ops(wgpu::Operations {
-                                load: wgpu::LoadOp::Clear(wgpu::Color {
-                                    r: 0.1,
-                                    g: 0.2,
-                                    b: 0.3,
-                                    a: 1.0,
-                                }),
-                                store: wgpu::StoreOp::Store,
-                            }),
-                    )
-                    .entities(0..1)
-                    .instances(0..30)
-                    .vertex_buffer(&vb)
-                    .bind_groups(vec![c.get_group(), camera.bind_group()])
-                    .model(&m),
-            )
-            .render_stage(
-                1,
                 RenderStage::new(&hdr_pipeline)
                     .color_attachments_builder(
                         ColorAttachmentBuilder::new()
@@ -364,25 +336,110 @@ impl RenderWorker for SimpleModelRender {
                     .bind_groups(vec![hdr_bind_group])
                     .instances(0..1)
                     .entities(0..3),
-            );
+            ),
+        )
+        .read("HDR texture")
+        .write("Surface");
+
+        let scene_pass = GraphPass::new(
+            "Scene pass",
+            w.render_pass().label("Scene pass").render_stage(
+                0,
+                RenderStage::new(&pipeline)
+                    .depth_stencil_builder(
+                        DepthStencilAttachmentBuilder::new()
+                            .label("Some depth attach")
+                            .view(d_t_view)
+                            .depth_ops(wgpu::Operations {
+                                load: wgpu::LoadOp::Clear(1.0),
+                                store: wgpu::StoreOp::Store,
+                            }),
+                    )
+                    .color_attachments_builder(hdr_attachment.label("Some color attach").ops(
+                        wgpu::Operations {
+                            load: wgpu::LoadOp::Clear(wgpu::Color {
+                                r: 0.1,
+                                g: 0.2,
+                                b: 0.3,
+                                a: 1.0,
+                            }),
+                            store: wgpu::StoreOp::Store,
+                        },
+                    ))
+                    .entities(0..1)
+                    .instances(0..30)
+                    .vertex_buffer(&vb)
+                    .bind_groups(vec![c.get_group(), camera.bind_group(), ibl.bind_group()])
+                    .model(&m),
+            ),
+        )
+        .reads(vec!["Light", "Camera"])
+        .writes(vec!["HDR texture", "Depth texture"]);
 
-        w.render(r_p)?;
+        w.render_graph(vec![hdr_pass, scene_pass])?;
         w.present()?;
 
         Ok(())
     }
 
-    fn update(
-        &mut self,
-        w: &mut Worker<'_>,
-        event: &WindowEvent,
-        dt: Duration,
-    ) -> Result<(), CoreError> {
-        self.camera.as_mut().unwrap().update(w, event, dt)?;
-        self.light.update(event, dt);
+    fn input(&mut self, _: &mut Worker<'_>, event: &WindowEvent) -> Result<(), CoreError> {
+        self.camera.as_mut().unwrap().input(event);
+        self.light.input(event);
+
+        Ok(())
+    }
+
+    fn update(&mut self, w: &mut Worker<'_>, dt: Duration) -> Result<(), CoreError> {
+        self.camera.as_mut().unwrap().update(w, dt)?;
+        self.light.update(dt);
 
         w.update_uniform(self.c_id, "Light", &[self.light.data()])?;
 
         Ok(())
     }
 }
+
+// Faces of the sky gradient, which lights the model without the `.hdr` environment
+fn environment_faces() -> Result<Vec<Vec<u8>>, CoreError> {
+    let zenith = Vector3::new(0.3, 0.5, 1.2);
+    let horizon = Vector3::new(1.0, 0.95, 0.9);
+    let ground = Vector3::new(0.25, 0.2, 0.15);
+
+    (0..6)
+        .map(|face| {
+            let texels = (0..ENVIRONMENT_SIZE * ENVIRONMENT_SIZE)
+                .map(|i| {
+                    let (x, y) = (i % ENVIRONMENT_SIZE, i / ENVIRONMENT_SIZE);
+                    let u = (x as f32 + 0.5) / ENVIRONMENT_SIZE as f32 * 2.0 - 1.0;
+                    let v = (y as f32 + 0.5) / ENVIRONMENT_SIZE as f32 * 2.0 - 1.0;
+                    // Face order is `+X`, `-X`, `+Y`, `-Y`, `+Z`, `-Z`
+                    let direction = match face {
+                        0 => Vector3::new(1.0, -v, -u),
+                        1 => Vector3::new(-1.0, -v, u),
+                        2 => Vector3::new(u, 1.0, v),
+                        3 => Vector3::new(u, -1.0, -v),
+                        4 => Vector3::new(u, -v, 1.0),
+                        _ => Vector3::new(-u, -v, -1.0),
+                    };
+                    let up = direction.normalize().y;
+                    let color = if up > 0.0 {
+                        horizon.lerp(zenith, up)
+                    } else {
+                        ground
+                    };
+
+                    Rgb(color.into())
+                })
+                .collect::<Vec<Rgb<f32>>>();
+
+            let mut hdr = vec![];
+            HdrEncoder::new(&mut hdr).encode(
+                &texels,
+                ENVIRONMENT_SIZE as usize,
+                ENVIRONMENT_SIZE as usize,
+            )?;
+
+            Ok(hdr)
+        })
+        .collect()
+}
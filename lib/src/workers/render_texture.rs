@@ -3,9 +3,13 @@ use std::{fs::read, path::Path};
 use anyhow::Result;
 
 use custom_engine_core::{
+    buffer::Buffer,
+    context::Handle,
     errors::CoreError,
+    pipeline::Pipeline,
     render_pass::color_attachment::ColorAttachmentBuilder,
     render_pass::RenderStage,
+    texture::RenderTexture,
     traits::{Builder, RenderWorker, VertexLayout},
     worker::Worker,
 };
@@ -57,9 +61,9 @@ const VERTICES_POS: &[VertexPos] = &[
 
 #[derive(Debug, Default)]
 pub struct SimpleRenderTexture {
-    vb_id: usize,
-    p_id: usize,
-    rt_id: usize,
+    vb_id: Handle<Buffer>,
+    p_id: Handle<Pipeline>,
+    rt_id: Handle<RenderTexture>,
 }
 
 impl RenderWorker for SimpleRenderTexture {
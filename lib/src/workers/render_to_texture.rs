@@ -1,7 +1,10 @@
 use anyhow::Result;
 
 use custom_engine_core::{
+    buffer::Buffer,
+    context::Handle,
     errors::CoreError,
+    pipeline::Pipeline,
     render_pass::{color_attachment::ColorAttachmentBuilder, RenderStage},
     runtime::ImageFormat,
     traits::{Builder, RenderWorker, VertexLayout},
@@ -37,8 +40,8 @@ const VERTICES: &[Vertex] = &[
 
 #[derive(Debug, Default)]
 pub struct SimpleRenderToTexture {
-    vb_id: usize,
-    p_id: usize,
+    vb_id: Handle<Buffer>,
+    p_id: Handle<Pipeline>,
 }
 
 impl RenderWorker for SimpleRenderToTexture {
@@ -3,11 +3,14 @@ use cgmath::Vector3;
 
 use custom_engine_components::object::triangle::{Triangle, Triangles};
 use custom_engine_core::{
+    buffer::Buffer,
+    context::Handle,
     errors::CoreError,
+    pipeline::Pipeline,
     render_pass::color_attachment::ColorAttachmentBuilder,
     render_pass::RenderStage,
     traits::{Builder, RenderWorker},
-    uniform::UniformDescription,
+    uniform::{UniformDescription, Uniforms},
     worker::Worker,
 };
 
@@ -29,9 +32,9 @@ pub struct SimpleRender {
     counter: usize,
     data: Triangles,
 
-    vb_id: usize,
-    p_id: usize,
-    c_id: usize,
+    vb_id: Handle<Buffer>,
+    p_id: Handle<Pipeline>,
+    c_id: Handle<Uniforms>,
 }
 
 impl SimpleRender {